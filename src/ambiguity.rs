@@ -0,0 +1,187 @@
+//! Every reading of an expression whose input could mean more than one thing, so callers
+//! can ask the user instead of guessing.
+
+use crate::{parse, token, DateMath, PeriodOp, TokenKind};
+use chrono::NaiveDate;
+use nom::combinator::all_consuming;
+
+// Each ambiguous date doubles the readings, so only the first few are varied.
+const MAX_AMBIGUOUS_DATES: usize = 4;
+
+/// One way of reading an expression.
+#[derive(Debug, PartialEq)]
+pub struct Interpretation {
+    pub math: DateMath,
+    /// How each ambiguous part of the input was read, e.g. "5/4/2021 as May 4, 2021"
+    pub readings: Vec<String>,
+}
+
+/// Parses an expression every way it can be read. Dates like "5/4/2021" are read both
+/// month first and day first, and periods subtracted from a date, as in "Mar 1 - 2 days",
+/// are also read as the difference between that date and the one the periods reach from
+/// today. The reading `parse` uses comes first. Input that doesn't parse has no
+/// interpretations.
+pub fn parse_all(input: &str) -> Vec<Interpretation> {
+    let tokens = token::tokenize(input);
+    // "2 weeks ago" subtracts too, but there's no second date it could be read as.
+    let subtracts = tokens
+        .iter()
+        .any(|token| token.kind == TokenKind::Operator && token.text == "-");
+    let dates: Vec<_> = tokens
+        .into_iter()
+        .filter(|token| token.kind == TokenKind::Date)
+        .filter_map(|token| Some((token.span.clone(), ambiguous_date(token.text)?)))
+        .take(MAX_AMBIGUOUS_DATES)
+        .collect();
+
+    (0..1 << dates.len())
+        .filter_map(|choices: usize| {
+            let mut text = input.to_string();
+            let mut readings = vec![];
+
+            // Replaced from the end, so earlier spans stay valid.
+            for (index, (span, readings_of_date)) in dates.iter().enumerate().rev() {
+                let date = readings_of_date[(choices >> index) & 1];
+
+                text.replace_range(span.clone(), &date.format("%m/%d/%Y").to_string());
+                readings.push(format!(
+                    "{} as {}",
+                    &input[span.clone()],
+                    date.format("%B %-d, %Y")
+                ));
+            }
+            readings.reverse();
+
+            let (_, math) = all_consuming(parse)(&text).ok()?;
+            Some(Interpretation { math, readings })
+        })
+        .flat_map(|interpretation| {
+            if subtracts {
+                subtraction_readings(interpretation)
+            } else {
+                vec![interpretation]
+            }
+        })
+        .collect()
+}
+
+// A date minus periods, read both as the subtraction and as a difference of dates.
+fn subtraction_readings(interpretation: Interpretation) -> Vec<Interpretation> {
+    let (date, first, rest) = match &interpretation.math {
+        DateMath::StartWithPeriods(date, PeriodOp::Subtract(first), rest) => {
+            (date.clone(), *first, rest.clone())
+        }
+        _ => return vec![interpretation],
+    };
+    let periods = DateMath::Periods(first, rest);
+    let written = periods.to_canonical_string();
+    let mut difference = Interpretation {
+        math: DateMath::Compare(Box::new(DateMath::Start(date)), Box::new(periods)),
+        readings: interpretation.readings.clone(),
+    };
+    let mut subtraction = interpretation;
+
+    subtraction
+        .readings
+        .push(format!("'-' as subtracting {}", written));
+    difference
+        .readings
+        .push(format!("'-' as the difference from {} from today", written));
+
+    vec![subtraction, difference]
+}
+
+// A slash-separated date valid both month first and day first, e.g. "5/4/2021".
+fn ambiguous_date(text: &str) -> Option<[NaiveDate; 2]> {
+    let mut parts = text.split('/');
+    let first = parts.next()?.parse().ok()?;
+    let second = parts.next()?.parse().ok()?;
+    let year = parts.next()?.parse().ok()?;
+
+    if first == second || parts.next().is_some() {
+        return None;
+    }
+
+    Some([
+        NaiveDate::from_ymd_opt(year, first, second)?,
+        NaiveDate::from_ymd_opt(year, second, first)?,
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CalculatedDate, ComputeOutcome};
+
+    #[test]
+    fn test_ambiguous_date() {
+        let interpretations = parse_all("5/4/2021");
+
+        assert_eq!(
+            interpretations,
+            vec![
+                Interpretation {
+                    math: DateMath::Start(CalculatedDate::Raw(NaiveDate::from_ymd(2021, 5, 4))),
+                    readings: vec!["5/4/2021 as May 4, 2021".to_string()],
+                },
+                Interpretation {
+                    math: DateMath::Start(CalculatedDate::Raw(NaiveDate::from_ymd(2021, 4, 5))),
+                    readings: vec!["5/4/2021 as April 5, 2021".to_string()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_several_ambiguous_dates() {
+        let interpretations = parse_all("5/4/2021 - 3/2/2021");
+
+        assert_eq!(interpretations.len(), 4);
+        assert_eq!(
+            interpretations[3].readings,
+            vec!["5/4/2021 as April 5, 2021", "3/2/2021 as February 3, 2021"]
+        );
+    }
+
+    #[test]
+    fn test_difference_or_subtraction() {
+        let interpretations = parse_all("Mar 1, 2022 - 2 days");
+        let today = NaiveDate::from_ymd(2022, 3, 10);
+
+        assert_eq!(interpretations.len(), 2);
+        assert_eq!(
+            interpretations[0].math,
+            parse("Mar 1, 2022 - 2 days").unwrap().1
+        );
+        assert_eq!(
+            interpretations[0].readings,
+            vec!["'-' as subtracting 2 days"]
+        );
+        assert_eq!(
+            interpretations[1].math.compute(today),
+            ComputeOutcome::Comparison(
+                NaiveDate::from_ymd(2022, 3, 1),
+                NaiveDate::from_ymd(2022, 3, 12)
+            )
+        );
+        assert_eq!(
+            interpretations[1].readings,
+            vec!["'-' as the difference from 2 days from today"]
+        );
+        assert_eq!(parse_all("5/4/2021 - 1 week + 2 days").len(), 4);
+    }
+
+    #[test]
+    fn test_unambiguous_input() {
+        assert_eq!(parse_all("5/13/2021").len(), 1);
+        assert_eq!(parse_all("5/5/2021").len(), 1);
+        assert_eq!(
+            parse_all("2 weeks ago"),
+            vec![Interpretation {
+                math: parse("2 weeks ago").unwrap().1,
+                readings: vec![],
+            }]
+        );
+        assert!(parse_all("soonish").is_empty());
+    }
+}
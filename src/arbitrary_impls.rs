@@ -0,0 +1,357 @@
+//! `Arbitrary` implementations for property testing code that stores or transforms
+//! expressions. Generated values are always well formed: dates fall between 1900 and
+//! 2100 and counts stay small enough to compute.
+
+use crate::{
+    config::DiffFormat,
+    cron,
+    grafana::{Endpoint, TimeRange},
+    month_days::MonthDays,
+    splunk::{Modifier, TimeUnit},
+    CalculatedDate, Conversion, DateMath, DayKind, Edge, JqlDate, MonthOf, Period, PeriodOp,
+    PeriodOps, Predicate, Quantity, Span, SplunkTime, Unit,
+};
+use ::arbitrary::{Arbitrary, Result, Unstructured};
+use chrono::{NaiveDate, NaiveDateTime, Weekday};
+
+fn date(u: &mut Unstructured) -> Result<NaiveDate> {
+    let year = u.int_in_range(1900..=2100)?;
+    let ordinal = u.int_in_range(1..=365)?;
+
+    Ok(NaiveDate::from_yo(year, ordinal))
+}
+
+fn count(u: &mut Unstructured) -> Result<usize> {
+    u.int_in_range(0..=100)
+}
+
+fn weekday(u: &mut Unstructured) -> Result<Weekday> {
+    Ok(*u.choose(&[
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+        Weekday::Sat,
+        Weekday::Sun,
+    ])?)
+}
+
+fn period_ops(u: &mut Unstructured) -> Result<PeriodOps> {
+    let length = u.int_in_range(0..=3)?;
+
+    (0..length).map(|_| PeriodOp::arbitrary(u)).collect()
+}
+
+fn month_days(u: &mut Unstructured) -> Result<MonthDays> {
+    let length = u.int_in_range(1..=3)?;
+
+    Ok(MonthDays::new(
+        (0..length)
+            .map(|_| u.int_in_range(1..=31))
+            .collect::<Result<_>>()?,
+    ))
+}
+
+fn schedule(u: &mut Unstructured) -> Result<cron::CronSchedule> {
+    let minute: u32 = u.int_in_range(0..=59)?;
+    let hour: u32 = u.int_in_range(0..=23)?;
+    let day_of_week = u.choose(&["*", "MON", "1-5", "SAT,SUN"])?;
+    let spec = format!("{} {} * * {}", minute, hour, day_of_week);
+
+    Ok(cron::parse_schedule(&spec)
+        .expect("generated cron schedules are valid")
+        .1)
+}
+
+// The first variant of each recursive type is a leaf, so generation bottoms out once the
+// input runs dry.
+
+impl<'a> Arbitrary<'a> for Quantity {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(*u.choose(&[Quantity::Couple, Quantity::Few])?)
+    }
+}
+
+impl<'a> Arbitrary<'a> for Unit {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(*u.choose(&[Unit::Day, Unit::Week, Unit::Month, Unit::Year])?)
+    }
+}
+
+impl<'a> Arbitrary<'a> for Period {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0..=5)? {
+            0 => Period::Day(count(u)?),
+            1 => Period::Week(count(u)?),
+            2 => Period::Month(count(u)?),
+            3 => Period::Year(count(u)?),
+            4 => Period::FractionalMonths(u.int_in_range(0..=100_000)?),
+            _ => Period::Approximately(Quantity::arbitrary(u)?, Unit::arbitrary(u)?),
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for PeriodOp {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(if u.arbitrary()? {
+            PeriodOp::Subtract(Period::arbitrary(u)?)
+        } else {
+            PeriodOp::Add(Period::arbitrary(u)?)
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for Edge {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(*u.choose(&[Edge::Start, Edge::End])?)
+    }
+}
+
+impl<'a> Arbitrary<'a> for Span {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0..=3)? {
+            0 => Span::Month,
+            1 => Span::Year,
+            2 => Span::Week,
+            _ if u.arbitrary()? => Span::Quarter(Some(u.int_in_range(1..=4)?)),
+            _ => Span::Quarter(None),
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for JqlDate {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let units = [Unit::Day, Unit::Week, Unit::Month, Unit::Year];
+        let operation = |u: &mut Unstructured<'a>, unit: Unit| -> Result<PeriodOp> {
+            let period = unit.of(u.int_in_range(0..=24)?);
+
+            Ok(if u.arbitrary()? {
+                PeriodOp::Add(period)
+            } else {
+                PeriodOp::Subtract(period)
+            })
+        };
+
+        if u.arbitrary()? {
+            return Ok(JqlDate::Relative(operation(u, Unit::Day)?));
+        }
+
+        let (edge, unit) = (Edge::arbitrary(u)?, *u.choose(&units)?);
+        let increment = if u.arbitrary()? {
+            let unit = *u.choose(&units)?;
+            Some(operation(u, unit)?)
+        } else {
+            None
+        };
+
+        Ok(JqlDate::Function(edge, unit, increment))
+    }
+}
+
+const TIME_UNITS: [TimeUnit; 8] = [
+    TimeUnit::Second,
+    TimeUnit::Minute,
+    TimeUnit::Hour,
+    TimeUnit::Day,
+    TimeUnit::Week,
+    TimeUnit::Month,
+    TimeUnit::Quarter,
+    TimeUnit::Year,
+];
+
+impl<'a> Arbitrary<'a> for SplunkTime {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut modifiers = vec![];
+
+        for _ in 0..u.int_in_range(1..=3)? {
+            modifiers.push(match u.int_in_range(0..=2)? {
+                0 => Modifier::Offset(u.int_in_range(-100..=100)?, *u.choose(&TIME_UNITS)?),
+                1 => Modifier::Snap(*u.choose(&TIME_UNITS)?),
+                _ => Modifier::SnapToWeekday(weekday(u)?),
+            });
+        }
+
+        Ok(SplunkTime(modifiers))
+    }
+}
+
+impl<'a> Arbitrary<'a> for TimeRange {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let endpoint = |u: &mut Unstructured<'a>| -> Result<Endpoint> {
+            if u.ratio(1, 4)? {
+                return Ok(Endpoint::Date(date(u)?));
+            }
+
+            // Epoch times are written without a sign, so they start in 1970.
+            if u.ratio(1, 4)? {
+                return Ok(Endpoint::Epoch(NaiveDateTime::from_timestamp(
+                    u.int_in_range(0..=4_102_444_800)?,
+                    0,
+                )));
+            }
+
+            let mut modifiers = vec![];
+
+            for _ in 0..u.int_in_range(0..=2)? {
+                modifiers.push(if u.arbitrary()? {
+                    Modifier::Offset(u.int_in_range(-100..=100)?, *u.choose(&TIME_UNITS)?)
+                } else {
+                    Modifier::Snap(*u.choose(&TIME_UNITS)?)
+                });
+            }
+
+            Ok(Endpoint::Now(modifiers))
+        };
+
+        Ok(TimeRange {
+            from: endpoint(u)?,
+            to: endpoint(u)?,
+            length: u.arbitrary()?,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for CalculatedDate {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0..=17)? {
+            0 => CalculatedDate::Today,
+            1 => CalculatedDate::Yesterday,
+            2 => CalculatedDate::Tomorrow,
+            3 => CalculatedDate::NextMonthDay(u.int_in_range(1..=31)?),
+            4 => CalculatedDate::PreviousMonthDay(u.int_in_range(1..=31)?),
+            5 => CalculatedDate::NextAnniversary(date(u)?),
+            6 => CalculatedDate::Boundary(Edge::arbitrary(u)?, Span::arbitrary(u)?),
+            7 => CalculatedDate::FirstBusinessDayAfter(Box::new(Self::arbitrary(u)?)),
+            8 => CalculatedDate::LastBusinessDayBefore(Box::new(Self::arbitrary(u)?)),
+            9 => {
+                let days = u.int_in_range(0..=10)?;
+                CalculatedDate::Settlement(days, Box::new(Self::arbitrary(u)?))
+            }
+            10 => CalculatedDate::UpcomingWeekday(weekday(u)?),
+            11 => CalculatedDate::NextMonth(u.int_in_range(1..=12)?),
+            12 => CalculatedDate::PreviousMonth(u.int_in_range(1..=12)?),
+            13 => CalculatedDate::WeekdayOfWeek(weekday(u)?, u.int_in_range(-1..=1)?),
+            14 => {
+                let nth = *u.choose(&[-1, 1, 2, 3, 10, 25])?;
+                let month = if u.arbitrary()? {
+                    MonthOf::Relative(u.int_in_range(-1..=1)?)
+                } else {
+                    MonthOf::Named(u.int_in_range(1..=12)?)
+                };
+
+                CalculatedDate::BusinessDayOfMonth(nth, month)
+            }
+            15 => CalculatedDate::Jql(JqlDate::arbitrary(u)?),
+            16 => u
+                .choose(&[CalculatedDate::NextWeekend, CalculatedDate::PreviousWeekend])?
+                .clone(),
+            _ => CalculatedDate::Raw(date(u)?),
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for Predicate {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0..=5)? {
+            0 => Predicate::Before(DateMath::arbitrary(u)?),
+            1 => Predicate::OnOrBefore(DateMath::arbitrary(u)?),
+            2 => Predicate::On(DateMath::arbitrary(u)?),
+            3 => Predicate::OnOrAfter(DateMath::arbitrary(u)?),
+            4 => Predicate::After(DateMath::arbitrary(u)?),
+            _ => Predicate::Between(DateMath::arbitrary(u)?, DateMath::arbitrary(u)?),
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for DateMath {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0..=20)? {
+            0 => DateMath::Start(CalculatedDate::arbitrary(u)?),
+            1 => DateMath::Periods(Period::arbitrary(u)?, period_ops(u)?),
+            2 => DateMath::StartWithPeriods(
+                CalculatedDate::arbitrary(u)?,
+                PeriodOp::arbitrary(u)?,
+                period_ops(u)?,
+            ),
+            3 => DateMath::DateDiff(u.arbitrary()?, u.arbitrary()?),
+            4 => DateMath::Schedule(
+                schedule(u)?,
+                CalculatedDate::arbitrary(u)?,
+                u.int_in_range(1..=5)?,
+            ),
+            5 => DateMath::Age(CalculatedDate::arbitrary(u)?, date(u)?),
+            6 => DateMath::WeekdayOf(Box::new(Self::arbitrary(u)?)),
+            7 => DateMath::Compare(u.arbitrary()?, u.arbitrary()?),
+            8 => DateMath::Is(
+                Box::new(Self::arbitrary(u)?),
+                Box::new(Predicate::arbitrary(u)?),
+            ),
+            9 => DateMath::Elapsed(Span::arbitrary(u)?, CalculatedDate::arbitrary(u)?),
+            10 => DateMath::DaysLeft(Span::arbitrary(u)?),
+            11 => DateMath::MonthDays(month_days(u)?, u.arbitrary::<Option<u8>>()?.map(u32::from)),
+            12 => DateMath::DaysBetween(
+                *u.choose(&[DayKind::Weekday, DayKind::Weekend])?,
+                u.arbitrary()?,
+                u.arbitrary()?,
+            ),
+            13 => DateMath::DaysRemainingInCycle,
+            14 => DateMath::FractionOfCycleRemaining,
+            15 => DateMath::Splunk(SplunkTime::arbitrary(u)?),
+            16 => DateMath::TimeRange(TimeRange::arbitrary(u)?),
+            17 => DateMath::Convert(
+                Box::new(DateMath::Periods(
+                    if u.arbitrary()? {
+                        Period::Day(u.int_in_range(0..=1000)?)
+                    } else {
+                        Period::Week(u.int_in_range(0..=100)?)
+                    },
+                    Default::default(),
+                )),
+                *u.choose(&[
+                    Conversion::Days,
+                    Conversion::Weeks,
+                    Conversion::WeeksAndDays,
+                ])?,
+            ),
+            18 => DateMath::DaysIn(Box::new(DateMath::StartWithPeriods(
+                CalculatedDate::arbitrary(u)?,
+                PeriodOp::arbitrary(u)?,
+                period_ops(u)?,
+            ))),
+            _ => DateMath::DiffIn(
+                Box::new(DateMath::DateDiff(
+                    CalculatedDate::arbitrary(u)?,
+                    CalculatedDate::arbitrary(u)?,
+                )),
+                *u.choose(&[
+                    DiffFormat::Days,
+                    DiffFormat::Auto,
+                    DiffFormat::WeeksAndDays,
+                    DiffFormat::Months,
+                ])?,
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Config;
+
+    #[test]
+    fn test_generated_expressions_compute() {
+        let today = NaiveDate::from_ymd(2022, 1, 31);
+        let config = Config::default();
+        // A fixed, varied byte stream, so the test is deterministic.
+        let bytes: Vec<u8> = (0..4096u32)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8)
+            .collect();
+
+        for chunk in bytes.chunks(64) {
+            let math = DateMath::arbitrary(&mut Unstructured::new(chunk)).unwrap();
+            let _ = math.try_compute_with_config(today, &config);
+        }
+    }
+}
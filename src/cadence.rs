@@ -0,0 +1,225 @@
+//! Release cadences, like "every 6 weeks from 2022-01-11" or "first Tuesday of every
+//! month", defined by name in the configuration and reached with "next <name>".
+
+use crate::{
+    calculated_date, date_utils, month_days, parse_whole, parser_utils::*, period, Config,
+    ParseError, Period, Step,
+};
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::alpha1,
+    combinator::{map, map_opt, value, verify},
+    sequence::{pair, preceded, separated_pair, terminated},
+    IResult,
+};
+use std::{convert::TryFrom, str::FromStr};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Cadence {
+    /// A period repeated from a first date, e.g. "every 6 weeks from 2022-01-11"
+    Every(Period, NaiveDate),
+    /// A weekday of every month, counting back from the end when negative, e.g. "first
+    /// Tuesday of every month" is `WeekdayOfMonth(1, Weekday::Tue)`
+    WeekdayOfMonth(i32, Weekday),
+}
+
+impl Cadence {
+    /// The first date of the cadence after `date`, or `None` when it's past the dates
+    /// supported.
+    pub fn next_after(&self, date: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Cadence::Every(_, first) if date < *first => Some(*first),
+            Cadence::Every(period, first) => match period.to_step(&Config::default()).ok()? {
+                // Cadences only parse from calendar periods, so business days are read as
+                // plain days.
+                Step::Days(days) | Step::BusinessDays(days) => {
+                    let days = i64::try_from(days.max(1)).ok()?;
+                    let steps = (date - *first).num_days() / days + 1;
+
+                    date_utils::add_days(*first, steps.checked_mul(days)?)
+                }
+                Step::Months(months) => every_months(*first, months, date),
+                Step::Years(years) => every_months(*first, years.saturating_mul(12), date),
+            },
+            // A fifth weekday is missing from most months, but turns up within a few.
+            Cadence::WeekdayOfMonth(nth, weekday) => {
+                month_days::monthly(date, 12, |year, month| {
+                    weekday_of_month(year, month, *nth, *weekday)
+                })?
+                .into_iter()
+                .next()
+            }
+        }
+    }
+}
+
+impl FromStr for Cadence {
+    type Err = ParseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        parse_whole(value, parse)
+    }
+}
+
+// Each date is counted from the first rather than the one before it, so a short month
+// doesn't pull later dates earlier.
+fn every_months(first: NaiveDate, months: usize, date: NaiveDate) -> Option<NaiveDate> {
+    let months = months.max(1) as i64;
+    let steps = date_utils::whole_months_between(first, date) as i64 / months;
+
+    (steps..)
+        .map(|step| date_utils::add_months(first, step.checked_mul(months)?))
+        .find(|next| !matches!(next, Some(next) if *next <= date))?
+}
+
+fn weekday_of_month(year: i32, month: u32, nth: i32, weekday: Weekday) -> Option<NaiveDate> {
+    let weeks = Duration::weeks(nth.unsigned_abs().saturating_sub(1).into());
+    let last = NaiveDate::from_ymd(year, month, date_utils::days_in_month(year, month));
+    let day = if nth > 0 {
+        date_utils::upcoming_weekday(NaiveDate::from_ymd(year, month, 1), weekday) + weeks
+    } else {
+        date_utils::upcoming_weekday(last - Duration::days(6), weekday) - weeks
+    };
+
+    Some(day).filter(|day| day.month() == month)
+}
+
+/// Parses "every 6 weeks from 2022-01-11" and "first tuesday of every month".
+pub(crate) fn parse(input: &str) -> IResult<&str, Cadence> {
+    alt((
+        map(
+            preceded(
+                tag("every "),
+                separated_pair(
+                    alt((
+                        verify(period::parse, |period| {
+                            matches!(
+                                period,
+                                Period::Day(1..)
+                                    | Period::Week(1..)
+                                    | Period::Month(1..)
+                                    | Period::Year(1..)
+                            )
+                        }),
+                        value(Period::Day(1), tag("day")),
+                        value(Period::Week(1), tag("week")),
+                        value(Period::Month(1), tag("month")),
+                        value(Period::Year(1), tag("year")),
+                    )),
+                    alt((tag(" from "), tag(" starting "))),
+                    calculated_date::parse_literal,
+                ),
+            ),
+            |(period, first)| Cadence::Every(period, first),
+        ),
+        map(
+            terminated(
+                pair(
+                    terminated(
+                        alt((
+                            value(1, tag("first")),
+                            value(2, tag("second")),
+                            value(3, tag("third")),
+                            value(4, tag("fourth")),
+                            value(-1, tag("last")),
+                            map(verify(parse_day_ordinal, |nth| *nth <= 5), |nth| nth as i32),
+                        )),
+                        tag(" "),
+                    ),
+                    map_opt(alpha1, |name: &str| name.parse().ok()),
+                ),
+                tag(" of every month"),
+            ),
+            |(nth, weekday)| Cadence::WeekdayOfMonth(nth, weekday),
+        ),
+    ))(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn next(cadence: &str, date: NaiveDate) -> Option<NaiveDate> {
+        cadence.parse::<Cadence>().unwrap().next_after(date)
+    }
+
+    #[test]
+    fn test_every() {
+        let cadence = "every 6 weeks from 2022-01-11";
+
+        assert_eq!(
+            next(cadence, NaiveDate::from_ymd(2021, 12, 1)),
+            Some(NaiveDate::from_ymd(2022, 1, 11))
+        );
+        assert_eq!(
+            next(cadence, NaiveDate::from_ymd(2022, 1, 11)),
+            Some(NaiveDate::from_ymd(2022, 2, 22))
+        );
+        assert_eq!(
+            next(cadence, NaiveDate::from_ymd(2022, 3, 1)),
+            Some(NaiveDate::from_ymd(2022, 4, 5))
+        );
+        assert_eq!(
+            next(
+                "every month starting 2022-01-31",
+                NaiveDate::from_ymd(2022, 3, 1)
+            ),
+            Some(NaiveDate::from_ymd(2022, 3, 31))
+        );
+        assert_eq!(
+            next(
+                "every 2 years from 2020-02-29",
+                NaiveDate::from_ymd(2021, 1, 1)
+            ),
+            Some(NaiveDate::from_ymd(2022, 2, 28))
+        );
+        assert_eq!(
+            next(
+                "every 99999999999 weeks from 2022-01-01",
+                NaiveDate::from_ymd(2022, 3, 1)
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_weekday_of_month() {
+        assert_eq!(
+            next(
+                "first tuesday of every month",
+                NaiveDate::from_ymd(2022, 3, 1)
+            ),
+            Some(NaiveDate::from_ymd(2022, 4, 5))
+        );
+        assert_eq!(
+            next(
+                "last Friday of every month",
+                NaiveDate::from_ymd(2022, 3, 1)
+            ),
+            Some(NaiveDate::from_ymd(2022, 3, 25))
+        );
+        assert_eq!(
+            next("5th monday of every month", NaiveDate::from_ymd(2022, 2, 1)),
+            Some(NaiveDate::from_ymd(2022, 5, 30))
+        );
+        assert_eq!(
+            next(
+                "first monday of every month",
+                NaiveDate::from_ymd(9999, 12, 10)
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_rejects_empty_periods() {
+        assert!("every 0 weeks from 2022-01-11".parse::<Cadence>().is_err());
+        assert!("every 1.5 months from 2022-01-11"
+            .parse::<Cadence>()
+            .is_err());
+        assert!("6th monday of every month".parse::<Cadence>().is_err());
+    }
+}
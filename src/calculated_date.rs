@@ -1,71 +1,783 @@
-use crate::parser_utils::*;
-use chrono::{format, Datelike, Duration, NaiveDate};
+#[cfg(feature = "cli")]
+use crate::syntax::Form;
+use crate::{
+    config::{Config, ShortMonthPolicy},
+    date_utils, holidays,
+    jql::{self, JqlDate},
+    parser_utils::*,
+    token, ComputeError,
+};
+use chrono::{format, Datelike, Duration, Month, NaiveDate, Weekday};
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take_till},
-    combinator::{map, map_opt, value},
-    sequence::{terminated, tuple},
+    bytes::complete::{tag, take_till, take_while1},
+    character::complete::{alpha1, one_of, space1},
+    combinator::{map, map_opt, opt, value, verify},
+    sequence::{delimited, pair, preceded, terminated, tuple},
     IResult,
 };
+use std::convert::TryFrom;
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CalculatedDate {
     Today,
     Yesterday,
     Tomorrow,
+    NextMonthDay(u32),
+    PreviousMonthDay(u32),
+    /// The first day of the given month after the current one, e.g. "next March"
+    NextMonth(u32),
+    /// The first day of the given month before the current one, e.g. "last March"
+    PreviousMonth(u32),
+    NextAnniversary(NaiveDate),
+    Boundary(Edge, Span),
+    FirstBusinessDayAfter(Box<CalculatedDate>),
+    LastBusinessDayBefore(Box<CalculatedDate>),
+    Settlement(usize, Box<CalculatedDate>),
+    /// The nth business day of a month, counting back from its end when negative, e.g.
+    /// "3rd business day of next month" is `BusinessDayOfMonth(3, MonthOf::Relative(1))`
+    BusinessDayOfMonth(i32, MonthOf),
+    UpcomingWeekday(Weekday),
+    /// The first day of the weekend after the current or most recent one, e.g. "next
+    /// weekend"
+    NextWeekend,
+    /// The first day of the weekend before the current or most recent one, e.g. "last
+    /// weekend"
+    PreviousWeekend,
+    /// A day of a week relative to the current one, e.g. "Tuesday of next week" is
+    /// `WeekdayOfWeek(Weekday::Tue, 1)`
+    WeekdayOfWeek(Weekday, i32),
+    /// The first or last day of a term defined in the configuration, e.g. "start of fall
+    /// term"
+    Term(Edge, String),
+    /// The nth billing date after today, on the configured billing day, e.g. "billing
+    /// date in 3 cycles" is `BillingDate(3)`
+    BillingDate(usize),
+    /// The first or last day of a numbered sprint, counting the configured first sprint
+    /// as 1, e.g. "sprint 7 start" is `Sprint(Edge::Start, 7)`
+    Sprint(Edge, usize),
+    /// The next date of a cadence defined in the configuration, e.g. "next release"
+    NextOccurrence(String),
+    /// A Jira JQL date function or relative date, e.g. "startOfMonth(-1)" or "-7d"
+    Jql(JqlDate),
+    /// A month and day in the current year, e.g. "Mar 1" is `MonthAndDay(3, 1)`
+    MonthAndDay(u32, u32),
     Raw(NaiveDate),
 }
 
+/// A month relative to the current one, or named within the current year.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MonthOf {
+    Relative(i32),
+    Named(u32),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Edge {
+    Start,
+    End,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Span {
+    /// A week starting on the configured day
+    Week,
+    Month,
+    Year,
+    /// A quarter of the year, either a specific one or the one containing the date
+    Quarter(Option<u32>),
+}
+
 impl CalculatedDate {
-    pub fn calculate(&self, today: NaiveDate) -> NaiveDate {
+    pub fn raw(date: NaiveDate) -> CalculatedDate {
+        CalculatedDate::Raw(date)
+    }
+
+    pub fn today() -> CalculatedDate {
+        CalculatedDate::Today
+    }
+
+    /// The first or last day of the week, month, quarter, or year containing today.
+    pub fn boundary(edge: Edge, span: Span) -> CalculatedDate {
+        CalculatedDate::Boundary(edge, span)
+    }
+
+    pub fn first_business_day_after(date: CalculatedDate) -> CalculatedDate {
+        CalculatedDate::FirstBusinessDayAfter(Box::new(date))
+    }
+
+    pub fn last_business_day_before(date: CalculatedDate) -> CalculatedDate {
+        CalculatedDate::LastBusinessDayBefore(Box::new(date))
+    }
+
+    /// The date `days` business days after `date`, e.g. "T+2". Counts reaching past the
+    /// supported years are reported as out of range rather than stepped through.
+    pub fn settlement(days: usize, date: CalculatedDate) -> CalculatedDate {
+        CalculatedDate::Settlement(days, Box::new(date))
+    }
+
+    /// The first or last day of a term defined with `Config::term`.
+    pub fn term(edge: Edge, name: &str) -> CalculatedDate {
+        CalculatedDate::Term(edge, name.to_lowercase())
+    }
+
+    /// The date itself, when it was written out in full.
+    pub fn literal(&self) -> Option<NaiveDate> {
+        match self {
+            CalculatedDate::Raw(date) => Some(*date),
+            _ => None,
+        }
+    }
+
+    /// The date business days are counted from, for dates defined by another.
+    pub fn anchor(&self) -> Option<&CalculatedDate> {
         match self {
+            CalculatedDate::FirstBusinessDayAfter(date)
+            | CalculatedDate::LastBusinessDayBefore(date)
+            | CalculatedDate::Settlement(_, date) => Some(date),
+            _ => None,
+        }
+    }
+
+    /// The date this stands for on `today`, or why it has none, e.g. an undefined term.
+    pub fn calculate(&self, today: NaiveDate, config: &Config) -> Result<NaiveDate, ComputeError> {
+        Ok(match self {
             CalculatedDate::Raw(v) => *v,
+            // February 29 lands where the leap day policy says in years without one.
+            CalculatedDate::MonthAndDay(month, day) => NaiveDate::from_ymd_opt(2000, *month, *day)
+                .and_then(|date| date_utils::anniversary(date, today.year(), config.leap_day))
+                .ok_or(ComputeError::OutOfRange)?,
             CalculatedDate::Today => today,
-            CalculatedDate::Yesterday => today - Duration::days(1),
-            CalculatedDate::Tomorrow => today + Duration::days(1),
-        }
+            CalculatedDate::Yesterday => today.pred_opt().ok_or(ComputeError::OutOfRange)?,
+            CalculatedDate::Tomorrow => today.succ_opt().ok_or(ComputeError::OutOfRange)?,
+            // Every day of the month recurs within a year, unless the month with it is past
+            // the last date there is.
+            CalculatedDate::NextMonthDay(day) => (0..=12)
+                .filter_map(|offset| month_day(today, offset, *day, config))
+                .find(|date| *date > today)
+                .ok_or(ComputeError::OutOfRange)?,
+            CalculatedDate::PreviousMonthDay(day) => (-12..=0)
+                .rev()
+                .filter_map(|offset| month_day(today, offset, *day, config))
+                .find(|date| *date < today)
+                .ok_or(ComputeError::OutOfRange)?,
+            CalculatedDate::NextMonth(month) => {
+                let year = if *month > today.month() {
+                    today.year()
+                } else {
+                    today.year() + 1
+                };
+
+                NaiveDate::from_ymd_opt(year, *month, 1).ok_or(ComputeError::OutOfRange)?
+            }
+            CalculatedDate::PreviousMonth(month) => {
+                let year = if *month < today.month() {
+                    today.year()
+                } else {
+                    today.year() - 1
+                };
+
+                NaiveDate::from_ymd_opt(year, *month, 1).ok_or(ComputeError::OutOfRange)?
+            }
+            // Every year has an anniversary, so one of the first two is after today, unless
+            // they're past the last date there is.
+            CalculatedDate::NextAnniversary(date) => {
+                let first = today.year().max(date.year() + 1);
+
+                (first..=first + 1)
+                    .filter_map(|year| date_utils::anniversary(*date, year, config.leap_day))
+                    .find(|anniversary| *anniversary > today)
+                    .ok_or(ComputeError::OutOfRange)?
+            }
+            CalculatedDate::Boundary(edge, span) => {
+                boundary(today, *edge, *span, config).ok_or(ComputeError::OutOfRange)?
+            }
+            CalculatedDate::FirstBusinessDayAfter(date) => {
+                holidays::next_business_day(date.calculate(today, config)?, &*config.holidays)
+                    .ok_or(ComputeError::OutOfRange)?
+            }
+            CalculatedDate::LastBusinessDayBefore(date) => {
+                holidays::previous_business_day(date.calculate(today, config)?, &*config.holidays)
+                    .ok_or(ComputeError::OutOfRange)?
+            }
+            CalculatedDate::Settlement(days, date) => holidays::add_business_days(
+                date.calculate(today, config)?,
+                *days,
+                &*config.holidays,
+            )
+            .ok_or(ComputeError::OutOfRange)?,
+            CalculatedDate::BusinessDayOfMonth(nth, month) => {
+                let first = match month {
+                    MonthOf::Relative(offset) => today
+                        .with_day(1)
+                        .and_then(|first| date_utils::add_months(first, (*offset).into())),
+                    MonthOf::Named(month) => NaiveDate::from_ymd_opt(today.year(), *month, 1),
+                };
+
+                // Counting past the month's business days carries on into the next month,
+                // as settlement does.
+                if *nth > 0 {
+                    first.and_then(|first| first.pred_opt()).and_then(|last| {
+                        holidays::add_business_days(last, *nth as usize, &*config.holidays)
+                    })
+                } else {
+                    first
+                        .and_then(|first| date_utils::add_months(first, 1))
+                        .and_then(|next| {
+                            holidays::subtract_business_days(
+                                next,
+                                nth.unsigned_abs() as usize,
+                                &*config.holidays,
+                            )
+                        })
+                }
+                .ok_or(ComputeError::OutOfRange)?
+            }
+            CalculatedDate::UpcomingWeekday(weekday) => {
+                date_utils::upcoming_weekday(today, *weekday)
+            }
+            // Weekends are two days, so on the first or second day "last weekend" is the
+            // one before.
+            CalculatedDate::NextWeekend => {
+                date_utils::add_days(date_utils::start_of_week(today, config.weekend_start), 7)
+                    .ok_or(ComputeError::OutOfRange)?
+            }
+            CalculatedDate::PreviousWeekend => {
+                let start = date_utils::start_of_week(today, config.weekend_start);
+
+                if today - start < Duration::days(2) {
+                    date_utils::add_days(start, -7).ok_or(ComputeError::OutOfRange)?
+                } else {
+                    start
+                }
+            }
+            CalculatedDate::WeekdayOfWeek(weekday, weeks) => (*weeks as i64)
+                .checked_mul(7)
+                .and_then(|days| {
+                    date_utils::add_days(date_utils::start_of_week(today, config.week_start), days)
+                })
+                .filter(|date| date_utils::in_supported_range(*date))
+                .map(|date| date_utils::upcoming_weekday(date, *weekday))
+                .ok_or(ComputeError::OutOfRange)?,
+            CalculatedDate::Term(edge, name) => match (config.terms.get(name), edge) {
+                (Some((start, _)), Edge::Start) => *start,
+                (Some((_, end)), Edge::End) => *end,
+                (None, _) => return Err(ComputeError::UnknownTerm),
+            },
+            CalculatedDate::BillingDate(cycles) => {
+                let (_, next) = billing_cycle(today, config);
+                // Capped well past the supported years, so huge counts are reported as out
+                // of range rather than overflowing.
+                let cycles = (*cycles).clamp(1, 240_000) as i32;
+
+                renewal(next, cycles - 1, config)
+            }
+            CalculatedDate::NextOccurrence(name) => config
+                .cadences
+                .get(name)
+                .ok_or(ComputeError::UnknownCadence)?
+                .next_after(today)
+                .ok_or(ComputeError::OutOfRange)?,
+            CalculatedDate::Jql(date) => date
+                .calculate(today, config)
+                .ok_or(ComputeError::OutOfRange)?,
+            CalculatedDate::Sprint(edge, number) => {
+                let (first, days) = config.sprints.ok_or(ComputeError::NoSprints)?;
+                let days = i64::try_from(days).map_err(|_| ComputeError::OutOfRange)?;
+                let offset = i64::try_from(number.saturating_sub(1))
+                    .ok()
+                    .and_then(|sprints| sprints.checked_mul(days))
+                    .and_then(|offset| match edge {
+                        Edge::Start => Some(offset),
+                        Edge::End => offset.checked_add(days - 1),
+                    })
+                    .ok_or(ComputeError::OutOfRange)?;
+
+                date_utils::add_days(first, offset).ok_or(ComputeError::OutOfRange)?
+            }
+        })
+    }
+}
+
+/// The billing cycle containing `today`, from the renewal on or before it up to the next
+/// one. Without a billing day, cycles are calendar months.
+pub(crate) fn billing_cycle(today: NaiveDate, config: &Config) -> (NaiveDate, NaiveDate) {
+    let this_month = renewal(today, 0, config);
+
+    if this_month <= today {
+        (this_month, renewal(today, 1, config))
+    } else {
+        (renewal(today, -1, config), this_month)
     }
 }
 
+// Each renewal is placed from the billing day itself rather than the previous renewal, so
+// a short month doesn't pull later ones earlier.
+fn renewal(date: NaiveDate, months: i32, config: &Config) -> NaiveDate {
+    let (year, month) = date_utils::shift_month(date.year(), date.month(), months);
+
+    date_utils::day_of_month(
+        year,
+        month,
+        config.billing_day.unwrap_or(1),
+        ShortMonthPolicy::Clamp,
+    )
+    .expect("clamped days exist in every month")
+}
+
+/// The first or last day of the span containing `today`, or `None` for a quarter that
+/// doesn't exist or a week past the last date there is.
+pub(crate) fn boundary(
+    today: NaiveDate,
+    edge: Edge,
+    span: Span,
+    config: &Config,
+) -> Option<NaiveDate> {
+    let (month, day) = match (edge, span) {
+        (edge, Span::Week) => {
+            let start = date_utils::start_of_week(today, config.week_start);
+
+            return match edge {
+                Edge::Start => Some(start),
+                Edge::End => date_utils::add_days(start, 6),
+            };
+        }
+        (Edge::Start, Span::Month) => (today.month(), 1),
+        (Edge::End, Span::Month) => (
+            today.month(),
+            date_utils::days_in_month(today.year(), today.month()),
+        ),
+        (Edge::Start, Span::Year) => (1, 1),
+        (Edge::End, Span::Year) => (12, 31),
+        (edge, Span::Quarter(quarter)) => {
+            let quarter = quarter.unwrap_or(today.month().div_ceil(3));
+
+            match edge {
+                Edge::Start => (quarter.checked_mul(3)?.checked_sub(2)?, 1),
+                Edge::End => (
+                    quarter.checked_mul(3)?,
+                    date_utils::days_in_month(today.year(), quarter.checked_mul(3)?),
+                ),
+            }
+        }
+    };
+
+    NaiveDate::from_ymd_opt(today.year(), month, day)
+}
+
+fn month_day(today: NaiveDate, offset: i32, day: u32, config: &Config) -> Option<NaiveDate> {
+    let (year, month) = date_utils::shift_month(today.year(), today.month(), offset);
+
+    date_utils::day_of_month(year, month, day, config.short_months)
+}
+
+/// The dates `parse` reads, other than Jira's.
+#[cfg(feature = "cli")]
+pub(crate) const SYNTAX: &[Form] = &[
+    Form {
+        pattern: "today|now|tomorrow|yesterday",
+        description: "relative to the reference date",
+        examples: &["now"],
+    },
+    Form {
+        pattern: "<month> <day>[, <year> [BC|AD]] | [-]<year>-<month>-<day>",
+        description: "a literal date, in the reference date's year when none is given; a \
+                      negative year counts back from 1 BC as year 0",
+        examples: &[
+            "July 4, 2025",
+            "March 1",
+            "2022-03-01",
+            "March 15, 44 BC",
+            "-0043-03-15",
+        ],
+    },
+    Form {
+        pattern: "(start|end) of [the] <name> term",
+        description: "the first or last day of a term defined with --term",
+        examples: &["start of fall term", "end of the spring-2025 term"],
+    },
+    Form {
+        pattern: "sprint <number> (start|end)",
+        description: "the first or last day of a sprint set with --sprint-length",
+        examples: &["sprint 7 start", "sprint 12 end"],
+    },
+    Form {
+        pattern: "next <name>",
+        description: "the next date of a cadence defined with --cadence",
+        examples: &["next release"],
+    },
+    Form {
+        pattern: "next billing date | billing date in <count> cycles",
+        description: "renewals on the day set with --billing-day",
+        examples: &["next billing date", "billing date in 3 cycles"],
+    },
+    Form {
+        pattern: "[next] <weekday>",
+        description: "the next occurrence of a weekday, including today",
+        examples: &["friday", "next friday"],
+    },
+    Form {
+        pattern: "(next|last) weekend",
+        description: "the first day of the coming or previous weekend; weekends start \
+                      on --weekend-start",
+        examples: &["next weekend", "last weekend"],
+    },
+    Form {
+        pattern: "<weekday> of (next|last|this) week",
+        description: "a day of a week relative to the current one; weeks start on \
+                      --week-start",
+        examples: &["tuesday of next week"],
+    },
+    Form {
+        pattern: "(next|last) <ordinal>",
+        description: "the next or previous occurrence of a day of the month",
+        examples: &["next 31st", "last 15th"],
+    },
+    Form {
+        pattern: "(next|last) <month>",
+        description: "the first day of the next or previous such month",
+        examples: &["next March", "last dec"],
+    },
+    Form {
+        pattern: "next anniversary of <date>",
+        description: "the next yearly recurrence of a date",
+        examples: &["next anniversary of 2020-02-29"],
+    },
+    Form {
+        pattern: "(start|beginning|end) of [the|this] <span>",
+        description: "the first or last day of the current week, month, quarter, or year",
+        examples: &["end of the month", "start of Q3", "end of this month"],
+    },
+    Form {
+        pattern: "bow|eow|bom|eom|boy|eoy",
+        description: "shorthand for the beginning or end of the week, month, or year",
+        examples: &["eom", "bow"],
+    },
+    Form {
+        pattern: "this <span>",
+        description: "the first day of the current week, month, quarter, or year",
+        examples: &["this month", "this year"],
+    },
+    Form {
+        pattern: "(first business day after|last business day before) <date>",
+        description: "skipping weekends and holidays",
+        examples: &["first business day after July 4, 2025"],
+    },
+    Form {
+        pattern: "(first|last|<nth>) business day of ((next|last|this) month|<month>)",
+        description: "a business day counted from the start or end of a month",
+        examples: &[
+            "3rd business day of next month",
+            "last business day of March",
+        ],
+    },
+    Form {
+        pattern: "T+<days> [from <date>]",
+        description: "a settlement date, counting business days",
+        examples: &["T+2 from 2024-03-01"],
+    },
+    Form {
+        pattern: "serial <number>",
+        description: "a spreadsheet date serial number, e.g. from Excel",
+        examples: &["serial 44575"],
+    },
+];
+
 pub fn parse(input: &str) -> IResult<&str, CalculatedDate> {
     alt((
         value(CalculatedDate::Today, tag("today")),
         value(CalculatedDate::Today, tag("now")),
         value(CalculatedDate::Yesterday, tag("yesterday")),
         value(CalculatedDate::Tomorrow, tag("tomorrow")),
-        map(parse_dash_date, CalculatedDate::Raw),
         map(
-            map_opt(take_till(|c: char| c == '+' || c == '-'), parse_date),
+            preceded(pair(tag("next"), space1), parse_day_ordinal),
+            CalculatedDate::NextMonthDay,
+        ),
+        map(
+            preceded(pair(tag("last"), space1), parse_day_ordinal),
+            CalculatedDate::PreviousMonthDay,
+        ),
+        alt((
+            value(CalculatedDate::NextWeekend, tag("next weekend")),
+            value(CalculatedDate::PreviousWeekend, tag("last weekend")),
+            map(
+                preceded(pair(tag("next"), space1), parse_month),
+                CalculatedDate::NextMonth,
+            ),
+            map(
+                preceded(pair(tag("last"), space1), parse_month),
+                CalculatedDate::PreviousMonth,
+            ),
+        )),
+        map(
+            preceded(tag("next anniversary of "), parse_literal),
+            CalculatedDate::NextAnniversary,
+        ),
+        alt((
+            map(
+                pair(
+                    terminated(parse_edge, pair(tag(" of "), opt(tag("the ")))),
+                    parse_term,
+                ),
+                |(edge, name)| CalculatedDate::Term(edge, name),
+            ),
+            map(
+                pair(
+                    preceded(tag("sprint "), verify(parse_digits, |number| *number > 0)),
+                    preceded(space1, parse_edge),
+                ),
+                |(number, edge)| CalculatedDate::Sprint(edge, number),
+            ),
+            value(CalculatedDate::BillingDate(1), tag("next billing date")),
+            map(
+                preceded(
+                    tag("next "),
+                    verify(
+                        take_while1(|c: char| c.is_alphanumeric() || c == '-' || c == '_'),
+                        // Words the grammar knows, like "friday" in "next friday", keep
+                        // their meaning.
+                        |name: &str| {
+                            name.starts_with(char::is_alphabetic)
+                                && token::classify(name) == token::TokenKind::Word
+                        },
+                    ),
+                ),
+                |name: &str| CalculatedDate::NextOccurrence(name.to_lowercase()),
+            ),
+            map(
+                delimited(
+                    tag("billing date in "),
+                    verify(parse_digits, |cycles| *cycles > 0),
+                    alt((tag(" cycles"), tag(" cycle"))),
+                ),
+                CalculatedDate::BillingDate,
+            ),
+        )),
+        map(
+            alt((parse_boundary, parse_boundary_shorthand)),
+            |(edge, span)| CalculatedDate::Boundary(edge, span),
+        ),
+        map(preceded(tag("this "), parse_span), |span| {
+            CalculatedDate::Boundary(Edge::Start, span)
+        }),
+        map(
+            pair(
+                terminated(
+                    alt((
+                        value(1, tag("first")),
+                        value(-1, tag("last")),
+                        map(parse_day_ordinal, |nth| nth as i32),
+                    )),
+                    tag(" business day of "),
+                ),
+                alt((
+                    map(
+                        terminated(
+                            alt((
+                                value(1, tag("next")),
+                                value(-1, tag("last")),
+                                value(0, tag("this")),
+                            )),
+                            tag(" month"),
+                        ),
+                        MonthOf::Relative,
+                    ),
+                    map(parse_month, MonthOf::Named),
+                )),
+            ),
+            |(nth, month)| CalculatedDate::BusinessDayOfMonth(nth, month),
+        ),
+        map(preceded(tag("first business day after "), parse), |date| {
+            CalculatedDate::FirstBusinessDayAfter(Box::new(date))
+        }),
+        map(preceded(tag("last business day before "), parse), |date| {
+            CalculatedDate::LastBusinessDayBefore(Box::new(date))
+        }),
+        map(
+            pair(
+                preceded(alt((tag("T+"), tag("t+"))), parse_digits),
+                opt(preceded(tag(" from "), parse)),
+            ),
+            |(days, date)| {
+                CalculatedDate::Settlement(days, Box::new(date.unwrap_or(CalculatedDate::Today)))
+            },
+        ),
+        map(
+            pair(
+                terminated(map_opt(alpha1, |name: &str| name.parse().ok()), tag(" of ")),
+                terminated(
+                    alt((
+                        value(1, tag("next")),
+                        value(-1, tag("last")),
+                        value(0, tag("this")),
+                    )),
+                    tag(" week"),
+                ),
+            ),
+            |(weekday, weeks)| CalculatedDate::WeekdayOfWeek(weekday, weeks),
+        ),
+        map(
+            preceded(
+                opt(pair(tag("next"), space1)),
+                map_opt(alpha1, |name: &str| name.parse().ok()),
+            ),
+            CalculatedDate::UpcomingWeekday,
+        ),
+        map(jql::parse, CalculatedDate::Jql),
+        map(
+            map_opt(
+                preceded(tag("serial "), parse_digits),
+                date_utils::from_excel_serial,
+            ),
             CalculatedDate::Raw,
         ),
+        map(parse_calendar_date, CalculatedDate::Raw),
+        alt((
+            map(parse_literal, CalculatedDate::Raw),
+            map(
+                map_opt(
+                    take_till(|c: char| c == '+' || c == '-'),
+                    parse_month_and_day,
+                ),
+                |(month, day)| CalculatedDate::MonthAndDay(month, day),
+            ),
+        )),
+    ))(input)
+}
+
+#[cfg(feature = "calendars")]
+use crate::calendars::parse as parse_calendar_date;
+
+#[cfg(not(feature = "calendars"))]
+fn parse_calendar_date(input: &str) -> IResult<&str, NaiveDate> {
+    Err(nom::Err::Error(nom::error::Error::new(
+        input,
+        nom::error::ErrorKind::Alt,
+    )))
+}
+
+fn parse_month(input: &str) -> IResult<&str, u32> {
+    map_opt(alpha1, |name: &str| {
+        name.parse::<Month>()
+            .ok()
+            .map(|month| month.number_from_month())
+    })(input)
+}
+
+fn parse_boundary(input: &str) -> IResult<&str, (Edge, Span)> {
+    pair(
+        terminated(
+            parse_edge,
+            pair(tag(" of "), opt(alt((tag("the "), tag("this "))))),
+        ),
+        parse_span,
+    )(input)
+}
+
+fn parse_edge(input: &str) -> IResult<&str, Edge> {
+    alt((
+        value(Edge::Start, tag("start")),
+        value(Edge::Start, tag("beginning")),
+        value(Edge::End, tag("end")),
+    ))(input)
+}
+
+/// The name of a term followed by "term", e.g. "fall term".
+pub(crate) fn parse_term(input: &str) -> IResult<&str, String> {
+    map(
+        terminated(
+            take_while1(|c: char| c.is_alphanumeric() || c == '-' || c == '_'),
+            tag(" term"),
+        ),
+        str::to_lowercase,
+    )(input)
+}
+
+fn parse_boundary_shorthand(input: &str) -> IResult<&str, (Edge, Span)> {
+    alt((
+        value((Edge::Start, Span::Week), tag("bow")),
+        value((Edge::End, Span::Week), tag("eow")),
+        value((Edge::Start, Span::Month), tag("bom")),
+        value((Edge::End, Span::Month), tag("eom")),
+        value((Edge::Start, Span::Year), tag("boy")),
+        value((Edge::End, Span::Year), tag("eoy")),
+    ))(input)
+}
+
+pub(crate) fn parse_span(input: &str) -> IResult<&str, Span> {
+    alt((
+        value(Span::Week, tag("week")),
+        value(Span::Month, tag("month")),
+        value(Span::Year, tag("year")),
+        value(Span::Quarter(None), tag("quarter")),
+        map(
+            preceded(alt((tag("Q"), tag("q"))), one_of("1234")),
+            |quarter| Span::Quarter(quarter.to_digit(10)),
+        ),
+    ))(input)
+}
+
+pub(crate) fn parse_literal(input: &str) -> IResult<&str, NaiveDate> {
+    alt((
+        parse_dash_date,
+        map_opt(take_till(|c: char| c == '+' || c == '-'), parse_date),
     ))(input)
 }
 
+// A leading minus is an astronomical year, where 0 is 1 BC and -43 is 44 BC.
 fn parse_dash_date(input: &str) -> IResult<&str, NaiveDate> {
     map_opt(
         tuple((
+            opt(tag("-")),
             terminated(parse_digits, tag("-")),
             terminated(parse_digits, tag("-")),
             parse_digits,
         )),
-        |(year, month, day)| NaiveDate::from_ymd_opt(year, month, day),
+        |(sign, year, month, day): (_, i32, _, _)| {
+            NaiveDate::from_ymd_opt(if sign.is_some() { -year } else { year }, month, day)
+        },
     )(input)
 }
 
 pub(crate) fn parse_date(value: &str) -> Option<NaiveDate> {
     let value = value.trim();
 
+    if let Some(date) = strip_era(value, &[" bc", " bce"]) {
+        // There's no year 0 between 1 BC and AD 1.
+        return parse_date(date)
+            .filter(|date| date.year() > 0)
+            .and_then(|date| date.with_year(1 - date.year()));
+    }
+    if let Some(date) = strip_era(value, &[" ad", " ce"]) {
+        return parse_date(date);
+    }
+
     NaiveDate::parse_from_str(value, "%h %d, %Y")
-        .or(NaiveDate::parse_from_str(value, "%B %d"))
-        .or(NaiveDate::parse_from_str(value, "%B %d, %Y"))
-        .or(NaiveDate::parse_from_str(value, "%m/%d/%Y"))
+        .or_else(|_| NaiveDate::parse_from_str(value, "%B %d"))
+        .or_else(|_| NaiveDate::parse_from_str(value, "%B %d, %Y"))
+        .or_else(|_| NaiveDate::parse_from_str(value, "%m/%d/%Y"))
         .ok()
-        .or(parse_partial_date(value))
 }
 
-fn parse_partial_date(value: &str) -> Option<NaiveDate> {
+fn strip_era<'a>(value: &'a str, suffixes: &[&str]) -> Option<&'a str> {
+    suffixes.iter().find_map(|suffix| {
+        let split = value.len().checked_sub(suffix.len())?;
+
+        (value.is_char_boundary(split) && value[split..].eq_ignore_ascii_case(suffix))
+            .then(|| &value[..split])
+    })
+}
+
+// A month and day without a year, e.g. "Mar 1", checked against a leap year so February
+// 29 is allowed.
+fn parse_month_and_day(value: &str) -> Option<(u32, u32)> {
+    let value = value.trim();
     let mut parsed = format::Parsed::new();
-    let long_month_name_format = vec![
+    let long_month_name_format = [
         format::Item::Fixed(format::Fixed::LongMonthName),
         format::Item::Space(" "),
         format::Item::Numeric(format::Numeric::Day, format::Pad::None),
@@ -73,7 +785,7 @@ fn parse_partial_date(value: &str) -> Option<NaiveDate> {
 
     if format::parse(&mut parsed, value, long_month_name_format.iter()).is_ok() {
         match (parsed.month, parsed.day) {
-            (Some(m), Some(d)) => NaiveDate::from_ymd_opt(chrono::Local::today().year(), m, d),
+            (Some(m), Some(d)) => NaiveDate::from_ymd_opt(2000, m, d).map(|_| (m, d)),
             _ => None,
         }
     } else {
@@ -84,9 +796,91 @@ fn parse_partial_date(value: &str) -> Option<NaiveDate> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{LeapDayPolicy, ShortMonthPolicy};
+    use crate::holidays::HolidayList;
 
     fn parse_and_calculate(value: &str, today: NaiveDate) -> NaiveDate {
-        parse(value).unwrap().1.calculate(today)
+        parse(value)
+            .unwrap()
+            .1
+            .calculate(today, &Config::default())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_constructors_and_accessors() {
+        let date = NaiveDate::from_ymd(2024, 3, 1);
+
+        assert_eq!(CalculatedDate::raw(date), parse("2024-03-01").unwrap().1);
+        assert_eq!(CalculatedDate::today(), parse("today").unwrap().1);
+        assert_eq!(
+            CalculatedDate::settlement(2, CalculatedDate::raw(date)),
+            parse("T+2 from 2024-03-01").unwrap().1
+        );
+        assert_eq!(
+            CalculatedDate::boundary(Edge::End, Span::Month),
+            parse("end of the month").unwrap().1
+        );
+
+        let after = CalculatedDate::first_business_day_after(CalculatedDate::raw(date));
+        assert_eq!(after.anchor().and_then(CalculatedDate::literal), Some(date));
+        assert_eq!(after.literal(), None);
+        assert_eq!(CalculatedDate::today().anchor(), None);
+    }
+
+    #[test]
+    fn test_date_undefined_in_config() {
+        let date = NaiveDate::from_ymd(2022, 3, 1);
+        let config = Config::default();
+
+        assert_eq!(
+            CalculatedDate::term(Edge::Start, "fall").calculate(date, &config),
+            Err(ComputeError::UnknownTerm)
+        );
+        assert_eq!(
+            CalculatedDate::NextOccurrence("release".to_string()).calculate(date, &config),
+            Err(ComputeError::UnknownCadence)
+        );
+    }
+
+    #[test]
+    fn test_date_out_of_range() {
+        let date = NaiveDate::from_ymd(2022, 3, 1);
+        let config = Config::default();
+        let out_of_range = [
+            CalculatedDate::WeekdayOfWeek(Weekday::Tue, i32::MAX),
+            CalculatedDate::WeekdayOfWeek(Weekday::Tue, i32::MIN),
+            CalculatedDate::BusinessDayOfMonth(1, MonthOf::Named(13)),
+            CalculatedDate::BusinessDayOfMonth(-1, MonthOf::Named(0)),
+            CalculatedDate::BusinessDayOfMonth(1, MonthOf::Relative(i32::MAX)),
+            CalculatedDate::NextMonth(13),
+            CalculatedDate::PreviousMonth(0),
+            CalculatedDate::MonthAndDay(2, 30),
+            CalculatedDate::Boundary(Edge::End, Span::Quarter(Some(5))),
+        ];
+
+        for value in out_of_range.iter() {
+            assert_eq!(
+                value.calculate(date, &config),
+                Err(ComputeError::OutOfRange),
+                "{:?}",
+                value
+            );
+        }
+
+        let last = chrono::naive::MAX_DATE;
+        assert_eq!(
+            CalculatedDate::Tomorrow.calculate(last, &config),
+            Err(ComputeError::OutOfRange)
+        );
+        assert_eq!(
+            CalculatedDate::NextMonthDay(31).calculate(last, &config),
+            Err(ComputeError::OutOfRange)
+        );
+        assert_eq!(
+            CalculatedDate::Yesterday.calculate(chrono::naive::MIN_DATE, &config),
+            Err(ComputeError::OutOfRange)
+        );
     }
 
     #[test]
@@ -100,6 +894,320 @@ mod tests {
         assert_eq!(parse_and_calculate("tomorrow", date), date + one_day);
     }
 
+    #[test]
+    fn test_date_named_month() {
+        let date = NaiveDate::from_ymd(2022, 3, 15);
+
+        assert_eq!(
+            parse_and_calculate("next March", date),
+            NaiveDate::from_ymd(2023, 3, 1)
+        );
+        assert_eq!(
+            parse_and_calculate("next dec", date),
+            NaiveDate::from_ymd(2022, 12, 1)
+        );
+        assert_eq!(
+            parse_and_calculate("last March", date),
+            NaiveDate::from_ymd(2021, 3, 1)
+        );
+        assert_eq!(
+            parse_and_calculate("last february", date),
+            NaiveDate::from_ymd(2022, 2, 1)
+        );
+    }
+
+    #[test]
+    fn test_date_weekend() {
+        // March 3, 2022 is a Thursday
+        let thursday = NaiveDate::from_ymd(2022, 3, 3);
+        let sunday = NaiveDate::from_ymd(2022, 3, 6);
+
+        assert_eq!(
+            parse_and_calculate("next weekend", thursday),
+            NaiveDate::from_ymd(2022, 3, 5)
+        );
+        assert_eq!(
+            parse_and_calculate("last weekend", thursday),
+            NaiveDate::from_ymd(2022, 2, 26)
+        );
+        assert_eq!(
+            parse_and_calculate("next weekend", sunday),
+            NaiveDate::from_ymd(2022, 3, 12)
+        );
+        assert_eq!(
+            parse_and_calculate("last weekend", sunday),
+            NaiveDate::from_ymd(2022, 2, 26)
+        );
+
+        let friday_start = Config::default().weekend_start(Weekday::Fri);
+        assert_eq!(
+            CalculatedDate::NextWeekend.calculate(thursday, &friday_start),
+            Ok(NaiveDate::from_ymd(2022, 3, 4))
+        );
+    }
+
+    #[test]
+    fn test_date_weekday_of_week() {
+        // March 3, 2022 is a Thursday
+        let date = NaiveDate::from_ymd(2022, 3, 3);
+
+        assert_eq!(
+            parse_and_calculate("tuesday of next week", date),
+            NaiveDate::from_ymd(2022, 3, 8)
+        );
+        assert_eq!(
+            parse_and_calculate("Tuesday of this week", date),
+            NaiveDate::from_ymd(2022, 3, 1)
+        );
+        assert_eq!(
+            parse_and_calculate("sunday of last week", date),
+            NaiveDate::from_ymd(2022, 2, 27)
+        );
+
+        let sunday_start = Config::default().week_start(Weekday::Sun);
+        assert_eq!(
+            CalculatedDate::WeekdayOfWeek(Weekday::Sun, 1).calculate(date, &sunday_start),
+            Ok(NaiveDate::from_ymd(2022, 3, 6))
+        );
+    }
+
+    #[test]
+    fn test_date_month_day() {
+        let date = NaiveDate::from_ymd_opt(2022, 1, 31).unwrap();
+
+        assert_eq!(
+            parse_and_calculate("next 15th", date),
+            NaiveDate::from_ymd(2022, 2, 15)
+        );
+        assert_eq!(
+            parse_and_calculate("next 31st", date),
+            NaiveDate::from_ymd(2022, 3, 31)
+        );
+        assert_eq!(
+            parse_and_calculate("last 1st", date),
+            NaiveDate::from_ymd(2022, 1, 1)
+        );
+        assert_eq!(
+            parse_and_calculate("last 31st", date),
+            NaiveDate::from_ymd(2021, 12, 31)
+        );
+        assert!(parse("next 32nd").is_err());
+    }
+
+    #[test]
+    fn test_date_month_day_clamped() {
+        let date = NaiveDate::from_ymd_opt(2022, 1, 31).unwrap();
+        let config = Config::default().short_months(ShortMonthPolicy::Clamp);
+
+        assert_eq!(
+            CalculatedDate::NextMonthDay(31).calculate(date, &config),
+            Ok(NaiveDate::from_ymd(2022, 2, 28))
+        );
+        assert_eq!(
+            CalculatedDate::PreviousMonthDay(30)
+                .calculate(NaiveDate::from_ymd(2022, 3, 1), &config),
+            Ok(NaiveDate::from_ymd(2022, 2, 28))
+        );
+    }
+
+    #[test]
+    fn test_date_next_anniversary() {
+        let date = NaiveDate::from_ymd_opt(2022, 1, 31).unwrap();
+
+        assert_eq!(
+            parse_and_calculate("next anniversary of 1990-05-04", date),
+            NaiveDate::from_ymd(2022, 5, 4)
+        );
+        assert_eq!(
+            parse_and_calculate("next anniversary of Jan 31, 2020", date),
+            NaiveDate::from_ymd(2023, 1, 31)
+        );
+        assert_eq!(
+            parse_and_calculate("next anniversary of 2024-02-29", date),
+            NaiveDate::from_ymd(2025, 2, 28)
+        );
+        assert_eq!(
+            CalculatedDate::NextAnniversary(NaiveDate::from_ymd(2020, 2, 29))
+                .calculate(date, &Config::default().leap_day(LeapDayPolicy::March1)),
+            Ok(NaiveDate::from_ymd(2022, 3, 1))
+        );
+        assert_eq!(
+            CalculatedDate::NextAnniversary(NaiveDate::from_ymd(2020, 2, 29))
+                .calculate(chrono::naive::MAX_DATE, &Config::default()),
+            Err(ComputeError::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_date_boundary() {
+        let date = NaiveDate::from_ymd_opt(2022, 2, 14).unwrap();
+
+        assert_eq!(
+            parse_and_calculate("end of month", date),
+            NaiveDate::from_ymd(2022, 2, 28)
+        );
+        assert_eq!(
+            parse_and_calculate("beginning of the month", date),
+            NaiveDate::from_ymd(2022, 2, 1)
+        );
+        assert_eq!(
+            parse_and_calculate("start of year", date),
+            NaiveDate::from_ymd(2022, 1, 1)
+        );
+        assert_eq!(
+            parse_and_calculate("end of the year", date),
+            NaiveDate::from_ymd(2022, 12, 31)
+        );
+        assert_eq!(
+            parse_and_calculate("this month", date),
+            NaiveDate::from_ymd(2022, 2, 1)
+        );
+        assert_eq!(
+            parse_and_calculate("this year", date),
+            NaiveDate::from_ymd(2022, 1, 1)
+        );
+        assert_eq!(
+            parse_and_calculate("end of this month", date),
+            NaiveDate::from_ymd(2022, 2, 28)
+        );
+    }
+
+    #[test]
+    fn test_date_boundary_shorthand() {
+        // February 16, 2022 is a Wednesday
+        let date = NaiveDate::from_ymd_opt(2022, 2, 16).unwrap();
+
+        assert_eq!(
+            parse_and_calculate("eom", date),
+            NaiveDate::from_ymd(2022, 2, 28)
+        );
+        assert_eq!(
+            parse_and_calculate("bom", date),
+            NaiveDate::from_ymd(2022, 2, 1)
+        );
+        assert_eq!(
+            parse_and_calculate("eoy", date),
+            NaiveDate::from_ymd(2022, 12, 31)
+        );
+        assert_eq!(
+            parse_and_calculate("boy", date),
+            NaiveDate::from_ymd(2022, 1, 1)
+        );
+        assert_eq!(
+            parse_and_calculate("bow", date),
+            NaiveDate::from_ymd(2022, 2, 14)
+        );
+        assert_eq!(
+            parse_and_calculate("eow", date),
+            NaiveDate::from_ymd(2022, 2, 20)
+        );
+        assert_eq!(
+            parse_and_calculate("end of the week", date),
+            NaiveDate::from_ymd(2022, 2, 20)
+        );
+    }
+
+    #[test]
+    fn test_date_business_days() {
+        // July 31, 2022 is a Sunday
+        let date = NaiveDate::from_ymd_opt(2022, 7, 14).unwrap();
+
+        assert_eq!(
+            parse_and_calculate("first business day after July 4, 2025", date),
+            NaiveDate::from_ymd(2025, 7, 7)
+        );
+        assert_eq!(
+            parse_and_calculate("last business day before end of month", date),
+            NaiveDate::from_ymd(2022, 7, 29)
+        );
+
+        let config =
+            Config::default().holidays(HolidayList::new(vec![NaiveDate::from_ymd(2025, 7, 4)]));
+
+        assert_eq!(
+            parse("last business day before 2025-07-07")
+                .unwrap()
+                .1
+                .calculate(date, &config),
+            Ok(NaiveDate::from_ymd(2025, 7, 3))
+        );
+    }
+
+    #[test]
+    fn test_date_business_day_of_month() {
+        let today = NaiveDate::from_ymd(2022, 1, 10);
+
+        assert_eq!(
+            parse_and_calculate("3rd business day of next month", today),
+            NaiveDate::from_ymd(2022, 2, 3)
+        );
+        assert_eq!(
+            parse_and_calculate("first business day of this month", today),
+            NaiveDate::from_ymd(2022, 1, 3)
+        );
+        assert_eq!(
+            parse_and_calculate("last business day of April", today),
+            NaiveDate::from_ymd(2022, 4, 29)
+        );
+        assert_eq!(
+            parse_and_calculate("last business day of last month", today),
+            NaiveDate::from_ymd(2021, 12, 31)
+        );
+
+        let config =
+            Config::default().holidays(HolidayList::new(vec![NaiveDate::from_ymd(2022, 2, 1)]));
+        assert_eq!(
+            parse("1st business day of next month")
+                .unwrap()
+                .1
+                .calculate(today, &config),
+            Ok(NaiveDate::from_ymd(2022, 2, 2))
+        );
+    }
+
+    #[test]
+    fn test_date_settlement() {
+        let date = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+
+        assert_eq!(
+            parse_and_calculate("T+2 from 2024-03-01", date),
+            NaiveDate::from_ymd(2024, 3, 5)
+        );
+        assert_eq!(
+            parse_and_calculate("T+1", date),
+            NaiveDate::from_ymd(2024, 3, 1)
+        );
+        assert_eq!(parse_and_calculate("t+0", date), date);
+        assert_eq!(
+            parse("T+99999999999")
+                .unwrap()
+                .1
+                .calculate(date, &Config::default()),
+            Err(ComputeError::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_date_weekday() {
+        // January 31, 2022 is a Monday
+        let date = NaiveDate::from_ymd_opt(2022, 1, 31).unwrap();
+
+        assert_eq!(parse_and_calculate("monday", date), date);
+        assert_eq!(
+            parse_and_calculate("Friday", date),
+            NaiveDate::from_ymd(2022, 2, 4)
+        );
+        assert_eq!(
+            parse_and_calculate("sun", date),
+            NaiveDate::from_ymd(2022, 2, 6)
+        );
+        assert_eq!(
+            parse("next friday").unwrap().1,
+            CalculatedDate::UpcomingWeekday(Weekday::Fri)
+        );
+        assert!(parse("month").is_err());
+    }
+
     #[test]
     fn test_date_parse_exact() {
         assert_eq!(
@@ -119,6 +1227,24 @@ mod tests {
         assert!(parse("2021-01-32").is_err());
     }
 
+    #[test]
+    fn test_date_parse_eras() {
+        assert_eq!(
+            parse_date("March 15, 44 BC"),
+            NaiveDate::from_ymd_opt(-43, 3, 15)
+        );
+        assert_eq!(
+            parse_literal("-0043-03-15"),
+            Ok(("", NaiveDate::from_ymd(-43, 3, 15)))
+        );
+        assert_eq!(
+            parse_date("Dec 31, 1 bce"),
+            NaiveDate::from_ymd_opt(0, 12, 31)
+        );
+        assert_eq!(parse_date("Jan 1, 1 AD"), NaiveDate::from_ymd_opt(1, 1, 1));
+        assert_eq!(parse_date("Jan 1, 0 BC"), None);
+    }
+
     #[test]
     fn test_date_parse() {
         assert_eq!(
@@ -141,15 +1267,11 @@ mod tests {
             NaiveDate::from_ymd_opt(2021, 1, 1)
         );
 
-        assert_eq!(
-            parse_date("january 1"),
-            NaiveDate::from_ymd_opt(chrono::Local::today().year(), 1, 1)
-        );
-
-        assert_eq!(
-            parse_date("apr 30"),
-            NaiveDate::from_ymd_opt(chrono::Local::today().year(), 4, 30)
-        );
+        assert_eq!(parse_month_and_day("january 1"), Some((1, 1)));
+        assert_eq!(parse_month_and_day("apr 30"), Some((4, 30)));
+        assert_eq!(parse_month_and_day("feb 29"), Some((2, 29)));
+        assert_eq!(parse_month_and_day("apr 31"), None);
+        assert_eq!(parse_date("january 1"), None);
 
         assert_eq!(
             parse_date("Jan 01, 2021"),
@@ -0,0 +1,98 @@
+//! A month laid out as a grid, in the style of cal(1).
+
+use crate::date_utils;
+use chrono::{Datelike, NaiveDate};
+
+const HIGHLIGHT: &str = "\x1b[7m";
+const UNDERLINE: &str = "\x1b[4m";
+const RESET: &str = "\x1b[0m";
+
+/// Renders the month containing `date`, with `date` highlighted and `today` underlined
+/// when it falls in the same month. Without color, `date` is followed by a `*` instead,
+/// and `today` isn't marked.
+pub(crate) fn render(date: NaiveDate, today: NaiveDate, color: bool) -> String {
+    let (year, month) = (date.year(), date.month());
+    let title = format!("{} {}", date.format("%B"), year);
+    let mut lines = vec![
+        format!("{:^20}", title).trim_end().to_string(),
+        "Su Mo Tu We Th Fr Sa".to_string(),
+    ];
+    let offset = NaiveDate::from_ymd(year, month, 1)
+        .weekday()
+        .num_days_from_sunday() as usize;
+    let mut cells = vec!["  ".to_string(); offset];
+
+    for day in 1..=date_utils::days_in_month(year, month) {
+        let current = NaiveDate::from_ymd(year, month, day);
+        let mut cell = format!("{:>2}", day);
+
+        if current == today && color {
+            cell = format!("{}{}{}", UNDERLINE, cell, RESET);
+        }
+
+        if current == date && color {
+            cell = format!("{}{}{}", HIGHLIGHT, cell, RESET);
+        } else if current == date {
+            // Takes the place of the space after it, so the columns stay aligned.
+            cell.push('*');
+        }
+
+        cells.push(cell);
+    }
+
+    for week in cells.chunks(7) {
+        let line = week
+            .iter()
+            .fold(String::new(), |line, cell| match line.chars().last() {
+                Some('*') | None => line + cell,
+                Some(_) => line + " " + cell,
+            });
+
+        lines.push(line.trim_end().to_string());
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let rendered = render(
+            NaiveDate::from_ymd(2022, 2, 14),
+            NaiveDate::from_ymd(2022, 2, 1),
+            true,
+        );
+
+        assert_eq!(
+            rendered,
+            [
+                "   February 2022",
+                "Su Mo Tu We Th Fr Sa",
+                "      \x1b[4m 1\x1b[0m  2  3  4  5",
+                " 6  7  8  9 10 11 12",
+                "13 \x1b[7m14\x1b[0m 15 16 17 18 19",
+                "20 21 22 23 24 25 26",
+                "27 28",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_render_without_color() {
+        let render = |day| {
+            render(
+                NaiveDate::from_ymd(2022, 2, day),
+                NaiveDate::from_ymd(2022, 2, 1),
+                false,
+            )
+        };
+
+        assert_eq!(render(14).lines().nth(4), Some("13 14*15 16 17 18 19"));
+        assert_eq!(render(12).lines().nth(3), Some(" 6  7  8  9 10 11 12*"));
+        assert_eq!(render(1).lines().nth(2), Some("       1* 2  3  4  5"));
+    }
+}
@@ -0,0 +1,74 @@
+//! Dates written in non-Gregorian calendars, converted to Gregorian for computation.
+
+mod bikram_sambat;
+mod chinese;
+mod ethiopian;
+mod hijri;
+
+use calendrical_calculations::rata_die::RataDie;
+use chrono::{Datelike, NaiveDate};
+use nom::{branch::alt, IResult};
+use std::convert::TryInto;
+use std::str::FromStr;
+
+/// The calendar computed dates are displayed in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputCalendar {
+    Gregorian,
+    /// The Gregorian calendar counting years from the Buddhist era, as used in Thailand
+    ThaiBuddhist,
+    Ethiopian,
+    BikramSambat,
+}
+
+impl OutputCalendar {
+    pub fn format(self, date: NaiveDate) -> String {
+        match self {
+            OutputCalendar::Gregorian => date.to_string(),
+            OutputCalendar::ThaiBuddhist => {
+                format!("{} {} BE", date.format("%-d %B"), date.year() + 543)
+            }
+            OutputCalendar::Ethiopian => ethiopian::format(date),
+            OutputCalendar::BikramSambat => bikram_sambat::format(date),
+        }
+    }
+}
+
+impl FromStr for OutputCalendar {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "gregorian" => Ok(OutputCalendar::Gregorian),
+            "thai" => Ok(OutputCalendar::ThaiBuddhist),
+            "ethiopian" => Ok(OutputCalendar::Ethiopian),
+            "nepali" => Ok(OutputCalendar::BikramSambat),
+            _ => Err(format!("unknown calendar: '{}'", value)),
+        }
+    }
+}
+
+pub(crate) fn parse(input: &str) -> IResult<&str, NaiveDate> {
+    alt((hijri::parse, chinese::parse, bikram_sambat::parse))(input)
+}
+
+fn to_rata_die(date: NaiveDate) -> RataDie {
+    RataDie::new(date.num_days_from_ce().into())
+}
+
+fn from_rata_die(date: RataDie) -> Option<NaiveDate> {
+    NaiveDate::from_num_days_from_ce_opt(date.to_i64_date().try_into().ok()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thai_buddhist() {
+        assert_eq!(
+            OutputCalendar::ThaiBuddhist.format(NaiveDate::from_ymd(2022, 7, 15)),
+            "15 July 2565 BE"
+        );
+    }
+}
@@ -0,0 +1,346 @@
+//! The Bikram Sambat calendar used in Nepal. Its months begin on the day (in Nepal time)
+//! that the sun enters each sidereal zodiac sign, so their lengths change from year to
+//! year. Nepal publishes them in advance; those of 2000 to 2090 BS are kept here. Other
+//! years are computed from the sun's apparent longitude and the Lahiri ayanamsa, which
+//! may differ from the published calendar by a day when a sign change falls close to
+//! midnight.
+
+use crate::parser_utils::parse_digits;
+use chrono::{Datelike, NaiveDate};
+use nom::{
+    branch::alt,
+    bytes::complete::tag_no_case,
+    character::complete::space1,
+    combinator::{map_opt, opt, value},
+    sequence::{preceded, terminated, tuple},
+    IResult,
+};
+use std::convert::TryFrom;
+
+const MONTHS: [&str; 12] = [
+    "Baisakh", "Jestha", "Asar", "Shrawan", "Bhadra", "Asoj", "Kartik", "Mangsir", "Poush", "Magh",
+    "Falgun", "Chaitra",
+];
+
+/// The year `MONTH_LENGTHS` starts with, which began on April 14, 1943
+const FIRST_TABLE_YEAR: i32 = 2000;
+
+/// The days in each month of the years from `FIRST_TABLE_YEAR`, as published
+const MONTH_LENGTHS: [[u8; 12]; 91] = [
+    [30, 32, 31, 32, 31, 30, 30, 30, 29, 30, 29, 31], // 2000
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // 2001
+    [31, 31, 32, 32, 31, 30, 30, 29, 30, 29, 30, 30], // 2002
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 31], // 2003
+    [30, 32, 31, 32, 31, 30, 30, 30, 29, 30, 29, 31], // 2004
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // 2005
+    [31, 31, 32, 32, 31, 30, 30, 29, 30, 29, 30, 30], // 2006
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 31], // 2007
+    [31, 31, 31, 32, 31, 31, 29, 30, 30, 29, 29, 31], // 2008
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // 2009
+    [31, 31, 32, 32, 31, 30, 30, 29, 30, 29, 30, 30], // 2010
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 31], // 2011
+    [31, 31, 31, 32, 31, 31, 29, 30, 30, 29, 30, 30], // 2012
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // 2013
+    [31, 31, 32, 32, 31, 30, 30, 29, 30, 29, 30, 30], // 2014
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 31], // 2015
+    [31, 31, 31, 32, 31, 31, 29, 30, 30, 29, 30, 30], // 2016
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // 2017
+    [31, 32, 31, 32, 31, 30, 30, 29, 30, 29, 30, 30], // 2018
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 30, 29, 31], // 2019
+    [31, 31, 31, 32, 31, 31, 30, 29, 30, 29, 30, 30], // 2020
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // 2021
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 30], // 2022
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 30, 29, 31], // 2023
+    [31, 31, 31, 32, 31, 31, 30, 29, 30, 29, 30, 30], // 2024
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // 2025
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 31], // 2026
+    [30, 32, 31, 32, 31, 30, 30, 30, 29, 30, 29, 31], // 2027
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // 2028
+    [31, 31, 32, 31, 32, 30, 30, 29, 30, 29, 30, 30], // 2029
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 31], // 2030
+    [30, 32, 31, 32, 31, 30, 30, 30, 29, 30, 29, 31], // 2031
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // 2032
+    [31, 31, 32, 32, 31, 30, 30, 29, 30, 29, 30, 30], // 2033
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 31], // 2034
+    [30, 32, 31, 32, 31, 31, 29, 30, 30, 29, 29, 31], // 2035
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // 2036
+    [31, 31, 32, 32, 31, 30, 30, 29, 30, 29, 30, 30], // 2037
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 31], // 2038
+    [31, 31, 31, 32, 31, 31, 29, 30, 30, 29, 30, 30], // 2039
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // 2040
+    [31, 31, 32, 32, 31, 30, 30, 29, 30, 29, 30, 30], // 2041
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 31], // 2042
+    [31, 31, 31, 32, 31, 31, 29, 30, 30, 29, 30, 30], // 2043
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // 2044
+    [31, 32, 31, 32, 31, 30, 30, 29, 30, 29, 30, 30], // 2045
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 31], // 2046
+    [31, 31, 31, 32, 31, 31, 30, 29, 30, 29, 30, 30], // 2047
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // 2048
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 30], // 2049
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 30, 29, 31], // 2050
+    [31, 31, 31, 32, 31, 31, 30, 29, 30, 29, 30, 30], // 2051
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // 2052
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 30], // 2053
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 30, 29, 31], // 2054
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // 2055
+    [31, 31, 32, 31, 32, 30, 30, 29, 30, 29, 30, 30], // 2056
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 31], // 2057
+    [30, 32, 31, 32, 31, 30, 30, 30, 29, 30, 29, 31], // 2058
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // 2059
+    [31, 31, 32, 32, 31, 30, 30, 29, 30, 29, 30, 30], // 2060
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 31], // 2061
+    [30, 32, 31, 32, 31, 31, 29, 30, 29, 30, 29, 31], // 2062
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // 2063
+    [31, 31, 32, 32, 31, 30, 30, 29, 30, 29, 30, 30], // 2064
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 31], // 2065
+    [31, 31, 31, 32, 31, 31, 29, 30, 30, 29, 29, 31], // 2066
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // 2067
+    [31, 31, 32, 32, 31, 30, 30, 29, 30, 29, 30, 30], // 2068
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 31], // 2069
+    [31, 31, 31, 32, 31, 31, 29, 30, 30, 29, 30, 30], // 2070
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // 2071
+    [31, 32, 31, 32, 31, 30, 30, 29, 30, 29, 30, 30], // 2072
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 31], // 2073
+    [31, 31, 31, 32, 31, 31, 30, 29, 30, 29, 30, 30], // 2074
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // 2075
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 30], // 2076
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 30, 29, 31], // 2077
+    [31, 31, 31, 32, 31, 31, 30, 29, 30, 29, 30, 30], // 2078
+    [31, 31, 32, 31, 31, 31, 30, 29, 30, 29, 30, 30], // 2079
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 29, 30, 30], // 2080
+    [31, 32, 31, 32, 31, 30, 30, 30, 29, 30, 29, 31], // 2081
+    [30, 32, 31, 32, 31, 30, 30, 30, 29, 30, 30, 30], // 2082
+    [31, 31, 32, 31, 31, 30, 30, 30, 29, 30, 30, 30], // 2083
+    [31, 31, 32, 31, 31, 30, 30, 30, 29, 30, 30, 30], // 2084
+    [31, 32, 31, 32, 30, 31, 30, 30, 29, 30, 30, 30], // 2085
+    [30, 32, 31, 32, 31, 30, 30, 30, 29, 30, 30, 30], // 2086
+    [31, 31, 32, 31, 31, 31, 30, 30, 29, 30, 30, 30], // 2087
+    [30, 31, 32, 32, 30, 31, 30, 30, 29, 30, 30, 30], // 2088
+    [30, 32, 31, 32, 31, 30, 30, 30, 29, 30, 30, 30], // 2089
+    [30, 32, 31, 32, 31, 30, 30, 30, 29, 30, 30, 30], // 2090
+];
+
+/// Nepal Standard Time, UTC+5:45, as a fraction of a day
+const NEPAL_OFFSET: f64 = 5.75 / 24.0;
+
+/// The Julian day at 00:00 UTC on January 1, 1 CE, less one day
+const JULIAN_DAY_OF_CE: f64 = 1_721_424.5;
+
+pub(crate) fn to_gregorian(year: i32, month: u8, day: u8) -> Option<NaiveDate> {
+    if !(1..=12).contains(&month) || day == 0 {
+        return None;
+    }
+
+    let start = month_start(year, month)?;
+    let next = if month == 12 {
+        month_start(year + 1, 1)?
+    } else {
+        month_start(year, month + 1)?
+    };
+    let date = start.checked_add_signed(chrono::Duration::days(i64::from(day - 1)))?;
+
+    if date < next {
+        Some(date)
+    } else {
+        None
+    }
+}
+
+pub(crate) fn from_gregorian(date: NaiveDate) -> Option<(i32, u8, u8)> {
+    let year = match month_start(date.year() + 57, 1)? {
+        new_year if new_year <= date => date.year() + 57,
+        _ => date.year() + 56,
+    };
+
+    for month in (1..=12).rev() {
+        let start = month_start(year, month)?;
+
+        if start <= date {
+            return Some((year, month, (date - start).num_days() as u8 + 1));
+        }
+    }
+
+    None
+}
+
+/// Formats a date in Bikram Sambat, e.g. "1 Baisakh 2080 BS".
+pub(crate) fn format(date: NaiveDate) -> String {
+    match from_gregorian(date) {
+        Some((year, month, day)) => {
+            format!("{} {} {} BS", day, MONTHS[usize::from(month - 1)], year)
+        }
+        None => date.to_string(),
+    }
+}
+
+/// Parses dates like "15 Baisakh 2080" or "1 Magh 2080 BS".
+pub(crate) fn parse(input: &str) -> IResult<&str, NaiveDate> {
+    map_opt(
+        tuple((
+            terminated(parse_digits, space1),
+            terminated(parse_month, space1),
+            terminated(parse_digits, opt(preceded(space1, tag_no_case("BS")))),
+        )),
+        |(day, month, year)| to_gregorian(year, month, day),
+    )(input)
+}
+
+fn parse_month(input: &str) -> IResult<&str, u8> {
+    alt((
+        value(1, alt((tag_no_case("baisakh"), tag_no_case("baishakh")))),
+        value(2, alt((tag_no_case("jestha"), tag_no_case("jeth")))),
+        value(3, alt((tag_no_case("asar"), tag_no_case("ashadh")))),
+        value(4, alt((tag_no_case("shrawan"), tag_no_case("saun")))),
+        value(5, alt((tag_no_case("bhadra"), tag_no_case("bhadau")))),
+        value(6, alt((tag_no_case("asoj"), tag_no_case("ashwin")))),
+        value(7, tag_no_case("kartik")),
+        value(8, tag_no_case("mangsir")),
+        value(9, alt((tag_no_case("poush"), tag_no_case("push")))),
+        value(10, tag_no_case("magh")),
+        value(11, alt((tag_no_case("falgun"), tag_no_case("phagun")))),
+        value(12, alt((tag_no_case("chaitra"), tag_no_case("chait")))),
+    ))(input)
+}
+
+/// The Gregorian date in Nepal on which the given month of the given year begins.
+fn month_start(year: i32, month: u8) -> Option<NaiveDate> {
+    published_month_start(year, month).or_else(|| computed_month_start(year, month))
+}
+
+// Counted from the start of the table, which also gives the first day after it.
+fn published_month_start(year: i32, month: u8) -> Option<NaiveDate> {
+    let months = usize::try_from(year - FIRST_TABLE_YEAR).ok()? * 12 + usize::from(month - 1);
+
+    if months > MONTH_LENGTHS.len() * 12 {
+        return None;
+    }
+
+    let days: i64 = MONTH_LENGTHS
+        .iter()
+        .flatten()
+        .take(months)
+        .map(|&days| i64::from(days))
+        .sum();
+
+    NaiveDate::from_ymd(1943, 4, 14).checked_add_signed(chrono::Duration::days(days))
+}
+
+fn computed_month_start(year: i32, month: u8) -> Option<NaiveDate> {
+    // Baisakh begins in mid-April of the Gregorian year 57 years earlier
+    let new_year = NaiveDate::from_ymd_opt(year - 57, 4, 14)?;
+    let estimate = julian_day(new_year) + 30.44 * f64::from(month - 1);
+    let moment = sankranti(f64::from(month - 1) * 30.0, estimate) + NEPAL_OFFSET;
+
+    NaiveDate::from_num_days_from_ce_opt((moment - JULIAN_DAY_OF_CE).floor() as i32)
+}
+
+fn julian_day(date: NaiveDate) -> f64 {
+    f64::from(date.num_days_from_ce()) + JULIAN_DAY_OF_CE
+}
+
+/// The Julian day (UTC) nearest `estimate` at which the sun's sidereal longitude is `target`.
+fn sankranti(target: f64, estimate: f64) -> f64 {
+    (0..8).fold(estimate, |moment, _| {
+        let difference = (target - sidereal_solar_longitude(moment) + 540.0) % 360.0 - 180.0;
+        moment + difference / 0.985_647
+    })
+}
+
+fn sidereal_solar_longitude(julian_day: f64) -> f64 {
+    let centuries = (julian_day - 2_451_545.0) / 36_525.0;
+    let ayanamsa = 23.853 + 1.396_971 * centuries;
+
+    (apparent_solar_longitude(centuries) - ayanamsa).rem_euclid(360.0)
+}
+
+/// The sun's apparent geocentric longitude in degrees, accurate to about 0.01°.
+fn apparent_solar_longitude(centuries: f64) -> f64 {
+    let mean_longitude = 280.466_46 + 36_000.769_83 * centuries + 0.000_303_2 * centuries.powi(2);
+    let mean_anomaly =
+        (357.529_11 + 35_999.050_29 * centuries - 0.000_153_7 * centuries.powi(2)).to_radians();
+    let center = (1.914_602 - 0.004_817 * centuries - 0.000_014 * centuries.powi(2))
+        * mean_anomaly.sin()
+        + (0.019_993 - 0.000_101 * centuries) * (2.0 * mean_anomaly).sin()
+        + 0.000_289 * (3.0 * mean_anomaly).sin();
+    let omega = (125.04 - 1_934.136 * centuries).to_radians();
+
+    (mean_longitude + center - 0.005_69 - 0.004_78 * omega.sin()).rem_euclid(360.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_year() {
+        assert_eq!(
+            to_gregorian(2079, 1, 1),
+            Some(NaiveDate::from_ymd(2022, 4, 14))
+        );
+        assert_eq!(
+            to_gregorian(2080, 1, 1),
+            Some(NaiveDate::from_ymd(2023, 4, 14))
+        );
+        assert_eq!(
+            to_gregorian(2081, 1, 1),
+            Some(NaiveDate::from_ymd(2024, 4, 13))
+        );
+    }
+
+    #[test]
+    fn test_published_months() {
+        assert_eq!(
+            to_gregorian(2000, 1, 1),
+            Some(NaiveDate::from_ymd(1943, 4, 14))
+        );
+        // The sun entered Capricorn late on January 14, so Magh began the next day.
+        assert_eq!(
+            to_gregorian(2079, 10, 1),
+            Some(NaiveDate::from_ymd(2023, 1, 15))
+        );
+        assert_eq!(to_gregorian(2080, 9, 30), None);
+        assert_eq!(
+            from_gregorian(NaiveDate::from_ymd(2034, 4, 13)),
+            Some((2090, 12, 30))
+        );
+    }
+
+    #[test]
+    fn test_computed_months() {
+        assert_eq!(
+            to_gregorian(2091, 1, 1),
+            Some(NaiveDate::from_ymd(2034, 4, 14))
+        );
+        assert_eq!(to_gregorian(1999, 12, 1), computed_month_start(1999, 12));
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse("1 Magh 2080"),
+            Ok(("", NaiveDate::from_ymd(2024, 1, 15)))
+        );
+        assert_eq!(
+            parse("1 baishakh 2081 BS"),
+            Ok(("", NaiveDate::from_ymd(2024, 4, 13)))
+        );
+        assert!(parse("33 Baisakh 2080").is_err());
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(
+            format(NaiveDate::from_ymd(2023, 4, 14)),
+            "1 Baisakh 2080 BS"
+        );
+        assert_eq!(format(NaiveDate::from_ymd(2024, 1, 14)), "29 Poush 2080 BS");
+        assert_eq!(
+            format(NaiveDate::from_ymd(2024, 3, 14)),
+            "1 Chaitra 2080 BS"
+        );
+        assert_eq!(
+            format(NaiveDate::from_ymd(2024, 4, 12)),
+            "30 Chaitra 2080 BS"
+        );
+    }
+}
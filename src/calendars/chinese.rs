@@ -0,0 +1,154 @@
+//! The Chinese lunisolar calendar, with years numbered by the Gregorian year in which
+//! they begin. Months start at new moons observed from China, so conversions are
+//! computed astronomically rather than from tables.
+
+use super::{from_rata_die, to_rata_die};
+use crate::parser_utils::{parse_day_ordinal, parse_digits};
+use calendrical_calculations::chinese_based::{
+    days_until_month, month_structure_for_year, Chinese, YearBounds,
+};
+use chrono::NaiveDate;
+use nom::{
+    branch::alt,
+    bytes::complete::tag_no_case,
+    character::complete::space1,
+    combinator::{map_opt, opt, value},
+    sequence::{pair, preceded, terminated, tuple},
+    IResult,
+};
+
+/// A month as it's traditionally numbered: 1 through 12, where a leap month repeats the
+/// number of the month before it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Month {
+    number: u8,
+    leap: bool,
+}
+
+fn to_gregorian(year: i32, month: Month, day: u8) -> Option<NaiveDate> {
+    // Every lunar year starts between January 21 and February 20, so the year containing
+    // the middle of the Gregorian year is the one that started in it.
+    let bounds = YearBounds::compute::<Chinese>(to_rata_die(NaiveDate::from_ymd_opt(year, 7, 1)?));
+    let (long_months, leap_month) =
+        month_structure_for_year::<Chinese>(bounds.new_year, bounds.next_new_year);
+
+    // Months are stored in order, with the leap month (if any) at its own position
+    let ordinal = match (month.leap, leap_month) {
+        (true, Some(leap)) if leap == month.number + 1 => leap,
+        (true, _) => return None,
+        (false, Some(leap)) if month.number >= leap => month.number + 1,
+        (false, _) => month.number,
+    };
+    let length = if long_months[usize::from(ordinal - 1)] {
+        30
+    } else {
+        29
+    };
+
+    if !(1..=length).contains(&day) {
+        return None;
+    }
+
+    let start = bounds.new_year + i64::from(days_until_month::<Chinese>(bounds.new_year, ordinal));
+
+    from_rata_die(start + i64::from(day - 1))
+}
+
+/// Parses dates like "8th month 15th day 2024", "leap 2nd month 1st day 2023", or
+/// festivals like "Lunar New Year 2025".
+pub(crate) fn parse(input: &str) -> IResult<&str, NaiveDate> {
+    map_opt(
+        pair(
+            terminated(alt((parse_festival, parse_month_day)), space1),
+            parse_digits,
+        ),
+        |((month, day), year)| to_gregorian(year, month, day),
+    )(input)
+}
+
+fn parse_festival(input: &str) -> IResult<&str, (Month, u8)> {
+    let month = |number| Month {
+        number,
+        leap: false,
+    };
+
+    alt((
+        value(
+            (month(1), 1),
+            alt((
+                tag_no_case("lunar new year"),
+                tag_no_case("chinese new year"),
+            )),
+        ),
+        value((month(1), 15), tag_no_case("lantern festival")),
+        value((month(5), 5), tag_no_case("dragon boat festival")),
+        value((month(7), 7), tag_no_case("qixi festival")),
+        value((month(8), 15), tag_no_case("mid-autumn festival")),
+        value((month(9), 9), tag_no_case("double ninth festival")),
+    ))(input)
+}
+
+fn parse_month_day(input: &str) -> IResult<&str, (Month, u8)> {
+    map_opt(
+        tuple((
+            opt(terminated(tag_no_case("leap"), space1)),
+            terminated(parse_day_ordinal, preceded(space1, tag_no_case("month"))),
+            preceded(
+                space1,
+                terminated(parse_day_ordinal, preceded(space1, tag_no_case("day"))),
+            ),
+        )),
+        |(leap, month, day)| {
+            if month > 12 || day > 30 {
+                return None;
+            }
+
+            Some((
+                Month {
+                    number: month as u8,
+                    leap: leap.is_some(),
+                },
+                day as u8,
+            ))
+        },
+    )(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_festivals() {
+        assert_eq!(
+            parse("Lunar New Year 2025"),
+            Ok(("", NaiveDate::from_ymd(2025, 1, 29)))
+        );
+        assert_eq!(
+            parse("chinese new year 2023"),
+            Ok(("", NaiveDate::from_ymd(2023, 1, 22)))
+        );
+        assert_eq!(
+            parse("Mid-Autumn Festival 2024"),
+            Ok(("", NaiveDate::from_ymd(2024, 9, 17)))
+        );
+    }
+
+    #[test]
+    fn test_month_day() {
+        assert_eq!(
+            parse("8th month 15th day 2024"),
+            Ok(("", NaiveDate::from_ymd(2024, 9, 17)))
+        );
+        assert_eq!(
+            parse("leap 2nd month 1st day 2023"),
+            Ok(("", NaiveDate::from_ymd(2023, 3, 22)))
+        );
+        assert_eq!(
+            parse("3rd month 1st day 2023"),
+            Ok(("", NaiveDate::from_ymd(2023, 4, 20)))
+        );
+        assert!(parse("leap 3rd month 1st day 2023").is_err());
+        assert!(parse("13th month 1st day 2023").is_err());
+    }
+}
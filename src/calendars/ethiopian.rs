@@ -0,0 +1,33 @@
+use super::to_rata_die;
+use calendrical_calculations::ethiopian::ethiopian_from_fixed;
+use chrono::NaiveDate;
+
+const MONTHS: [&str; 13] = [
+    "Meskerem", "Tikimt", "Hidar", "Tahsas", "Tir", "Yekatit", "Megabit", "Miyazya", "Ginbot",
+    "Sene", "Hamle", "Nehase", "Pagume",
+];
+
+/// Formats a date in the Ethiopian calendar, e.g. "8 Hamle 2014 EC".
+pub(crate) fn format(date: NaiveDate) -> String {
+    match ethiopian_from_fixed(to_rata_die(date)) {
+        Ok((year, month, day)) => {
+            format!("{} {} {} EC", day, MONTHS[usize::from(month - 1)], year)
+        }
+        Err(_) => date.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        assert_eq!(format(NaiveDate::from_ymd(2022, 7, 15)), "8 Hamle 2014 EC");
+        assert_eq!(
+            format(NaiveDate::from_ymd(2023, 9, 12)),
+            "1 Meskerem 2016 EC"
+        );
+        assert_eq!(format(NaiveDate::from_ymd(2023, 9, 11)), "6 Pagume 2015 EC");
+    }
+}
@@ -0,0 +1,107 @@
+//! The tabular (civil) Islamic calendar. Observed month starts depend on moon sightings,
+//! so they may differ from these dates by a day.
+
+use super::from_rata_die;
+use crate::parser_utils::parse_digits;
+use calendrical_calculations::islamic::{
+    fixed_from_tabular_islamic, tabular_islamic_from_fixed, ISLAMIC_EPOCH_FRIDAY,
+};
+use chrono::NaiveDate;
+use nom::{
+    branch::alt,
+    bytes::complete::tag_no_case,
+    character::complete::space1,
+    combinator::{map_opt, opt, value},
+    sequence::{preceded, terminated, tuple},
+    IResult,
+};
+
+fn to_gregorian(year: i32, month: u8, day: u8) -> Option<NaiveDate> {
+    if !(1..=12).contains(&month) || !(1..=30).contains(&day) {
+        return None;
+    }
+
+    let date = fixed_from_tabular_islamic(year, month, day, ISLAMIC_EPOCH_FRIDAY);
+
+    // Day 30 only exists in some months; reject dates that roll into the next month
+    if tabular_islamic_from_fixed(date, ISLAMIC_EPOCH_FRIDAY) != (year, month, day) {
+        return None;
+    }
+
+    from_rata_die(date)
+}
+
+/// Parses dates like "15 Ramadan 1445" or "1 Shawwal 1445 AH".
+pub(crate) fn parse(input: &str) -> IResult<&str, NaiveDate> {
+    map_opt(
+        tuple((
+            terminated(parse_digits, space1),
+            terminated(parse_month, space1),
+            terminated(parse_digits, opt(preceded(space1, tag_no_case("AH")))),
+        )),
+        |(day, month, year)| to_gregorian(year, month, day),
+    )(input)
+}
+
+fn parse_month(input: &str) -> IResult<&str, u8> {
+    alt((
+        value(1, tag_no_case("muharram")),
+        value(2, tag_no_case("safar")),
+        value(
+            3,
+            alt((tag_no_case("rabi al-awwal"), tag_no_case("rabi i"))),
+        ),
+        value(
+            4,
+            alt((tag_no_case("rabi al-thani"), tag_no_case("rabi ii"))),
+        ),
+        value(
+            5,
+            alt((tag_no_case("jumada al-awwal"), tag_no_case("jumada i"))),
+        ),
+        value(
+            6,
+            alt((tag_no_case("jumada al-thani"), tag_no_case("jumada ii"))),
+        ),
+        value(7, tag_no_case("rajab")),
+        value(8, alt((tag_no_case("shaban"), tag_no_case("sha'ban")))),
+        value(9, tag_no_case("ramadan")),
+        value(10, tag_no_case("shawwal")),
+        value(
+            11,
+            alt((tag_no_case("dhu al-qadah"), tag_no_case("dhu al-qi'dah"))),
+        ),
+        value(
+            12,
+            alt((tag_no_case("dhu al-hijjah"), tag_no_case("dhu al-hijja"))),
+        ),
+    ))(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse("15 Ramadan 1445"),
+            Ok(("", NaiveDate::from_ymd(2024, 3, 25)))
+        );
+        assert_eq!(
+            parse("1 muharram 1446 AH"),
+            Ok(("", NaiveDate::from_ymd(2024, 7, 8)))
+        );
+        assert!(parse("31 Ramadan 1445").is_err());
+    }
+
+    #[test]
+    fn test_month_lengths() {
+        // Ramadan 1445 has 30 days in the tabular calendar; Shaban has 29
+        assert_eq!(
+            to_gregorian(1445, 9, 30),
+            Some(NaiveDate::from_ymd(2024, 4, 9))
+        );
+        assert_eq!(to_gregorian(1445, 8, 30), None);
+    }
+}
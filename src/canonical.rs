@@ -0,0 +1,378 @@
+//! Expressions written back out in one standard spelling: ISO dates, digits, and full
+//! words, so the text reads back as the same expression.
+
+use crate::{
+    date_utils, CalculatedDate, DateMath, DayKind, DiffFormat, Edge, MonthOf, Period, PeriodOp,
+    Predicate, Span,
+};
+use chrono::NaiveDate;
+
+const MONTH_NAMES: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+pub(crate) fn render(math: &DateMath) -> String {
+    match math {
+        DateMath::Periods(base, rest) => format!("{}{}", period(*base), operations(rest)),
+        DateMath::Start(date) => calculated_date(date),
+        DateMath::StartWithPeriods(date, base, rest) => format!(
+            "{} {}{}",
+            calculated_date(date),
+            operation(base),
+            operations(rest)
+        ),
+        DateMath::DateDiff(from, to) => {
+            format!("{} - {}", calculated_date(from), calculated_date(to))
+        }
+        DateMath::Schedule(schedule, CalculatedDate::Today, count) => {
+            format!("next {} '{}'", count, schedule)
+        }
+        DateMath::Schedule(schedule, anchor, count) => format!(
+            "next {} '{}' after {}",
+            count,
+            schedule,
+            calculated_date(anchor)
+        ),
+        DateMath::MonthDays(days, None) => format!("next of the {}", ordinals(days.days())),
+        DateMath::MonthDays(days, Some(months)) => format!(
+            "list the {} for the next {} months",
+            ordinals(days.days()),
+            months
+        ),
+        DateMath::Age(CalculatedDate::Today, born) => format!("age for {}", literal(*born)),
+        DateMath::Age(on, born) => {
+            format!("age on {} for {}", calculated_date(on), literal(*born))
+        }
+        DateMath::WeekdayOf(math) => format!("weekday of {}", render(math)),
+        DateMath::Compare(first, second) => {
+            format!("compare {} vs {}", render(first), render(second))
+        }
+        DateMath::Is(subject, predicate) => {
+            format!("is {} {}", render(subject), self::predicate(predicate))
+        }
+        DateMath::Elapsed(span, CalculatedDate::Today) => {
+            format!("percent of {} elapsed", self::span(*span))
+        }
+        DateMath::Elapsed(span, date) => format!(
+            "percent of {} elapsed at {}",
+            self::span(*span),
+            calculated_date(date)
+        ),
+        DateMath::DaysLeft(span) => format!("days left in {}", self::span(*span)),
+        DateMath::DaysRemainingInCycle => "days remaining in cycle".to_string(),
+        DateMath::FractionOfCycleRemaining => "fraction of cycle remaining".to_string(),
+        DateMath::Splunk(time) => time.to_string(),
+        DateMath::TimeRange(range) => range.to_string(),
+        DateMath::DaysBetween(kind, first, second) => format!(
+            "{} between {} and {}",
+            match kind {
+                DayKind::Weekday => "weekdays",
+                DayKind::Weekend => "weekend days",
+            },
+            render(first),
+            render(second)
+        ),
+        DateMath::DiffIn(math, format) => format!("{} in {}", render(math), diff_format(*format)),
+        DateMath::Convert(math, conversion) => {
+            format!("{} in {}", render(math), conversion.name())
+        }
+        DateMath::DaysIn(math) => format!("days in {}", render(math)),
+    }
+}
+
+fn calculated_date(date: &CalculatedDate) -> String {
+    match date {
+        CalculatedDate::Today => "today".to_string(),
+        CalculatedDate::Yesterday => "yesterday".to_string(),
+        CalculatedDate::Tomorrow => "tomorrow".to_string(),
+        CalculatedDate::NextMonthDay(day) => format!("next {}", ordinal(*day)),
+        CalculatedDate::PreviousMonthDay(day) => format!("last {}", ordinal(*day)),
+        CalculatedDate::NextMonth(month) => format!("next {}", month_name(*month)),
+        CalculatedDate::PreviousMonth(month) => format!("last {}", month_name(*month)),
+        CalculatedDate::NextAnniversary(date) => {
+            format!("next anniversary of {}", literal(*date))
+        }
+        CalculatedDate::Boundary(Edge::Start, span) => format!("start of {}", self::span(*span)),
+        CalculatedDate::Boundary(Edge::End, span) => format!("end of {}", self::span(*span)),
+        CalculatedDate::FirstBusinessDayAfter(date) => {
+            format!("first business day after {}", calculated_date(date))
+        }
+        CalculatedDate::LastBusinessDayBefore(date) => {
+            format!("last business day before {}", calculated_date(date))
+        }
+        CalculatedDate::Settlement(days, date) => {
+            format!("T+{} from {}", days, calculated_date(date))
+        }
+        CalculatedDate::BusinessDayOfMonth(nth, month) => {
+            let nth = match nth {
+                1 => "first".to_string(),
+                -1 => "last".to_string(),
+                nth => ordinal(nth.unsigned_abs()),
+            };
+            let month = match month {
+                MonthOf::Relative(1) => "next month".to_string(),
+                MonthOf::Relative(-1) => "last month".to_string(),
+                MonthOf::Relative(_) => "this month".to_string(),
+                MonthOf::Named(month) => month_name(*month).to_string(),
+            };
+
+            format!("{} business day of {}", nth, month)
+        }
+        CalculatedDate::UpcomingWeekday(weekday) => weekday_name(*weekday),
+        CalculatedDate::NextWeekend => "next weekend".to_string(),
+        CalculatedDate::PreviousWeekend => "last weekend".to_string(),
+        CalculatedDate::WeekdayOfWeek(weekday, weeks) => {
+            let week = match weeks {
+                1 => "next",
+                -1 => "last",
+                _ => "this",
+            };
+
+            format!("{} of {} week", weekday_name(*weekday), week)
+        }
+        CalculatedDate::Term(Edge::Start, name) => format!("start of {} term", name),
+        CalculatedDate::Term(Edge::End, name) => format!("end of {} term", name),
+        CalculatedDate::NextOccurrence(name) => format!("next {}", name),
+        CalculatedDate::Jql(date) => date.to_string(),
+        CalculatedDate::Sprint(Edge::Start, number) => format!("sprint {} start", number),
+        CalculatedDate::Sprint(Edge::End, number) => format!("sprint {} end", number),
+        CalculatedDate::BillingDate(1) => "next billing date".to_string(),
+        CalculatedDate::BillingDate(cycles) => format!("billing date in {} cycles", cycles),
+        CalculatedDate::MonthAndDay(month, day) => format!("{} {}", month_name(*month), day),
+        CalculatedDate::Raw(date) => literal(*date),
+    }
+}
+
+// ISO dates, with a signed year before AD 1, e.g. "-0043-03-15".
+fn literal(date: NaiveDate) -> String {
+    date.to_string()
+}
+
+fn period(period: Period) -> String {
+    period.to_string()
+}
+
+fn operation(op: &PeriodOp) -> String {
+    op.to_string()
+}
+
+fn operations(ops: &[PeriodOp]) -> String {
+    ops.iter().map(|op| format!(" {}", operation(op))).collect()
+}
+
+fn predicate(predicate: &Predicate) -> String {
+    match predicate {
+        Predicate::Before(math) => format!("before {}", render(math)),
+        Predicate::OnOrBefore(math) => format!("on or before {}", render(math)),
+        Predicate::On(math) => format!("on {}", render(math)),
+        Predicate::OnOrAfter(math) => format!("on or after {}", render(math)),
+        Predicate::After(math) => format!("after {}", render(math)),
+        Predicate::Between(first, second) => {
+            format!("between {} and {}", render(first), render(second))
+        }
+    }
+}
+
+fn span(span: Span) -> String {
+    match span {
+        Span::Week => "week".to_string(),
+        Span::Month => "month".to_string(),
+        Span::Year => "year".to_string(),
+        Span::Quarter(None) => "quarter".to_string(),
+        Span::Quarter(Some(quarter)) => format!("Q{}", quarter),
+    }
+}
+
+// Only weeks and days and months have a spelling; other formats come from configuration
+// rather than from expressions.
+fn diff_format(format: DiffFormat) -> &'static str {
+    match format {
+        DiffFormat::Months => "months",
+        DiffFormat::WeeksAndDays | DiffFormat::Days | DiffFormat::Auto => "weeks and days",
+    }
+}
+
+fn ordinals(days: &[u32]) -> String {
+    let days: Vec<String> = days.iter().map(|day| ordinal(*day)).collect();
+
+    match &days[..] {
+        [init @ .., last] if !init.is_empty() => format!("{} and {}", init.join(", "), last),
+        _ => days.join(""),
+    }
+}
+
+fn ordinal(day: u32) -> String {
+    let suffix = match (day % 10, day % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+
+    format!("{}{}", day, suffix)
+}
+
+fn month_name(month: u32) -> &'static str {
+    MONTH_NAMES[(month as usize).saturating_sub(1).min(11)]
+}
+
+fn weekday_name(weekday: chrono::Weekday) -> String {
+    date_utils::weekday_name(weekday).to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse;
+    use nom::combinator::all_consuming;
+
+    fn canonical(input: &str) -> String {
+        all_consuming(parse)(input).unwrap().1.to_canonical_string()
+    }
+
+    #[test]
+    fn test_canonical_spelling() {
+        assert_eq!(
+            canonical("Mar 31, 2021 + 15 weeks + 2 days"),
+            "2021-03-31 + 15 weeks + 2 days"
+        );
+        assert_eq!(canonical("two weeks ago"), "today - 2 weeks");
+        assert_eq!(
+            canonical("1 year, 2 months, and 3 days from now"),
+            "today + 1 year + 2 months + 3 days"
+        );
+        assert_eq!(
+            canonical("earlier of eom and T+2"),
+            "compare end of month vs T+2 from today"
+        );
+        assert_eq!(canonical("March 15, 44 BC"), "-0043-03-15");
+        assert_eq!(
+            canonical("list the 1st, 10th, and 22nd for the next month"),
+            "list the 1st, 10th and 22nd for the next 1 months"
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for input in &[
+            "today",
+            "now + 1.5 months - 2.0 months",
+            "a couple of weeks + a few days",
+            "half a year ago",
+            "2 weeks and 1 day before January 15, 2022",
+            "next 31st - 2 days",
+            "last 2nd",
+            "next March",
+            "last dec",
+            "next anniversary of 2020-02-29",
+            "bow",
+            "end of this year",
+            "this Q3",
+            "start of the quarter",
+            "first business day after July 4, 2025",
+            "last business day before end of month",
+            "T+2",
+            "T+1 from 2024-03-01 + 1 week",
+            "3rd business day of next month",
+            "last business day of April",
+            "first business day of this month",
+            "friday",
+            "tuesday of last week",
+            "last weekend + 1 day",
+            "2 weeks + 3 days in days",
+            "days in 3 months from 2022-01-31",
+            "serial 44575",
+            "-0043-03-15 + 2 days",
+            "Jan 1, 1 AD - Dec 31, 1 BC",
+            "today - 2022-01-01 in weeks and days",
+            "Mar 31, 2021 - Jan 6, 2021 in months",
+            "age for 1990-05-04",
+            "age on next March for 1990-05-04",
+            "weekday of today + 3 days",
+            "compare today vs 2 weeks ago",
+            "is today after 2022-06-01",
+            "is tomorrow between today and 1 week from now",
+            "is 1 week ago on or before yesterday",
+            "next '0 9 * * MON'",
+            "next 3 '*/15 8-17 1,15 * *' after January 15, 2022",
+            "next of the 1st and 15th",
+            "next of the 30th",
+            "list the 1st and 15th for the next 6 months",
+            "percent of year elapsed",
+            "percent of Q3 elapsed at 2022-08-15",
+            "days left in the month",
+            "weekdays between today and end of month",
+            "3 weeks into the Fall term",
+            "next billing date + 1 day",
+            "sprint 7 start",
+            "today + 3w 2d 4h 30m of work - 1d of work",
+            "endOfMonth(-1) + 1 day",
+            "-7d@d",
+            "now-6h/h to now with length",
+            "from=1648737300000&to=now/d",
+            "@w1-2h@h",
+            "startOfWeek(+3d) - -7d",
+            "5 business days from tomorrow",
+            "next Release - 2 days",
+            "2 sprints from sprint 3 end",
+            "a few sprints ago",
+            "days remaining in cycle",
+            "fraction of cycle remaining",
+            "billing date in 1 cycle",
+            "billing date in 3 cycles - today",
+            "end of spring-2025 term - today",
+            "weekend days between 2 weeks ago and 2022-03-01",
+        ] {
+            let (_, math) = all_consuming(parse)(input).unwrap();
+            let rendered = math.to_canonical_string();
+
+            assert_eq!(
+                all_consuming(parse)(&rendered).map(|(_, math)| math),
+                Ok(math),
+                "{} rendered as {}",
+                input,
+                rendered
+            );
+        }
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_round_trip_grammar_examples() {
+        for section in crate::syntax::GRAMMAR {
+            if let "Dates" | "Periods" = section.title {
+                continue;
+            }
+
+            for example in section
+                .forms
+                .iter()
+                .copied()
+                .flatten()
+                .flat_map(|form| form.examples)
+            {
+                let (_, math) = all_consuming(parse)(example).unwrap();
+                let rendered = math.to_canonical_string();
+
+                assert_eq!(
+                    all_consuming(parse)(&rendered).map(|(_, math)| math),
+                    Ok(math),
+                    "{} rendered as {}",
+                    example,
+                    rendered
+                );
+            }
+        }
+    }
+}
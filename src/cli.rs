@@ -1,31 +1,1373 @@
-use crate::{calculated_date, parse, ParseResult};
-use chrono::NaiveDate;
-use structopt::StructOpt;
+#[cfg(feature = "calendars")]
+use crate::calendars::OutputCalendar;
+use crate::{
+    calculated_date::{self, CalculatedDate},
+    calendar_grid, date_utils, did_you_mean, difference, document,
+    json::{self, ToJson},
+    parse,
+    period_operation::PeriodOp,
+    predicate, sql, syntax,
+    template::{self, Template},
+    time_unit, Assumption, Cadence, ComputeError, ComputeOutcome, Config, DateMath, DiffFormat,
+    FractionalMonthPolicy, FuzzyQuantities, HolidayList, LeapDayPolicy, MarketCalendar,
+    MonthEndConvention, ParseResult, ShortMonthPolicy, Thresholds,
+};
+use chrono::{
+    format::{Item, StrftimeItems},
+    DateTime, Datelike, FixedOffset, Local, NaiveDate, NaiveDateTime, TimeZone, Timelike, Utc,
+    Weekday,
+};
+use clap::{
+    builder::PossibleValuesParser, builder::TypedValueParser, Parser, Subcommand, ValueEnum,
+};
+use nom::combinator::all_consuming;
+use std::fs::File;
+use std::io::{BufRead, BufReader, IsTerminal};
+use std::str::FromStr;
 
-#[derive(Debug, StructOpt)]
+#[derive(Debug, Parser)]
+#[command(name = "date-math", version, about, subcommand_negates_reqs = true)]
 struct Flags {
-    value: String,
+    /// Expressions to evaluate, each printed on its own line
+    #[arg(required_unless_present_any = ["sort", "filter", "check", "file", "null", "help_syntax"])]
+    value: Vec<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// The date expressions are evaluated against, instead of the current date or $TODAY
+    #[arg(long, value_parser = parse_date_flag)]
+    today: Option<NaiveDate>,
+
+    /// The date and time expressions are evaluated at, e.g. '2022-01-31 10:30', instead of
+    /// the current date or $NOW; schedules starting today only list times after it
+    #[arg(long, conflicts_with = "today", value_parser = parse_now_flag)]
+    now: Option<NaiveDateTime>,
+
+    /// An expression evaluated first and used as the reference date, e.g. 'end of the
+    /// month'
+    #[arg(long, conflicts_with = "today")]
+    base: Option<String>,
+
+    /// How expressions are read: 'auto' for the full grammar, which also accepts
+    /// Elasticsearch date math like 'now-1d/d', or 'es' for Elasticsearch date math alone
+    #[arg(long, value_enum, default_value_t = Dialect::Auto)]
+    dialect: Dialect,
+
+    /// How to resolve days like the 31st in months too short to contain them
+    #[arg(long, default_value = "skip", value_parser = one_of::<ShortMonthPolicy>(&["clamp", "skip"]))]
+    short_months: ShortMonthPolicy,
+
+    /// Where anniversaries of February 29 fall in non-leap years
+    #[arg(long, default_value = "feb28", value_parser = one_of::<LeapDayPolicy>(&["feb28", "mar1"]))]
+    leap_day: LeapDayPolicy,
+
+    /// The day weeks begin on, for expressions like "tuesday of next week"
+    #[arg(long, default_value = "monday", value_parser = parse_weekday_flag)]
+    week_start: Weekday,
+
+    /// The day weekends begin on, for "next weekend" and "last weekend"
+    #[arg(long, default_value = "saturday", value_parser = parse_weekday_flag)]
+    weekend_start: Weekday,
+
+    /// A date to skip when counting business days; may be repeated
+    #[arg(long = "holiday", value_parser = parse_date_flag)]
+    holidays: Vec<NaiveDate>,
+
+    /// An exchange whose holidays are also skipped when counting business days
+    #[arg(long, value_parser = one_of::<MarketCalendar>(&["target2", "nyse", "lse"]))]
+    holiday_calendar: Option<MarketCalendar>,
+
+    /// A named term, e.g. a semester, as NAME=START..END; may be repeated
+    #[arg(long = "term", value_name = "NAME=START..END", value_parser = parse_term_flag)]
+    terms: Vec<(String, NaiveDate, NaiveDate)>,
+
+    /// The day of the month billing cycles renew on, for "next billing date"
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..=31))]
+    billing_day: Option<u32>,
+
+    /// The length of a sprint, in days or weeks, e.g. "2w" or "10d"
+    #[arg(long, requires = "sprint_anchor", value_parser = parse_sprint_length)]
+    sprint_length: Option<usize>,
+
+    /// The date sprint 1 starts on
+    #[arg(long, requires = "sprint_length", value_parser = parse_date_flag)]
+    sprint_anchor: Option<NaiveDate>,
+
+    /// A named cadence, e.g. "release=every 6 weeks from 2022-01-11", for expressions like
+    /// "next release"; may be repeated
+    #[arg(long = "cadence", value_name = "NAME=RULE", value_parser = parse_cadence_flag)]
+    cadences: Vec<(String, Cadence)>,
+
+    /// Working days in a week, for working time like "3w 2d of work"
+    #[arg(long, default_value_t = 5, value_parser = clap::value_parser!(u32).range(1..=7))]
+    days_per_week: u32,
+
+    /// Working hours in a day, for working time like "2d 4h"
+    #[arg(long, default_value_t = 8, value_parser = clap::value_parser!(u32).range(1..=24))]
+    hours_per_day: u32,
+
+    /// How fractional months like "1.5 months" are resolved
+    #[arg(long, default_value = "reject", value_parser = one_of::<FractionalMonthPolicy>(&["reject", "30days"]))]
+    fractional_months: FractionalMonthPolicy,
+
+    /// Accept vague quantities like "a couple of days" and "a few weeks", and correct
+    /// likely typos
+    #[arg(long)]
+    lenient: bool,
+
+    /// Treat months as 30 days and years as 365, rather than stepping through the
+    /// calendar
+    #[arg(long)]
+    approximate: bool,
+
+    /// How many units "a few" means with --lenient
+    #[arg(long, default_value_t = 3)]
+    few: usize,
+
+    /// Report assumptions made while computing, such as the meaning of "a few" or a
+    /// corrected typo
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// How differences between dates are reported; 'months' counts complete calendar
+    /// months, rounded down
+    #[arg(long, visible_alias = "unit", default_value = "days", value_parser = one_of::<DiffFormat>(&["days", "auto", "weeks", "months"]))]
+    diff: DiffFormat,
+
+    /// Day counts at which '--diff auto' switches to weeks, months, and years
+    #[arg(long, default_value = "14,60,365", value_name = "weeks,months,years")]
+    diff_thresholds: Thresholds,
+
+    /// How months are counted between two month-ends: 'calendar' goes by day of the
+    /// month, while 'month-end' counts January 31 to February 28 as a month
+    #[arg(long, default_value = "calendar", value_parser = one_of::<MonthEndConvention>(&["calendar", "month-end"]))]
+    month_ends: MonthEndConvention,
+
+    /// The calendar computed dates are displayed in
+    #[cfg(feature = "calendars")]
+    #[arg(long, default_value = "gregorian", value_parser = one_of::<OutputCalendar>(&["gregorian", "thai", "ethiopian", "nepali"]))]
+    calendar: OutputCalendar,
+
+    /// A strftime pattern computed dates are printed with, e.g. '%A, %B %-d'
+    #[arg(long, value_parser = parse_format)]
+    format: Option<String>,
+
+    /// A template computed dates are printed with, e.g. 'Due {date} ({weekday})'; the
+    /// placeholders are {date}, {weekday}, {iso_week}, {doy}, {epoch} (seconds, in
+    /// --zone), and {diff} (from today, e.g. "in 3 days")
+    #[arg(long, conflicts_with = "format")]
+    template: Option<Template>,
+
+    /// How computed dates are printed: as text, highlighted in a calendar of their month,
+    /// as a UTC timestamp for Taskwarrior's 'due:', as a remind(1) reminder, as a SQL
+    /// literal, as a spreadsheet serial number, as JavaScript epoch milliseconds, or as a
+    /// JSON, YAML, or TOML document. Differences can be printed as a Postgres interval
+    /// with 'pg-interval'. Given more than once, or as 'all' (ISO date, epoch
+    /// seconds, RFC 3339, and weekday), each is printed on its own labeled line
+    #[arg(long, value_enum, default_values_t = [Output::Plain])]
+    output: Vec<Output>,
+
+    /// When to color output, like the highlighted date of '--output cal': 'auto' colors it
+    /// on a terminal unless $NO_COLOR is set
+    #[arg(long, value_enum, default_value_t = Color::Auto)]
+    color: Color,
+
+    /// With '--output remind', the reminder's message
+    #[arg(long, default_value = "Reminder")]
+    message: String,
+
+    /// With '--output js-ms' or 'all', the time zone computed times are in: 'utc',
+    /// 'local', or an offset like '+05:30'
+    #[arg(long, default_value = "utc", value_parser = parse_zone, allow_hyphen_values = true)]
+    zone: Zone,
+
+    /// Print the day of the week alongside computed dates
+    #[arg(long)]
+    weekday: bool,
+
+    /// Print how far computed dates are from today, e.g. "(in 165 days)"
+    #[arg(long)]
+    also_relative: bool,
+
+    /// Print nothing; exit 0 if the computed date is after today and 1 otherwise
+    #[arg(long, conflicts_with = "anchors")]
+    test: bool,
+
+    /// Evaluate the expression against each date in this file (one per line; '-' reads stdin)
+    #[arg(long)]
+    anchors: Option<String>,
+
+    /// Only parse the expression (or each line of stdin when none is given), reporting
+    /// whether it's valid without evaluating it
+    #[arg(long, conflicts_with_all = ["anchors", "sort", "filter", "test"])]
+    check: bool,
+
+    /// Read dates or expressions from stdin and print them in chronological order
+    #[arg(long, conflicts_with_all = ["value", "anchors"])]
+    sort: bool,
+
+    /// With --sort, print each original line next to its computed date
+    #[arg(long, requires = "sort")]
+    keep_line: bool,
+
+    /// Read dates or expressions from stdin and print only the lines matching a predicate
+    /// like "after 3 weeks ago", "on or before today", or "between X and Y"
+    #[arg(long, conflicts_with_all = ["value", "anchors", "sort"])]
+    filter: Option<String>,
+
+    /// Evaluate each line of this file as an expression, skipping blank lines and
+    /// comments starting with '#'
+    #[arg(short, long, conflicts_with_all = ["value", "anchors", "sort", "filter", "check"])]
+    file: Option<String>,
+
+    /// Separate expressions read from stdin (or --file) with NUL bytes rather than
+    /// newlines, and end each result with one, for use with 'xargs -0'
+    #[arg(short = '0', long, conflicts_with_all = ["anchors", "sort", "filter", "check", "test", "watch"])]
+    null: bool,
+
+    /// Keep re-evaluating the expressions and reprinting the results, like a live countdown
+    #[arg(long, conflicts_with_all = ["test", "anchors", "sort", "filter", "check", "file", "null"])]
+    watch: bool,
+
+    /// With --watch, the seconds between evaluations; by default results are refreshed
+    /// at local midnight
+    #[arg(long, requires = "watch", value_name = "SECONDS")]
+    interval: Option<u64>,
+
+    /// Print the forms an expression can take, with examples
+    #[arg(long)]
+    help_syntax: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Output {
+    Plain,
+    Cal,
+    Taskwarrior,
+    Remind,
+    Sql,
+    PgInterval,
+    ExcelSerial,
+    JsMs,
+    Json,
+    Yaml,
+    Toml,
+    All,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Color {
+    Auto,
+    Always,
+    Never,
+}
+
+impl Color {
+    fn enabled(self) -> bool {
+        match self {
+            Color::Always => true,
+            Color::Never => false,
+            Color::Auto => {
+                std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+                    && std::io::stdout().is_terminal()
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Dialect {
+    Auto,
+    Es,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Zone {
+    Utc,
+    Local,
+    Fixed(FixedOffset),
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Add periods to a date, e.g. 'add today 2 weeks'
+    Add {
+        date: String,
+
+        #[arg(required = true)]
+        periods: Vec<String>,
+
+        /// Subtract the periods instead
+        #[arg(long)]
+        subtract: bool,
+    },
+
+    /// Count the time between two dates
+    Diff {
+        from: String,
+
+        to: String,
+
+        /// How the difference is reported, overriding --diff
+        #[arg(long, visible_alias = "unit", value_parser = one_of::<DiffFormat>(&["days", "auto", "weeks", "months"]))]
+        format: Option<DiffFormat>,
+    },
+
+    /// Print how an expression is interpreted without evaluating it
+    Parse {
+        expression: String,
+
+        /// Print the interpretation as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Evaluate an expression one step at a time
+    Explain {
+        expression: String,
+
+        /// Combine adjacent days and weeks first, e.g. '+ 2 weeks + 1 week - 3 days' as
+        /// '+ 18 days'
+        #[arg(long)]
+        simplify: bool,
+    },
 }
 
 pub fn run() {
-    let flags = Flags::from_args();
-    let today = today_from_env().unwrap_or(chrono::Local::today().naive_local());
+    let flags = Flags::parse();
+    let config = build_config(&flags);
+    let today = current_today(&flags, &config);
+
+    match &flags.command {
+        Some(Command::Add {
+            date,
+            periods,
+            subtract,
+        }) => {
+            let operator = if *subtract { "-" } else { "+" };
+            let expression = format!("{} {} {}", date, operator, periods.join(" "));
+
+            if !run_expression(&expression, today, &config, &flags) {
+                std::process::exit(1)
+            }
+        }
+        Some(Command::Diff { from, to, format }) => {
+            let config = match format {
+                Some(format) => config.diff_format(*format),
+                None => config,
+            };
+
+            match (parse_anchor(from), parse_anchor(to)) {
+                (Ok(from), Ok(to)) => {
+                    if !print_outcome(&DateMath::DateDiff(from, to), today, &config, &flags) {
+                        std::process::exit(1)
+                    }
+                }
+                (Err(e), _) | (_, Err(e)) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1)
+                }
+            }
+        }
+        Some(Command::Parse { expression, json }) => {
+            let math = parse_or_exit(expression, &config, &flags);
+
+            if *json {
+                println!("{}", math.to_json());
+            } else {
+                println!("{:#?}", math);
+            }
+        }
+        Some(Command::Explain {
+            expression,
+            simplify,
+        }) => {
+            let math = parse_or_exit(expression, &config, &flags);
+            let math = if *simplify {
+                math.combine_like_units()
+            } else {
+                math
+            };
+
+            match explain(&math, today, &config) {
+                Ok(steps) => steps.iter().for_each(|step| println!("{}", step)),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1)
+                }
+            }
+        }
+        None if flags.help_syntax => print!("{}", syntax::render()),
+        None if flags.check => run_check(&config, &flags),
+        None if flags.sort => run_sort(today, &config, &flags),
+        None if flags.watch => run_watch(&config, &flags),
+        None if flags.file.is_some() || (flags.null && flags.value.is_empty()) => {
+            run_file(flags.file.as_deref().unwrap_or("-"), today, &config, &flags)
+        }
+        None => match &flags.filter {
+            Some(predicate) => run_filter(predicate, today, &config, &flags),
+            None => {
+                // Every expression is evaluated, even after one fails.
+                let mut failed = false;
+
+                for expression in &flags.value {
+                    failed |= !run_expression(expression, today, &config, &flags);
+                }
+
+                if failed {
+                    std::process::exit(1)
+                }
+            }
+        },
+    }
+}
+
+fn current_today(flags: &Flags, config: &Config) -> NaiveDate {
+    let local_today = chrono::Local::today().naive_local();
+    let today = flags
+        .today
+        .or_else(|| current_now(flags).map(|now| now.date()))
+        .or_else(|| today_from_env(local_today, config))
+        .unwrap_or(local_today);
 
-    match parse(&flags.value).into() {
-        ParseResult::Success(math) => println!("{}", math.compute(today)),
-        ParseResult::PartialSuccess(math, unparsed) => {
-            eprintln!("Unparsed input: '{}'", unparsed);
-            println!("{}", math.compute(today));
+    match &flags.base {
+        Some(base) => {
+            match parse_or_exit(base, config, flags).try_compute_with_config(today, config) {
+                Ok(outcome) => as_date(&outcome).unwrap_or_else(|| {
+                    eprintln!("--base must be a date, not '{}'", outcome);
+                    std::process::exit(1)
+                }),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1)
+                }
+            }
         }
-        ParseResult::Error(e) => {
+        None => today,
+    }
+}
+
+fn current_now(flags: &Flags) -> Option<NaiveDateTime> {
+    flags.now.or_else(|| {
+        std::env::var("NOW")
+            .ok()
+            .and_then(|value| parse_now_flag(&value).ok())
+    })
+}
+
+fn build_config(flags: &Flags) -> Config {
+    let holidays = HolidayList::new(flags.holidays.clone());
+    let config = Config::default()
+        .short_months(flags.short_months)
+        .leap_day(flags.leap_day)
+        .diff_format(flags.diff)
+        .thresholds(flags.diff_thresholds)
+        .month_ends(flags.month_ends)
+        .fractional_months(flags.fractional_months)
+        .week_start(flags.week_start)
+        .weekend_start(flags.weekend_start)
+        .working_time(flags.days_per_week as usize, flags.hours_per_day as usize)
+        .approximate(flags.approximate);
+    let config = match flags.holiday_calendar {
+        Some(market) => config.holidays((holidays, market)),
+        None => config.holidays(holidays),
+    };
+    let config = flags
+        .terms
+        .iter()
+        .fold(config, |config, (name, start, end)| {
+            config.term(name, *start, *end)
+        });
+    let config = match flags.billing_day {
+        Some(day) => config.billing_day(day),
+        None => config,
+    };
+    let config = match (flags.sprint_anchor, flags.sprint_length) {
+        (Some(start), Some(days)) => config.sprints(start, days),
+        _ => config,
+    };
+    let config = flags
+        .cadences
+        .iter()
+        .fold(config, |config, (name, cadence)| {
+            config.cadence(name, cadence.clone())
+        });
+    let config = match current_now(flags) {
+        Some(now) => config.time(now.time()),
+        None => config,
+    };
+
+    if flags.lenient {
+        config.lenient(FuzzyQuantities {
+            few: flags.few,
+            ..FuzzyQuantities::default()
+        })
+    } else {
+        config
+    }
+}
+
+/// Prints the outcome of an expression, returning whether it succeeded; an error or a
+/// false comparison is a failure.
+fn run_expression(expression: &str, today: NaiveDate, config: &Config, flags: &Flags) -> bool {
+    let math = match parse_or_report(expression, config, flags) {
+        Some(math) => math,
+        None => return false,
+    };
+
+    // Approximations the user didn't ask for are always worth pointing out.
+    if !flags.approximate || flags.verbose {
+        for (period, days) in math.approximations(config) {
+            eprintln!("Approximating {} as {}", period, days);
+        }
+    }
+
+    match &flags.anchors {
+        Some(path) => {
+            run_anchors(&math, path, config, flags);
+            true
+        }
+        None => print_outcome(&math, today, config, flags),
+    }
+}
+
+fn print_outcome(math: &DateMath, today: NaiveDate, config: &Config, flags: &Flags) -> bool {
+    match math.try_compute_with_config(today, config) {
+        Ok(outcome) if flags.test => run_test(&outcome, today),
+        Ok(outcome) => {
+            print_record(&render(&outcome, today, config, flags), flags);
+
+            outcome != ComputeOutcome::Boolean(false)
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            false
+        }
+    }
+}
+
+fn parse_or_exit(expression: &str, config: &Config, flags: &Flags) -> DateMath {
+    parse_or_report(expression, config, flags).unwrap_or_else(|| std::process::exit(1))
+}
+
+/// Parses an expression, reporting input that's left over or can't be parsed at all.
+fn parse_or_report(expression: &str, config: &Config, flags: &Flags) -> Option<DateMath> {
+    match read_expression(expression, config, flags) {
+        Ok(reading) => {
+            for warning in reading.warnings(flags) {
+                eprintln!("{}", warning);
+            }
+
+            Some(reading.math)
+        }
+        Err(e) => {
             eprintln!("{}", e);
+            None
+        }
+    }
+}
+
+/// An expression as it was read, with what was assumed in reading it and a note on any
+/// input left over after it.
+struct Reading {
+    math: DateMath,
+    assumptions: Vec<Assumption>,
+    unparsed: Option<String>,
+}
+
+impl Reading {
+    fn new(math: DateMath) -> Reading {
+        Reading {
+            math,
+            assumptions: vec![],
+            unparsed: None,
+        }
+    }
+
+    // Assumptions are only worth mentioning with --verbose; left over input always is.
+    fn warnings(&self, flags: &Flags) -> Vec<String> {
+        self.assumptions
+            .iter()
+            .filter(|_| flags.verbose)
+            .map(|assumption| format!("Assuming {}", assumption))
+            .chain(self.unparsed.clone())
+            .collect()
+    }
+}
+
+/// Reads an expression in the dialect the flags ask for, correcting it with --lenient, or
+/// explains why it can't be read at all. Every way of passing expressions in goes through
+/// here, so they're all read alike.
+fn read_expression(expression: &str, config: &Config, flags: &Flags) -> Result<Reading, String> {
+    if flags.dialect == Dialect::Es {
+        return crate::parse_elasticsearch(expression)
+            .map(Reading::new)
+            .map_err(|e| {
+                format!(
+                    "Unable to parse '{}' as Elasticsearch date math: {}",
+                    expression.trim(),
+                    e
+                )
+            });
+    }
+
+    let lenient = if flags.lenient {
+        crate::parse_lenient(expression, config)
+    } else {
+        None
+    };
+
+    if let Some(lenient) = lenient {
+        return Ok(Reading {
+            assumptions: lenient.assumptions,
+            ..Reading::new(lenient.math)
+        });
+    }
+
+    match parse(expression).into() {
+        ParseResult::Success(math) => Ok(Reading::new(math)),
+        ParseResult::PartialSuccess(math, unparsed) => Ok(Reading {
+            unparsed: Some(format!(
+                "Unparsed input: '{}'{}",
+                unparsed,
+                suggestion(unparsed)
+            )),
+            ..Reading::new(math)
+        }),
+        ParseResult::Error(_) => Err(format!(
+            "Unable to parse '{}'{}",
+            expression.trim(),
+            suggestion(expression)
+        )),
+    }
+}
+
+fn suggestion(input: &str) -> String {
+    if let Some(unit) = time_unit(input) {
+        return format!(
+            " (times are kept to the minute, so '{}' can't be used)",
+            unit
+        );
+    }
+
+    match did_you_mean(input) {
+        Some((unknown, known)) => format!(" (did you mean '{}' instead of '{}'?)", known, unknown),
+        None => String::new(),
+    }
+}
+
+/// Each intermediate date of an expression built from periods, alongside the operation
+/// that produced it. Other expressions are a single step.
+fn explain(
+    math: &DateMath,
+    today: NaiveDate,
+    config: &Config,
+) -> Result<Vec<String>, ComputeError> {
+    // Evaluating first reports dates out of range before any step is taken.
+    let outcome = math.try_compute_with_config(today, config)?;
+
+    let (start, first, rest) = match math {
+        // Clock time moves the time of day, which the dates of each step don't show.
+        _ if matches!(outcome, ComputeOutcome::DateTime(_)) => {
+            return Ok(vec![outcome.to_string()])
+        }
+        DateMath::StartWithPeriods(date, first, rest) => {
+            (date.calculate(today, config)?, first.resolve(config)?, rest)
+        }
+        DateMath::Periods(first, rest) => (today, PeriodOp::Add(first.resolve(config)?), rest),
+        _ => {
+            return Ok(vec![math
+                .try_compute_with_config(today, config)?
+                .to_string()])
+        }
+    };
+
+    let mut steps = vec![format!("{}  start", start)];
+    let mut date = start;
+
+    for op in std::iter::once(Ok(first)).chain(rest.iter().map(|op| op.resolve(config))) {
+        let op = op?;
+        date = op
+            .apply_with_config(date, config)
+            .ok_or(ComputeError::OutOfRange)?;
+        steps.push(format!("{}  {}", date, op));
+    }
+
+    Ok(steps)
+}
+
+fn run_anchors(math: &DateMath, path: &str, config: &Config, flags: &Flags) {
+    let lines = match read_lines(path) {
+        Ok(lines) => lines,
+        Err(e) => {
+            eprintln!("Unable to read '{}': {}", path, e);
+            std::process::exit(1)
+        }
+    };
+
+    let mut failed = false;
+
+    for (number, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let outcome = parse_date_flag(line.trim()).and_then(|anchor| {
+            math.try_compute_with_config(anchor, config)
+                .map(|outcome| (anchor, outcome))
+                .map_err(|e| e.to_string())
+        });
+
+        match outcome {
+            Ok((anchor, outcome)) => println!("{}", render(&outcome, anchor, config, flags)),
+            Err(e) => {
+                eprintln!("Line {}: {}", number + 1, e);
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        std::process::exit(1)
+    }
+}
+
+fn run_watch(config: &Config, flags: &Flags) -> ! {
+    let expressions: Vec<DateMath> = flags
+        .value
+        .iter()
+        .map(|v| parse_or_exit(v, config, flags))
+        .collect();
+
+    loop {
+        let today = current_today(flags, config);
+
+        // Clear the terminal so each refresh replaces the last
+        print!("\x1b[2J\x1b[H");
+
+        for math in &expressions {
+            match math.try_compute_with_config(today, config) {
+                Ok(outcome) => println!("{}", render(&outcome, today, config, flags)),
+                Err(e) => eprintln!("{}", e),
+            }
+        }
+
+        let seconds = flags.interval.unwrap_or_else(|| {
+            86_400 - u64::from(chrono::Local::now().num_seconds_from_midnight())
+        });
+
+        std::thread::sleep(std::time::Duration::from_secs(seconds.max(1)));
+    }
+}
+
+fn run_file(path: &str, today: NaiveDate, config: &Config, flags: &Flags) {
+    // A record may wrap over several lines, each break standing in for a space.
+    let (lines, unit) = if flags.null {
+        let records = read_records(path, b'\0').map(|records| {
+            records
+                .iter()
+                .map(|record| record.split_whitespace().collect::<Vec<_>>().join(" "))
+                .collect()
+        });
+
+        (records, "Record")
+    } else {
+        (read_lines(path), "Line")
+    };
+    let lines = match lines {
+        Ok(lines) => lines,
+        Err(e) => {
+            eprintln!("Unable to read '{}': {}", path, e);
             std::process::exit(1)
         }
+    };
+
+    let mut failed = false;
+
+    for (number, line) in lines.iter().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let outcome = read_expression(line, config, flags).and_then(|reading| {
+            for warning in reading.warnings(flags) {
+                eprintln!("{} {}: {}", unit, number + 1, warning);
+            }
+
+            reading
+                .math
+                .try_compute_with_config(today, config)
+                .map_err(|e| e.to_string())
+        });
+
+        match outcome {
+            Ok(outcome) => print_record(&render(&outcome, today, config, flags), flags),
+            Err(e) => {
+                eprintln!("{} {}: {}", unit, number + 1, e);
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        std::process::exit(1)
+    }
+}
+
+// With -0, each result ends in a NUL byte, so results spanning several lines (like
+// lists) stay one record each.
+fn print_record(record: &str, flags: &Flags) {
+    if flags.null {
+        print!("{}\0", record);
+    } else {
+        println!("{}", record);
+    }
+}
+
+fn run_check(config: &Config, flags: &Flags) {
+    let values = &flags.value;
+    let lines = match values.as_slice() {
+        [] => match read_lines("-") {
+            Ok(lines) => lines,
+            Err(e) => {
+                eprintln!("Unable to read stdin: {}", e);
+                std::process::exit(1)
+            }
+        },
+        values => values.to_vec(),
+    };
+
+    let mut failed = false;
+
+    for (number, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        // Input left over after an expression is a problem here, even though evaluating
+        // it would only warn.
+        let problem = match read_expression(line.trim(), config, flags) {
+            Ok(Reading { unparsed: None, .. }) => continue,
+            Ok(Reading {
+                unparsed: Some(problem),
+                ..
+            })
+            | Err(problem) => problem,
+        };
+
+        failed = true;
+
+        if !values.is_empty() {
+            eprintln!("{}", problem);
+        } else {
+            eprintln!("Line {}: {}", number + 1, problem);
+        }
+    }
+
+    if failed {
+        std::process::exit(1)
+    }
+
+    println!("ok");
+}
+
+fn run_test(outcome: &ComputeOutcome, today: NaiveDate) -> ! {
+    match outcome.date() {
+        Some(date) if date > today => std::process::exit(0),
+        Some(_) => std::process::exit(1),
+        None => {
+            eprintln!("--test requires an expression that evaluates to a date");
+            std::process::exit(2)
+        }
+    }
+}
+
+fn run_sort(today: NaiveDate, config: &Config, flags: &Flags) {
+    let lines = match read_lines("-") {
+        Ok(lines) => lines,
+        Err(e) => {
+            eprintln!("Unable to read stdin: {}", e);
+            std::process::exit(1)
+        }
+    };
+
+    let mut failed = false;
+    let mut dated = Vec::with_capacity(lines.len());
+
+    for (number, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match evaluate_date(line.trim(), number, today, config, flags) {
+            Ok(date) => dated.push((date, line)),
+            Err(e) => {
+                eprintln!("Line {}: {}", number + 1, e);
+                failed = true;
+            }
+        }
+    }
+
+    dated.sort_by_key(|(date, _)| *date);
+
+    for (date, line) in dated {
+        let rendered = render(&ComputeOutcome::Date(date), today, config, flags);
+
+        if flags.keep_line {
+            println!("{}\t{}", rendered, line);
+        } else {
+            println!("{}", rendered);
+        }
+    }
+
+    if failed {
+        std::process::exit(1)
+    }
+}
+
+fn run_filter(predicate: &str, today: NaiveDate, config: &Config, flags: &Flags) {
+    let predicate = match all_consuming(predicate::parse)(predicate) {
+        Ok((_, predicate)) => predicate,
+        Err(_) => {
+            eprintln!("Unable to parse filter: '{}'", predicate);
+            std::process::exit(1)
+        }
+    };
+
+    let lines = match read_lines("-") {
+        Ok(lines) => lines,
+        Err(e) => {
+            eprintln!("Unable to read stdin: {}", e);
+            std::process::exit(1)
+        }
+    };
+
+    let mut failed = false;
+
+    for (number, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let matched = evaluate_date(line.trim(), number, today, config, flags).and_then(|date| {
+            predicate
+                .matches(date, today, config)
+                .map_err(|e| e.to_string())
+        });
+
+        match matched {
+            Ok(true) => println!("{}", line),
+            Ok(false) => {}
+            Err(e) => {
+                eprintln!("Line {}: {}", number + 1, e);
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        std::process::exit(1)
+    }
+}
+
+/// The date the expression on line `number` (counting from 0) of stdin stands for,
+/// reporting anything worth a warning along the way.
+fn evaluate_date(
+    value: &str,
+    number: usize,
+    today: NaiveDate,
+    config: &Config,
+    flags: &Flags,
+) -> Result<NaiveDate, String> {
+    let reading = read_expression(value, config, flags)?;
+
+    for warning in reading.warnings(flags) {
+        eprintln!("Line {}: {}", number + 1, warning);
+    }
+
+    reading
+        .math
+        .try_compute_with_config(today, config)
+        .map_err(|e| e.to_string())?
+        .date()
+        .ok_or_else(|| format!("'{}' does not evaluate to a date", value))
+}
+
+fn read_lines(path: &str) -> std::io::Result<Vec<String>> {
+    if path == "-" {
+        std::io::stdin().lock().lines().collect()
+    } else {
+        BufReader::new(File::open(path)?).lines().collect()
+    }
+}
+
+fn read_records(path: &str, delimiter: u8) -> std::io::Result<Vec<String>> {
+    let reader: Box<dyn BufRead> = if path == "-" {
+        Box::new(std::io::stdin().lock())
+    } else {
+        Box::new(BufReader::new(File::open(path)?))
+    };
+
+    reader
+        .split(delimiter)
+        .map(|record| {
+            String::from_utf8(record?)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+        })
+        .collect()
+}
+
+fn render(outcome: &ComputeOutcome, today: NaiveDate, config: &Config, flags: &Flags) -> String {
+    match (outcome, flags.output.as_slice()) {
+        (_, [output]) if *output != Output::All => {
+            render_as(outcome, *output, today, config, flags)
+        }
+        (ComputeOutcome::List(outcomes), _) => outcomes
+            .iter()
+            .map(|outcome| render(outcome, today, config, flags))
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        (ComputeOutcome::Date(_) | ComputeOutcome::DateTime(_), outputs) => outputs
+            .iter()
+            .flat_map(|output| labeled(outcome, *output, today, config, flags))
+            .map(|(label, value)| {
+                // Multi-line outputs, like calendars and documents, start below their label.
+                if value.contains('\n') {
+                    format!("{}:\n{}", label, value)
+                } else {
+                    format!("{}: {}", label, value)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => render_as(outcome, Output::Plain, today, config, flags),
     }
 }
 
-fn today_from_env() -> Option<NaiveDate> {
-    std::env::var("TODAY")
-        .ok()
-        .and_then(|v| calculated_date::parse_date(&v))
+// A computed date in one output, labeled with its name; 'all' expands to several lines.
+fn labeled(
+    outcome: &ComputeOutcome,
+    output: Output,
+    today: NaiveDate,
+    config: &Config,
+    flags: &Flags,
+) -> Vec<(String, String)> {
+    let (datetime, iso) = match outcome {
+        ComputeOutcome::Date(date) => (date.and_hms(0, 0, 0), date.to_string()),
+        ComputeOutcome::DateTime(datetime) => {
+            (*datetime, datetime.format("%Y-%m-%dT%H:%M:%S").to_string())
+        }
+        _ => return vec![],
+    };
+
+    if output == Output::All {
+        let zoned = in_zone(datetime, flags.zone);
+
+        vec![
+            ("iso".to_string(), iso),
+            ("epoch".to_string(), zoned.timestamp().to_string()),
+            ("rfc3339".to_string(), zoned.to_rfc3339()),
+            (
+                "weekday".to_string(),
+                date_utils::weekday_name(datetime.weekday()).to_string(),
+            ),
+        ]
+    } else {
+        let label = output
+            .to_possible_value()
+            .map_or_else(String::new, |value| value.get_name().to_string());
+
+        vec![(label, render_as(outcome, output, today, config, flags))]
+    }
+}
+
+fn render_as(
+    outcome: &ComputeOutcome,
+    output: Output,
+    today: NaiveDate,
+    config: &Config,
+    flags: &Flags,
+) -> String {
+    let result = || {
+        json::object(vec![
+            ("today", today.to_json()),
+            ("result", outcome.to_json()),
+        ])
+    };
+
+    match outcome {
+        _ if output == Output::Json => result().to_string(),
+        _ if output == Output::Yaml => document::to_yaml(&result()).trim_end().to_string(),
+        _ if output == Output::Toml => match document::to_toml(&result()) {
+            Some(toml) => toml.trim_end().to_string(),
+            None => {
+                eprintln!("The result has a list with an empty value, which TOML can't hold");
+                std::process::exit(1)
+            }
+        },
+        ComputeOutcome::Date(date) if flags.template.is_some() => {
+            fill_template(date.and_hms(0, 0, 0), today, config, flags)
+        }
+        ComputeOutcome::DateTime(datetime) if flags.template.is_some() => {
+            fill_template(*datetime, today, config, flags)
+        }
+        ComputeOutcome::Date(date) if output == Output::Cal => {
+            calendar_grid::render(*date, today, flags.color.enabled())
+        }
+        ComputeOutcome::Date(date) if output == Output::Taskwarrior => {
+            taskwarrior(date.and_hms(0, 0, 0))
+        }
+        ComputeOutcome::DateTime(datetime) if output == Output::Taskwarrior => {
+            taskwarrior(*datetime)
+        }
+        ComputeOutcome::Date(date) if output == Output::Remind => {
+            format!("REM {} MSG {}", date.format("%-d %b %Y"), flags.message)
+        }
+        ComputeOutcome::DateTime(datetime) if output == Output::Remind => format!(
+            "REM {} AT {} MSG {}",
+            datetime.format("%-d %b %Y"),
+            datetime.format("%H:%M"),
+            flags.message
+        ),
+        ComputeOutcome::Date(date) if output == Output::Sql => format!("DATE '{}'", date),
+        ComputeOutcome::DateTime(datetime) if output == Output::Sql => {
+            format!("TIMESTAMP '{}'", datetime.format("%Y-%m-%d %H:%M:%S"))
+        }
+        ComputeOutcome::Date(date) if output == Output::ExcelSerial => {
+            match date_utils::to_excel_serial(*date) {
+                Some(serial) => serial.to_string(),
+                None => date.to_string(),
+            }
+        }
+        ComputeOutcome::DateTime(datetime) if output == Output::ExcelSerial => {
+            match date_utils::to_excel_serial(datetime.date()) {
+                Some(serial) => {
+                    let fraction = f64::from(datetime.num_seconds_from_midnight()) / 86_400.0;
+                    format!("{}", serial as f64 + fraction)
+                }
+                None => datetime.to_string(),
+            }
+        }
+        ComputeOutcome::Date(date) if output == Output::JsMs => {
+            epoch_millis(date.and_hms(0, 0, 0), flags.zone).to_string()
+        }
+        ComputeOutcome::DateTime(datetime) if output == Output::JsMs => {
+            epoch_millis(*datetime, flags.zone).to_string()
+        }
+        ComputeOutcome::Date(date) => {
+            #[cfg(feature = "calendars")]
+            let mut rendered = flags.calendar.format(*date);
+            #[cfg(not(feature = "calendars"))]
+            let mut rendered = date.to_string();
+
+            if let Some(format) = &flags.format {
+                rendered = date.format(format).to_string();
+            }
+
+            if flags.weekday {
+                rendered = format!("{} {}", rendered, date_utils::weekday_name(date.weekday()));
+            }
+
+            if flags.also_relative {
+                if let Ok(relative) = difference::relative(*date, today, config) {
+                    rendered = format!("{} ({})", rendered, relative);
+                }
+            }
+
+            rendered
+        }
+        ComputeOutcome::List(outcomes) => outcomes
+            .iter()
+            .map(|outcome| render_as(outcome, output, today, config, flags))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ if output == Output::PgInterval => {
+            sql::interval(outcome).unwrap_or_else(|| outcome.to_string())
+        }
+        _ => outcome.to_string(),
+    }
+}
+
+// Taskwarrior stores times in UTC, so local times are converted before printing.
+fn taskwarrior(local: NaiveDateTime) -> String {
+    let utc = match Local.from_local_datetime(&local).earliest() {
+        Some(datetime) => datetime.with_timezone(&Utc).naive_utc(),
+        None => local,
+    };
+
+    utc.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn fill_template(
+    datetime: NaiveDateTime,
+    today: NaiveDate,
+    config: &Config,
+    flags: &Flags,
+) -> String {
+    let diff = difference::relative(datetime.date(), today, config).unwrap_or_default();
+    let values = template::Values {
+        date: datetime.date(),
+        epoch: epoch_millis(datetime, flags.zone).div_euclid(1000),
+        diff: &diff,
+    };
+
+    flags
+        .template
+        .as_ref()
+        .map_or_else(String::new, |template| template.render(&values))
+}
+
+fn epoch_millis(datetime: NaiveDateTime, zone: Zone) -> i64 {
+    in_zone(datetime, zone).timestamp_millis()
+}
+
+fn in_zone(datetime: NaiveDateTime, zone: Zone) -> DateTime<FixedOffset> {
+    let local = match zone {
+        Zone::Utc => None,
+        Zone::Local => Local
+            .from_local_datetime(&datetime)
+            .earliest()
+            .map(DateTime::from),
+        Zone::Fixed(offset) => offset.from_local_datetime(&datetime).earliest(),
+    };
+
+    local.unwrap_or_else(|| Utc.from_utc_datetime(&datetime).into())
+}
+
+// $TODAY may be a literal date or any expression that evaluates to one, like "2 weeks
+// ago"; values that are neither are ignored.
+fn today_from_env(today: NaiveDate, config: &Config) -> Option<NaiveDate> {
+    let value = std::env::var("TODAY").ok()?;
+
+    calculated_date::parse_date(&value).or_else(|| {
+        let (_, math) = all_consuming(parse)(value.trim()).ok()?;
+        as_date(&math.try_compute_with_config(today, config).ok()?)
+    })
+}
+
+fn as_date(outcome: &ComputeOutcome) -> Option<NaiveDate> {
+    match outcome {
+        ComputeOutcome::Date(date) => Some(*date),
+        ComputeOutcome::DateTime(datetime) => Some(datetime.date()),
+        _ => None,
+    }
+}
+
+fn parse_anchor(value: &str) -> Result<CalculatedDate, String> {
+    match all_consuming(calculated_date::parse)(value) {
+        Ok((_, date)) => Ok(date),
+        Err(_) => Err(format!("unable to parse date: '{}'", value)),
+    }
+}
+
+// Restricts a flag to the given values, parsing the chosen one into its type.
+fn one_of<T>(values: &'static [&'static str]) -> impl TypedValueParser<Value = T>
+where
+    T: FromStr<Err = String> + Clone + Send + Sync + 'static,
+{
+    PossibleValuesParser::new(values.iter().copied())
+        .map(|value| value.parse().expect("every possible value parses"))
+}
+
+fn parse_format(value: &str) -> Result<String, String> {
+    if StrftimeItems::new(value).any(|item| item == Item::Error) {
+        Err(format!("invalid date format: '{}'", value))
+    } else {
+        Ok(value.to_string())
+    }
+}
+
+fn parse_zone(value: &str) -> Result<Zone, String> {
+    let invalid = || format!("unable to parse time zone: '{}'", value);
+
+    match value.to_lowercase().as_str() {
+        "utc" | "z" => Ok(Zone::Utc),
+        "local" => Ok(Zone::Local),
+        offset => {
+            let (sign, rest) = match (offset.strip_prefix('+'), offset.strip_prefix('-')) {
+                (Some(rest), _) => (1, rest),
+                (_, Some(rest)) => (-1, rest),
+                _ => return Err(invalid()),
+            };
+            let (hours, minutes) = rest.split_once(':').unwrap_or((rest, "0"));
+            let hours: u8 = hours.parse().map_err(|_| invalid())?;
+            let minutes: u8 = minutes.parse().map_err(|_| invalid())?;
+
+            FixedOffset::east_opt(sign * (i32::from(hours) * 3600 + i32::from(minutes) * 60))
+                .filter(|_| minutes < 60)
+                .map(Zone::Fixed)
+                .ok_or_else(invalid)
+        }
+    }
+}
+
+fn parse_now_flag(value: &str) -> Result<NaiveDateTime, String> {
+    [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(value.trim(), format).ok())
+    .ok_or_else(|| format!("unable to parse date and time: '{}'", value))
+}
+
+fn parse_weekday_flag(value: &str) -> Result<Weekday, String> {
+    value
+        .parse()
+        .map_err(|_| format!("unable to parse weekday: '{}'", value))
+}
+
+fn parse_term_flag(value: &str) -> Result<(String, NaiveDate, NaiveDate), String> {
+    let error = || {
+        format!(
+            "unable to parse term: '{}'; expected NAME=START..END",
+            value
+        )
+    };
+    let (name, dates) = value.split_once('=').ok_or_else(error)?;
+    let (start, end) = dates.split_once("..").ok_or_else(error)?;
+
+    match calculated_date::parse_term(&format!("{} term", name)) {
+        Ok(("", name)) => Ok((name, parse_date_flag(start)?, parse_date_flag(end)?)),
+        _ => Err(error()),
+    }
+}
+
+fn parse_cadence_flag(value: &str) -> Result<(String, Cadence), String> {
+    let (name, rule) = value
+        .split_once('=')
+        .ok_or_else(|| format!("unable to parse cadence: '{}'; expected NAME=RULE", value))?;
+
+    match (
+        calculated_date::parse(&format!("next {}", name)),
+        rule.parse(),
+    ) {
+        (Ok(("", CalculatedDate::NextOccurrence(name))), Ok(cadence)) => Ok((name, cadence)),
+        (_, Ok(_)) => Err(format!("unable to use '{}' as a cadence name", name)),
+        (_, Err(_)) => Err(format!("unable to parse cadence rule: '{}'", rule)),
+    }
+}
+
+fn parse_sprint_length(value: &str) -> Result<usize, String> {
+    let (count, days) = match value.strip_suffix('w') {
+        Some(weeks) => (weeks, 7),
+        None => (value.strip_suffix('d').unwrap_or(value), 1),
+    };
+
+    match count.parse::<usize>() {
+        Ok(count) if count > 0 => Ok(count * days),
+        _ => Err(format!("unable to parse sprint length: '{}'", value)),
+    }
+}
+
+fn parse_date_flag(value: &str) -> Result<NaiveDate, String> {
+    match calculated_date::parse_literal(value) {
+        Ok(("", date)) => Ok(date),
+        _ => Err(format!("unable to parse date: '{}'", value)),
+    }
 }
@@ -0,0 +1,59 @@
+use chrono::{Local, NaiveDate};
+
+/// Where the reference date comes from, so code built on date math can be tested
+/// against a fixed date.
+pub trait TodaySource {
+    fn today(&self) -> NaiveDate;
+}
+
+/// The current date in the local time zone.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemToday;
+
+impl TodaySource for SystemToday {
+    fn today(&self) -> NaiveDate {
+        Local::today().naive_local()
+    }
+}
+
+/// The same date every time, for tests.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FixedToday(pub NaiveDate);
+
+impl TodaySource for FixedToday {
+    fn today(&self) -> NaiveDate {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, ComputeOutcome};
+
+    #[test]
+    fn test_compute_with_fixed_today() {
+        let today = FixedToday(NaiveDate::from_ymd(2022, 1, 31));
+        let (_, math) = parse("2 weeks from now").unwrap();
+
+        assert_eq!(
+            math.compute_with(&today),
+            ComputeOutcome::Date(NaiveDate::from_ymd(2022, 2, 14))
+        );
+    }
+
+    #[test]
+    fn test_year_from_fixed_today() {
+        let today = FixedToday(NaiveDate::from_ymd(2022, 3, 1));
+        let (_, math) = parse("Mar 1 - 2 days").unwrap();
+
+        assert_eq!(
+            math.compute_with(&today),
+            ComputeOutcome::Date(NaiveDate::from_ymd(2022, 2, 27))
+        );
+        assert_eq!(
+            parse("feb 29").unwrap().1.compute_with(&today),
+            ComputeOutcome::Date(NaiveDate::from_ymd(2022, 2, 28))
+        );
+    }
+}
@@ -0,0 +1,287 @@
+//! Expressions prepared for evaluating many times, with everything that doesn't depend
+//! on the reference date worked out once up front.
+
+use crate::{
+    date_utils,
+    visit::{self, Visit},
+    CalculatedDate, ComputeError, ComputeOutcome, Config, DateMath, Period, PeriodOp, Step,
+};
+use chrono::{Datelike, NaiveDate};
+use std::convert::TryFrom;
+
+pub(crate) enum Compiled<'a> {
+    /// The outcome of an expression that never looks at the reference date
+    Constant(Result<ComputeOutcome, ComputeError>),
+    /// Periods applied to a date, or to the reference date itself when there's none
+    Operations(Option<&'a CalculatedDate>, Vec<Operation>),
+    Dynamic(&'a DateMath),
+}
+
+/// A resolved period, with runs of day-based periods merged into one.
+pub(crate) enum Operation {
+    Days(i64),
+    Calendar(PeriodOp),
+}
+
+impl<'a> Compiled<'a> {
+    pub(crate) fn new(math: &'a DateMath, config: &Config) -> Self {
+        if !math.literals_in_supported_range() {
+            return Compiled::Constant(Err(ComputeError::OutOfRange));
+        }
+
+        if let Err(e) = math.check_configuration(config) {
+            return Compiled::Constant(Err(e));
+        }
+
+        if !uses_today(math) {
+            // Any date in range will do, since the expression ignores it.
+            let today = NaiveDate::from_ymd(2000, 1, 1);
+            return Compiled::Constant(math.try_compute_with_config(today, config));
+        }
+
+        // Clock time gives a date and time, which only the full evaluation works out.
+        let clock_time = math
+            .periods()
+            .into_iter()
+            .any(|period| matches!(period.resolve(config), Ok(Period::Minute(_))));
+
+        let (anchor, operations) = match math {
+            _ if clock_time => return Compiled::Dynamic(math),
+            DateMath::Periods(base, rest) => {
+                let base = PeriodOp::Add(*base);
+                (
+                    None,
+                    operations(Some(&base).into_iter().chain(rest), config),
+                )
+            }
+            DateMath::StartWithPeriods(date, base, rest) => (
+                Some(date),
+                operations(Some(base).into_iter().chain(rest), config),
+            ),
+            math => return Compiled::Dynamic(math),
+        };
+
+        match operations {
+            Ok(operations) => Compiled::Operations(anchor, operations),
+            Err(e) => Compiled::Constant(Err(e)),
+        }
+    }
+
+    pub(crate) fn evaluate(
+        &self,
+        today: NaiveDate,
+        config: &Config,
+    ) -> Result<ComputeOutcome, ComputeError> {
+        match self {
+            Compiled::Constant(outcome) => outcome.clone(),
+            Compiled::Operations(anchor, operations) => {
+                if !date_utils::in_supported_range(today) {
+                    return Err(ComputeError::OutOfRange);
+                }
+
+                let start = anchor.map_or(Ok(today), |date| date.calculate(today, config))?;
+
+                operations
+                    .iter()
+                    .try_fold(start, |date, operation| operation.apply(date, config))
+                    .filter(|date| date_utils::in_supported_range(*date))
+                    .map(ComputeOutcome::Date)
+                    .ok_or(ComputeError::OutOfRange)
+            }
+            Compiled::Dynamic(math) => math.try_compute_with_config(today, config),
+        }
+    }
+}
+
+impl Operation {
+    fn apply(&self, date: NaiveDate, config: &Config) -> Option<NaiveDate> {
+        match self {
+            Operation::Days(days) => NaiveDate::from_num_days_from_ce_opt(
+                date.num_days_from_ce()
+                    .checked_add(i32::try_from(*days).ok()?)?,
+            ),
+            Operation::Calendar(op) => op.apply_with_config(date, config),
+        }
+    }
+}
+
+fn operations<'a>(
+    ops: impl IntoIterator<Item = &'a PeriodOp>,
+    config: &Config,
+) -> Result<Vec<Operation>, ComputeError> {
+    let mut operations: Vec<Operation> = vec![];
+
+    for op in ops {
+        let op = op.resolve(config)?;
+        let days = match (op.period().to_step(config)?, &op) {
+            (Step::Days(days), PeriodOp::Add(_)) => i64::try_from(days).ok(),
+            (Step::Days(days), PeriodOp::Subtract(_)) => i64::try_from(days).ok().map(|v| -v),
+            _ => {
+                operations.push(Operation::Calendar(op));
+                continue;
+            }
+        };
+        let days = days.ok_or(ComputeError::OutOfRange)?;
+
+        // As in `combine`, a run stops growing once its total passes what the supported
+        // years span, so a step that would fail part way still fails.
+        let within = |total: &i64| total.abs() <= date_utils::SUPPORTED_DAYS;
+        let merged = match operations.last() {
+            Some(Operation::Days(total)) if within(total) => total.checked_add(days).filter(within),
+            _ => None,
+        };
+
+        match (merged, operations.last_mut()) {
+            (Some(merged), Some(Operation::Days(total))) => *total = merged,
+            _ => operations.push(Operation::Days(days)),
+        }
+    }
+
+    Ok(operations)
+}
+
+// Only literal dates are fixed; everything else is worked out from the reference date.
+fn uses_today(math: &DateMath) -> bool {
+    struct UsesToday(bool);
+
+    impl Visit for UsesToday {
+        fn visit_date_math(&mut self, math: &DateMath) {
+            // Schedules and predicates may compare against the reference date directly.
+            if let DateMath::Periods(_, _)
+            | DateMath::Schedule(_, _, _)
+            | DateMath::Is(_, _)
+            | DateMath::DaysLeft(_)
+            | DateMath::DaysRemainingInCycle
+            | DateMath::FractionOfCycleRemaining
+            | DateMath::MonthDays(_, _)
+            | DateMath::Splunk(_)
+            | DateMath::TimeRange(_) = math
+            {
+                self.0 = true;
+            }
+
+            visit::walk_date_math(self, math)
+        }
+
+        fn visit_calculated_date(&mut self, date: &CalculatedDate) {
+            match date {
+                CalculatedDate::Raw(_)
+                | CalculatedDate::FirstBusinessDayAfter(_)
+                | CalculatedDate::LastBusinessDayBefore(_)
+                | CalculatedDate::Settlement(_, _)
+                | CalculatedDate::Term(_, _) => {}
+                _ => self.0 = true,
+            }
+
+            visit::walk_calculated_date(self, date)
+        }
+    }
+
+    let mut uses_today = UsesToday(false);
+    uses_today.visit_date_math(math);
+    uses_today.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, FractionalMonthPolicy};
+
+    fn compiled(input: &str, config: &Config) -> Vec<Result<ComputeOutcome, ComputeError>> {
+        let (_, math) = parse(input).unwrap();
+        let compiled = Compiled::new(&math, config);
+
+        dates()
+            .map(|today| compiled.evaluate(today, config))
+            .collect()
+    }
+
+    fn computed(input: &str, config: &Config) -> Vec<Result<ComputeOutcome, ComputeError>> {
+        let (_, math) = parse(input).unwrap();
+
+        dates()
+            .map(|today| math.try_compute_with_config(today, config))
+            .collect()
+    }
+
+    fn dates() -> impl Iterator<Item = NaiveDate> {
+        vec![
+            NaiveDate::from_ymd(2022, 1, 31),
+            NaiveDate::from_ymd(2024, 2, 29),
+            NaiveDate::from_ymd(9999, 12, 20),
+        ]
+        .into_iter()
+    }
+
+    #[test]
+    fn test_matches_compute() {
+        let configs = vec![
+            Config::default(),
+            Config::default().term(
+                "Fall",
+                NaiveDate::from_ymd(2022, 8, 29),
+                NaiveDate::from_ymd(2022, 12, 16),
+            ),
+            Config::default().approximate(true),
+            Config::default().fractional_months(FractionalMonthPolicy::ThirtyDays),
+        ];
+
+        for config in &configs {
+            for input in &[
+                "2 weeks + 3 days - 1 day",
+                "3 days + 1 month + 2 days",
+                "1 year ago",
+                "end of the month + 1 week + 1 day",
+                "Jan 31, 2022 + 1 month + 3 days",
+                "Mar 31, 2021 - Mar 24, 2021",
+                "today - Jan 1, 2022",
+                "weekday of 2022-03-01",
+                "1.5 months ago",
+                "days left in Q3",
+                "9999-12-31 + 1 day",
+                "2 weeks into fall term",
+                "end of spring term",
+                "fraction of cycle remaining",
+                "today + 100000000 days - 100000000 days",
+                "today + 5000000 days - 5000000 days",
+            ] {
+                assert_eq!(
+                    compiled(input, config),
+                    computed(input, config),
+                    "{}",
+                    input
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_merges_days() {
+        let (_, math) = parse("1 week + 3 days - 1 day + 1 month + 2 days").unwrap();
+
+        match Compiled::new(&math, &Config::default()) {
+            Compiled::Operations(None, operations) => {
+                let days: Vec<Option<i64>> = operations
+                    .iter()
+                    .map(|operation| match operation {
+                        Operation::Days(days) => Some(*days),
+                        Operation::Calendar(_) => None,
+                    })
+                    .collect();
+
+                assert_eq!(days, vec![Some(9), None, Some(2)]);
+            }
+            _ => panic!("expected operations"),
+        }
+    }
+
+    #[test]
+    fn test_literal_expressions_are_constant() {
+        let (_, math) = parse("Mar 31, 2021 - Mar 24, 2021").unwrap();
+
+        assert!(matches!(
+            Compiled::new(&math, &Config::default()),
+            Compiled::Constant(Ok(ComputeOutcome::DifferenceInDays(7)))
+        ));
+    }
+}
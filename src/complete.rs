@@ -0,0 +1,103 @@
+//! Suggestions for finishing a partially typed expression, for interactive UIs built on
+//! the grammar.
+
+use crate::parse;
+use nom::combinator::all_consuming;
+
+/// Phrases that can follow part of an expression. Placeholders in angle brackets stand
+/// for something the user fills in themselves.
+const CONTINUATIONS: &[&str] = &[
+    "today",
+    "tomorrow",
+    "yesterday",
+    "now",
+    "<date>",
+    "day",
+    "days",
+    "week",
+    "weeks",
+    "month",
+    "months",
+    "year",
+    "years",
+    "ago",
+    "from now",
+    "from today",
+    "from tomorrow",
+    "from <date>",
+    "after <date>",
+    "before <date>",
+    "on <date>",
+    "on or after <date>",
+    "on or before <date>",
+    "between <date> and <date>",
+    "vs <expression>",
+    "and <expression>",
+    "+ <period>",
+    "- <period>",
+    "in weeks and days",
+    "month elapsed",
+    "year elapsed",
+    "quarter elapsed",
+];
+
+// Stand-ins for placeholders when checking that a continuation fits the grammar.
+const SAMPLES: &[(&str, &str)] = &[
+    ("<date>", "2022-01-01"),
+    ("<period>", "1 day"),
+    ("<expression>", "today"),
+];
+
+/// Continuations of `input` that would make it a complete expression, e.g. "from now"
+/// for "2 weeks fr". The last word of the input is treated as unfinished unless the
+/// input ends with a space.
+pub fn completions(input: &str) -> Vec<String> {
+    let split = input.rfind(' ').map_or(0, |index| index + 1);
+    let (finished, partial) = input.split_at(split);
+
+    CONTINUATIONS
+        .iter()
+        .filter(|continuation| continuation.starts_with(partial))
+        .filter(|continuation| {
+            let candidate = SAMPLES.iter().fold(
+                format!("{}{}", finished, continuation),
+                |acc, (from, to)| acc.replace(from, to),
+            );
+
+            is_complete(&candidate)
+        })
+        .map(|continuation| continuation.to_string())
+        .collect()
+}
+
+fn is_complete(expression: &str) -> bool {
+    all_consuming(parse)(expression).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_word() {
+        assert_eq!(
+            completions("2 weeks fr"),
+            vec!["from now", "from today", "from tomorrow", "from <date>"]
+        );
+        assert_eq!(completions("2 we"), vec!["week", "weeks"]);
+    }
+
+    #[test]
+    fn test_next_word() {
+        let suggestions = completions("is today ");
+
+        assert!(suggestions.contains(&"after <date>".to_string()));
+        assert!(suggestions.contains(&"between <date> and <date>".to_string()));
+        assert!(!suggestions.contains(&"ago".to_string()));
+    }
+
+    #[test]
+    fn test_nothing_fits() {
+        assert!(completions("2 weeks zz").is_empty());
+    }
+}
@@ -0,0 +1,208 @@
+//! Lenient parsing that reports what it had to assume, so callers can decide whether to
+//! trust a result automatically or confirm it with the user first.
+
+use crate::{parse, parse_all, token, Config, DateMath, Period, TokenKind};
+use nom::combinator::all_consuming;
+
+/// Something taken for granted to make sense of an expression.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Assumption {
+    /// An unknown word was read as the known word closest to it
+    Corrected { from: String, to: &'static str },
+    /// A date was written without a year, e.g. "March 5"
+    DefaultYear(String),
+    /// A date like "5/4/2021" was read month first
+    DateOrder(String),
+    /// A vague quantity was read as a specific count
+    VagueQuantity(Period, Period),
+}
+
+impl Assumption {
+    // How much less likely the result is to be what was meant.
+    fn weight(&self) -> f64 {
+        match self {
+            Assumption::Corrected { .. } => 0.8,
+            Assumption::DefaultYear(_) => 0.9,
+            Assumption::DateOrder(_) => 0.5,
+            Assumption::VagueQuantity(_, _) => 0.7,
+        }
+    }
+}
+
+impl std::fmt::Display for Assumption {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Assumption::Corrected { from, to } => write!(f, "'{}' means '{}'", from, to),
+            Assumption::DefaultYear(date) => write!(f, "'{}' is in the current year", date),
+            Assumption::DateOrder(reading) => write!(f, "month first, reading {}", reading),
+            Assumption::VagueQuantity(vague, assumed) => {
+                write!(f, "'{}' means {}", vague, assumed)
+            }
+        }
+    }
+}
+
+/// An expression parsed leniently, with what was assumed along the way.
+#[derive(Debug, PartialEq)]
+pub struct LenientParse {
+    pub math: DateMath,
+    /// From 0 to 1, where 1 means nothing was assumed
+    pub confidence: f64,
+    pub assumptions: Vec<Assumption>,
+}
+
+/// Parses an expression, correcting likely typos when it doesn't parse as written.
+/// Vague quantities are read with the counts in `config`, or the defaults when it isn't
+/// lenient.
+pub fn parse_lenient(input: &str, config: &Config) -> Option<LenientParse> {
+    let config = config.clone().lenient(config.fuzzy.unwrap_or_default());
+    let mut assumptions = vec![];
+    let text = if all_consuming(parse)(input).is_ok() {
+        input.to_string()
+    } else {
+        correct(input, &mut assumptions)
+    };
+
+    let interpretation = parse_all(&text).into_iter().next()?;
+    assumptions.extend(
+        interpretation
+            .readings
+            .into_iter()
+            .map(Assumption::DateOrder),
+    );
+    assumptions.extend(default_years(&text));
+    assumptions.extend(
+        interpretation
+            .math
+            .assumptions(&config)
+            .into_iter()
+            .map(|(vague, assumed)| Assumption::VagueQuantity(vague, assumed)),
+    );
+
+    Some(LenientParse {
+        confidence: assumptions.iter().map(Assumption::weight).product(),
+        math: interpretation.math,
+        assumptions,
+    })
+}
+
+// `input` with each unknown word replaced by the closest known one.
+fn correct(input: &str, assumptions: &mut Vec<Assumption>) -> String {
+    let mut text = input.to_string();
+    let mut corrections = vec![];
+
+    for word in token::tokenize(input)
+        .into_iter()
+        .filter(|token| token.kind == TokenKind::Word)
+    {
+        if let Some(known) = token::closest_known(word.text) {
+            corrections.push((word.span, known));
+            assumptions.push(Assumption::Corrected {
+                from: word.text.to_string(),
+                to: known,
+            });
+        }
+    }
+
+    // Replaced from the end, so earlier spans stay valid.
+    for (span, known) in corrections.into_iter().rev() {
+        text.replace_range(span, known);
+    }
+
+    text
+}
+
+// Dates written as a month and day with no year after them, e.g. "March 5".
+fn default_years(input: &str) -> Vec<Assumption> {
+    let tokens = token::tokenize(input);
+
+    tokens
+        .windows(2)
+        .enumerate()
+        .filter(|(_, pair)| pair[0].kind == TokenKind::Month && pair[1].kind == TokenKind::Number)
+        .filter(|(index, _)| {
+            !matches!(
+                tokens.get(index + 2..index + 4),
+                Some([comma, year]) if comma.text == "," && year.kind == TokenKind::Number
+            )
+        })
+        .map(|(_, pair)| {
+            Assumption::DefaultYear(input[pair[0].span.start..pair[1].span.end].to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FuzzyQuantities, Quantity, Unit};
+    use smallvec::smallvec;
+
+    fn assumed(input: &str) -> Vec<String> {
+        parse_lenient(input, &Config::default())
+            .unwrap()
+            .assumptions
+            .iter()
+            .map(Assumption::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_nothing_assumed() {
+        let parsed = parse_lenient("Mar 1, 2022 + 2 weeks", &Config::default()).unwrap();
+
+        assert_eq!(parsed.confidence, 1.0);
+        assert!(parsed.assumptions.is_empty());
+    }
+
+    #[test]
+    fn test_typo_corrections() {
+        let parsed = parse_lenient("2 wekks ago", &Config::default()).unwrap();
+
+        assert_eq!(parsed.math, parse("2 weeks ago").unwrap().1);
+        assert_eq!(
+            parsed.assumptions,
+            vec![Assumption::Corrected {
+                from: "wekks".to_string(),
+                to: "weeks"
+            }]
+        );
+        assert!((parsed.confidence - 0.8).abs() < 1e-9);
+        assert_eq!(parse_lenient("2 xyzzy ago", &Config::default()), None);
+    }
+
+    #[test]
+    fn test_dates() {
+        assert_eq!(
+            assumed("March 5 + 2 days"),
+            vec!["'March 5' is in the current year"]
+        );
+        assert!(assumed("March 5, 2022 + 2 days").is_empty());
+        assert_eq!(
+            assumed("5/4/2021"),
+            vec!["month first, reading 5/4/2021 as May 4, 2021"]
+        );
+    }
+
+    #[test]
+    fn test_vague_quantities() {
+        let config = Config::default().lenient(FuzzyQuantities { couple: 2, few: 4 });
+        let parsed = parse_lenient("a few weeks", &config).unwrap();
+
+        assert_eq!(
+            parsed.math,
+            DateMath::Periods(
+                Period::Approximately(Quantity::Few, Unit::Week),
+                smallvec![]
+            )
+        );
+        assert_eq!(
+            parsed.assumptions[0].to_string(),
+            "'a few weeks' means 4 weeks"
+        );
+        assert_eq!(
+            assumed("a cuople of days + 1 day"),
+            vec!["'cuople' means 'couple'", "'a couple of days' means 2 days"]
+        );
+    }
+}
@@ -0,0 +1,346 @@
+use crate::{
+    cadence::Cadence,
+    holidays::{HolidayCalendar, NoHolidays},
+    period::Quantity,
+};
+use chrono::{NaiveDate, NaiveTime, Weekday};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub(crate) short_months: ShortMonthPolicy,
+    pub(crate) leap_day: LeapDayPolicy,
+    pub(crate) holidays: Arc<dyn HolidayCalendar>,
+    pub(crate) diff_format: DiffFormat,
+    pub(crate) month_ends: MonthEndConvention,
+    pub(crate) thresholds: Thresholds,
+    pub(crate) fractional_months: FractionalMonthPolicy,
+    pub(crate) approximate: bool,
+    pub(crate) fuzzy: Option<FuzzyQuantities>,
+    pub(crate) time: Option<NaiveTime>,
+    pub(crate) week_start: Weekday,
+    pub(crate) weekend_start: Weekday,
+    pub(crate) terms: BTreeMap<String, (NaiveDate, NaiveDate)>,
+    pub(crate) billing_day: Option<u32>,
+    pub(crate) sprints: Option<(NaiveDate, usize)>,
+    pub(crate) cadences: BTreeMap<String, Cadence>,
+    pub(crate) work_week_days: usize,
+    pub(crate) work_day_hours: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            short_months: ShortMonthPolicy::default(),
+            leap_day: LeapDayPolicy::default(),
+            holidays: Arc::new(NoHolidays),
+            diff_format: DiffFormat::default(),
+            month_ends: MonthEndConvention::default(),
+            thresholds: Thresholds::default(),
+            fractional_months: FractionalMonthPolicy::default(),
+            approximate: false,
+            fuzzy: None,
+            time: None,
+            week_start: Weekday::Mon,
+            weekend_start: Weekday::Sat,
+            terms: BTreeMap::new(),
+            billing_day: None,
+            sprints: None,
+            cadences: BTreeMap::new(),
+            work_week_days: 5,
+            work_day_hours: 8,
+        }
+    }
+}
+
+impl Config {
+    pub fn short_months(mut self, policy: ShortMonthPolicy) -> Self {
+        self.short_months = policy;
+        self
+    }
+
+    pub fn leap_day(mut self, policy: LeapDayPolicy) -> Self {
+        self.leap_day = policy;
+        self
+    }
+
+    pub fn holidays<H: HolidayCalendar + 'static>(mut self, calendar: H) -> Self {
+        self.holidays = Arc::new(calendar);
+        self
+    }
+
+    pub fn diff_format(mut self, format: DiffFormat) -> Self {
+        self.diff_format = format;
+        self
+    }
+
+    pub fn month_ends(mut self, convention: MonthEndConvention) -> Self {
+        self.month_ends = convention;
+        self
+    }
+
+    pub fn thresholds(mut self, thresholds: Thresholds) -> Self {
+        self.thresholds = thresholds;
+        self
+    }
+
+    pub fn fractional_months(mut self, policy: FractionalMonthPolicy) -> Self {
+        self.fractional_months = policy;
+        self
+    }
+
+    /// Treats every month as 30 days and every year as 365, rather than stepping through
+    /// the calendar.
+    pub fn approximate(mut self, approximate: bool) -> Self {
+        self.approximate = approximate;
+        self
+    }
+
+    /// Accepts vague quantities like "a few weeks", reading them as the given counts.
+    pub fn lenient(mut self, quantities: FuzzyQuantities) -> Self {
+        self.fuzzy = Some(quantities);
+        self
+    }
+
+    /// The current time of day, so schedules starting today only list times after it.
+    /// Without one, today is treated as having just begun.
+    pub fn time(mut self, time: NaiveTime) -> Self {
+        self.time = Some(time);
+        self
+    }
+
+    /// The day weeks begin on, Monday unless set.
+    pub fn week_start(mut self, weekday: Weekday) -> Self {
+        self.week_start = weekday;
+        self
+    }
+
+    /// The first day of the weekend, for "next weekend" and "last weekend", Saturday unless
+    /// set.
+    pub fn weekend_start(mut self, weekday: Weekday) -> Self {
+        self.weekend_start = weekday;
+        self
+    }
+
+    /// Defines a named term, e.g. a semester, for expressions like "start of fall term".
+    /// Names are matched without regard to case.
+    pub fn term(mut self, name: &str, start: NaiveDate, end: NaiveDate) -> Self {
+        self.terms
+            .insert(name.to_lowercase(), (start.min(end), start.max(end)));
+        self
+    }
+
+    /// The day of the month billing cycles renew on, e.g. 31 for a subscription started
+    /// on the 31st. Months too short for it renew on their last day.
+    pub fn billing_day(mut self, day: u32) -> Self {
+        self.billing_day = Some(day.clamp(1, 31));
+        self
+    }
+
+    /// Sprints of `days` days each, with sprint 1 starting on `start`, for expressions
+    /// like "sprint 7 start" and "2 sprints from now".
+    pub fn sprints(mut self, start: NaiveDate, days: usize) -> Self {
+        self.sprints = Some((start, days.max(1)));
+        self
+    }
+
+    /// Defines a named cadence, e.g. releases, for expressions like "next release".
+    /// Names are matched without regard to case.
+    pub fn cadence(mut self, name: &str, cadence: Cadence) -> Self {
+        self.cadences.insert(name.to_lowercase(), cadence);
+        self
+    }
+
+    /// The working week that working time like "3w 2d 4h of work" is counted in, five eight-hour
+    /// days unless set.
+    pub fn working_time(mut self, days_per_week: usize, hours_per_day: usize) -> Self {
+        self.work_week_days = days_per_week;
+        self.work_day_hours = hours_per_day.max(1);
+        self
+    }
+}
+
+/// How a day of the month is resolved in a month that is too short to contain it,
+/// e.g. the 31st in April.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ShortMonthPolicy {
+    /// Use the last day of the short month instead
+    Clamp,
+    /// Ignore the short month entirely
+    #[default]
+    Skip,
+}
+
+impl FromStr for ShortMonthPolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "clamp" => Ok(ShortMonthPolicy::Clamp),
+            "skip" => Ok(ShortMonthPolicy::Skip),
+            _ => Err(format!("unknown short month policy: '{}'", value)),
+        }
+    }
+}
+
+/// Where the anniversary of February 29 falls in years without one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LeapDayPolicy {
+    #[default]
+    February28,
+    March1,
+}
+
+impl FromStr for LeapDayPolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "feb28" => Ok(LeapDayPolicy::February28),
+            "mar1" => Ok(LeapDayPolicy::March1),
+            _ => Err(format!("unknown leap day policy: '{}'", value)),
+        }
+    }
+}
+
+/// How quantities like "1.5 months" are resolved, since months vary in length.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FractionalMonthPolicy {
+    /// Refuse to compute expressions containing fractional months
+    #[default]
+    Reject,
+    /// Treat a month as 30 days
+    ThirtyDays,
+}
+
+impl FromStr for FractionalMonthPolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "reject" => Ok(FractionalMonthPolicy::Reject),
+            "30days" => Ok(FractionalMonthPolicy::ThirtyDays),
+            _ => Err(format!("unknown fractional month policy: '{}'", value)),
+        }
+    }
+}
+
+/// The counts assumed for vague quantities in lenient mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FuzzyQuantities {
+    pub couple: usize,
+    pub few: usize,
+}
+
+impl FuzzyQuantities {
+    pub(crate) fn count(self, quantity: Quantity) -> usize {
+        match quantity {
+            Quantity::Couple => self.couple,
+            Quantity::Few => self.few,
+        }
+    }
+}
+
+impl Default for FuzzyQuantities {
+    fn default() -> Self {
+        FuzzyQuantities { couple: 2, few: 3 }
+    }
+}
+
+/// How the difference between two dates is reported.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DiffFormat {
+    /// Always a number of days
+    #[default]
+    Days,
+    /// Days, weeks, months, or years and months, depending on the size of the difference
+    Auto,
+    /// Whole weeks and the remaining days, e.g. "3 weeks and 4 days"
+    WeeksAndDays,
+    /// Complete calendar months, rounded down: a month has passed once the later date
+    /// reaches the earlier one's day of the month, so January 31 to February 28 is 0 months
+    Months,
+}
+
+impl FromStr for DiffFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "days" => Ok(DiffFormat::Days),
+            "auto" => Ok(DiffFormat::Auto),
+            "weeks" => Ok(DiffFormat::WeeksAndDays),
+            "months" => Ok(DiffFormat::Months),
+            _ => Err(format!("unknown diff format: '{}'", value)),
+        }
+    }
+}
+
+/// How months are counted in a difference between two dates that both fall on the last
+/// day of their months.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MonthEndConvention {
+    /// Count by day of the month, so January 31 to February 28 is less than a month
+    #[default]
+    Calendar,
+    /// Count from one month-end to the next as a month, so January 31 to February 28 is
+    /// exactly one
+    MonthEnd,
+}
+
+impl FromStr for MonthEndConvention {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "calendar" => Ok(MonthEndConvention::Calendar),
+            "month-end" => Ok(MonthEndConvention::MonthEnd),
+            _ => Err(format!("unknown month-end convention: '{}'", value)),
+        }
+    }
+}
+
+/// The number of days at which `DiffFormat::Auto` switches to a larger unit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Thresholds {
+    pub weeks: usize,
+    pub months: usize,
+    pub years: usize,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Thresholds {
+            weeks: 14,
+            months: 60,
+            years: 365,
+        }
+    }
+}
+
+impl FromStr for Thresholds {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let parts = value
+            .split(',')
+            .map(|part| part.trim().parse())
+            .collect::<Result<Vec<usize>, _>>()
+            .map_err(|_| format!("invalid thresholds: '{}'", value))?;
+
+        match parts[..] {
+            [weeks, months, years] => Ok(Thresholds {
+                weeks,
+                months,
+                years,
+            }),
+            _ => Err(format!(
+                "expected thresholds as <weeks>,<months>,<years>: '{}'",
+                value
+            )),
+        }
+    }
+}
@@ -0,0 +1,468 @@
+#[cfg(feature = "cli")]
+use crate::json::{self, Json, ToJson};
+use crate::parser_utils::*;
+#[cfg(feature = "cli")]
+use crate::syntax::Form;
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Timelike};
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::space1,
+    combinator::{map, opt},
+    multi::separated_list1,
+    sequence::{delimited, pair, preceded, separated_pair, terminated, tuple},
+    IResult,
+};
+
+const MONTH_NAMES: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+// The longest gap between two matching days is a Feb 29 that must also land on a given
+// weekday, which can take decades; searching 400 years covers a full Gregorian cycle.
+const SEARCH_LIMIT_DAYS: i64 = 400 * 366;
+
+/// The most times a schedule lists at once.
+pub(crate) const MAX_COUNT: usize = 1000;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u32,
+    days_of_month: u32,
+    months: u16,
+    days_of_week: u8,
+    days_of_month_restricted: bool,
+    days_of_week_restricted: bool,
+}
+
+impl CronSchedule {
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let start = after
+            .date()
+            .and_hms(after.hour(), after.minute(), 0)
+            .checked_add_signed(Duration::minutes(1))?;
+
+        self.next_from(start)
+    }
+
+    // The first time at or after `start`, which is on a whole minute.
+    fn next_from(&self, start: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut day = start.date();
+
+        for _ in 0..SEARCH_LIMIT_DAYS {
+            if self.matches_day(day) {
+                let earliest = if day == start.date() {
+                    (start.hour(), start.minute())
+                } else {
+                    (0, 0)
+                };
+
+                if let Some((hour, minute)) = self.first_time_from(earliest) {
+                    return Some(day.and_hms(hour, minute, 0));
+                }
+            }
+
+            day = day.succ_opt()?;
+        }
+
+        None
+    }
+
+    /// The next `count` times from the start of `from`.
+    pub fn upcoming(&self, from: NaiveDate, count: usize) -> Vec<NaiveDateTime> {
+        self.upcoming_from(self.next_from(from.and_hms(0, 0, 0)), count)
+    }
+
+    /// The next `count` times strictly after the minute of `after`.
+    pub fn upcoming_after(&self, after: NaiveDateTime, count: usize) -> Vec<NaiveDateTime> {
+        self.upcoming_from(self.next_after(after), count)
+    }
+
+    fn upcoming_from(&self, first: Option<NaiveDateTime>, count: usize) -> Vec<NaiveDateTime> {
+        std::iter::successors(first, |time| self.next_after(*time))
+            .take(count)
+            .collect()
+    }
+
+    fn matches_day(&self, day: NaiveDate) -> bool {
+        if !has_bit(self.months as u64, day.month()) {
+            return false;
+        }
+
+        let day_of_month = has_bit(self.days_of_month as u64, day.day());
+        let day_of_week = has_bit(
+            self.days_of_week as u64,
+            day.weekday().num_days_from_sunday(),
+        );
+
+        // Matches cron(8): when both day fields are restricted, either may match.
+        if self.days_of_month_restricted && self.days_of_week_restricted {
+            day_of_month || day_of_week
+        } else {
+            day_of_month && day_of_week
+        }
+    }
+
+    fn first_time_from(&self, (hour, minute): (u32, u32)) -> Option<(u32, u32)> {
+        (hour..24)
+            .filter(|h| has_bit(self.hours as u64, *h))
+            .find_map(|h| {
+                let first_minute = if h == hour { minute } else { 0 };
+                (first_minute..60)
+                    .find(|m| has_bit(self.minutes, *m))
+                    .map(|m| (h, m))
+            })
+    }
+}
+
+#[cfg(feature = "cli")]
+impl ToJson for CronSchedule {
+    fn to_json(&self) -> Json {
+        let positions = |bits: u64, range: std::ops::RangeInclusive<u32>| {
+            Json::Array(
+                range
+                    .filter(|position| has_bit(bits, *position))
+                    .map(|position| Json::Number(position.into()))
+                    .collect(),
+            )
+        };
+
+        json::object(vec![
+            ("minutes", positions(self.minutes, 0..=59)),
+            ("hours", positions(self.hours.into(), 0..=23)),
+            (
+                "days_of_month",
+                positions(self.days_of_month.into(), 1..=31),
+            ),
+            ("months", positions(self.months.into(), 1..=12)),
+            ("days_of_week", positions(self.days_of_week.into(), 0..=6)),
+        ])
+    }
+}
+
+/// The schedule as crontab fields, e.g. "0 9 * * 1". Fields are written in a standard
+/// form, so the text may differ from what was parsed while meaning the same.
+impl std::fmt::Display for CronSchedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {}",
+            field(self.minutes, 0, 59, None),
+            field(self.hours.into(), 0, 23, None),
+            field(
+                self.days_of_month.into(),
+                1,
+                31,
+                Some(self.days_of_month_restricted)
+            ),
+            field(self.months.into(), 1, 12, None),
+            field(
+                self.days_of_week.into(),
+                0,
+                6,
+                Some(self.days_of_week_restricted)
+            ),
+        )
+    }
+}
+
+// Whether a day field was restricted changes how the two day fields combine, so for those
+// it decides between a list and a form starting with '*'.
+fn field(bits: u64, min: u32, max: u32, restricted: Option<bool>) -> String {
+    let positions: Vec<u32> = (min..=max).filter(|p| has_bit(bits, *p)).collect();
+    let step = (1..=max - min + 1).find(|step| {
+        (min..=max)
+            .step_by(*step as usize)
+            .eq(positions.iter().copied())
+    });
+
+    match (restricted, step) {
+        (Some(true), _) => ranges(&positions),
+        (_, Some(1)) => "*".to_string(),
+        (Some(false), Some(step)) => format!("*/{}", step),
+        (None, Some(step)) if positions.len() > 2 => format!("*/{}", step),
+        // Of the steps whose positions are all in the field, the one leaving the fewest
+        // over is written, finest first. A step as wide as the field picks out only its
+        // first position, which every field starting with '*' includes, so one always fits.
+        (Some(false), None) => {
+            let (step, rest) = (1..=max - min + 1)
+                .map(|step| (step, (min..=max).step_by(step as usize).collect::<Vec<_>>()))
+                .filter(|(_, stepped)| stepped.iter().all(|p| positions.contains(p)))
+                .map(|(step, stepped)| {
+                    let rest: Vec<u32> = positions
+                        .iter()
+                        .copied()
+                        .filter(|p| !stepped.contains(p))
+                        .collect();
+
+                    (step, rest)
+                })
+                .min_by_key(|(_, rest)| rest.len())
+                .unwrap_or_default();
+
+            format!("*/{},{}", step, ranges(&rest))
+        }
+        (None, _) => ranges(&positions),
+    }
+}
+
+// Runs of consecutive positions are written as ranges, e.g. "1-5,10".
+fn ranges(positions: &[u32]) -> String {
+    let mut runs: Vec<(u32, u32)> = vec![];
+
+    for position in positions {
+        match runs.last_mut() {
+            Some((_, end)) if *end + 1 == *position => *end = *position,
+            _ => runs.push((*position, *position)),
+        }
+    }
+
+    runs.iter()
+        .map(|(start, end)| match end - start {
+            0 => start.to_string(),
+            1 => format!("{},{}", start, end),
+            _ => format!("{}-{}", start, end),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn has_bit(bits: u64, position: u32) -> bool {
+    bits & (1 << position) != 0
+}
+
+/// Cron schedules as they're written in an expression.
+#[cfg(feature = "cli")]
+pub(crate) const SYNTAX: &[Form] = &[Form {
+    pattern: "next [<count>] '<cron>' [after <date>]",
+    description: "upcoming times of a cron schedule, up to 1000 at once",
+    examples: &["next 2 '0 9 * * MON' after today"],
+}];
+
+pub fn parse(input: &str) -> IResult<&str, (CronSchedule, usize)> {
+    map(
+        preceded(
+            terminated(tag("next"), space1),
+            pair(
+                opt(terminated(parse_digits, space1)),
+                alt((
+                    delimited(tag("'"), parse_schedule, tag("'")),
+                    delimited(tag("\""), parse_schedule, tag("\"")),
+                )),
+            ),
+        ),
+        |(count, schedule)| (schedule, count.unwrap_or(1)),
+    )(input)
+}
+
+pub(crate) fn parse_schedule(input: &str) -> IResult<&str, CronSchedule> {
+    map(
+        tuple((
+            terminated(|i| parse_field(i, 0, 59, &[]), space1),
+            terminated(|i| parse_field(i, 0, 23, &[]), space1),
+            terminated(|i| parse_field(i, 1, 31, &[]), space1),
+            terminated(|i| parse_field(i, 1, 12, &MONTH_NAMES), space1),
+            parse_day_of_week_field,
+        )),
+        |(minutes, hours, days_of_month, months, days_of_week)| CronSchedule {
+            minutes: minutes.0,
+            hours: hours.0 as u32,
+            days_of_month: days_of_month.0 as u32,
+            months: months.0 as u16,
+            days_of_week: days_of_week.0 as u8,
+            days_of_month_restricted: days_of_month.1,
+            days_of_week_restricted: days_of_week.1,
+        },
+    )(input)
+}
+
+fn parse_day_of_week_field(input: &str) -> IResult<&str, (u64, bool)> {
+    // Both 0 and 7 mean Sunday.
+    map(
+        |i| parse_field(i, 0, 7, &WEEKDAY_NAMES),
+        |(bits, restricted)| ((bits | (bits >> 7)) & 0x7f, restricted),
+    )(input)
+}
+
+fn parse_field<'a>(
+    input: &'a str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+) -> IResult<&'a str, (u64, bool)> {
+    let (rest, ranges) = separated_list1(tag(","), |i| parse_range(i, min, max, names))(input)?;
+
+    let mut bits = 0;
+    for (start, end, step) in ranges.iter().copied() {
+        if start < min || end > max || start > end || step == 0 {
+            return Err(nom::Err::Error(nom::error::Error::new(
+                input,
+                nom::error::ErrorKind::Verify,
+            )));
+        }
+
+        for position in (start..=end).step_by(step as usize) {
+            bits |= 1 << position;
+        }
+    }
+
+    Ok((rest, (bits, !input.starts_with('*'))))
+}
+
+fn parse_range<'a>(
+    input: &'a str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+) -> IResult<&'a str, (u32, u32, u32)> {
+    let step = |i| opt(preceded(tag("/"), parse_digits))(i);
+
+    alt((
+        map(preceded(tag("*"), step), move |step| {
+            (min, max, step.unwrap_or(1))
+        }),
+        map(
+            pair(
+                separated_pair(
+                    |i| parse_value(i, min, names),
+                    tag("-"),
+                    |i| parse_value(i, min, names),
+                ),
+                step,
+            ),
+            |((start, end), step)| (start, end, step.unwrap_or(1)),
+        ),
+        map(
+            pair(|i| parse_value(i, min, names), step),
+            move |(start, step)| match step {
+                Some(step) => (start, max, step),
+                None => (start, start, 1),
+            },
+        ),
+    ))(input)
+}
+
+fn parse_value<'a>(input: &'a str, min: u32, names: &[&str]) -> IResult<&'a str, u32> {
+    let name = names.iter().position(|name| {
+        input
+            .get(..name.len())
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(name))
+    });
+
+    match name {
+        Some(position) => Ok((&input[names[position].len()..], min + position as u32)),
+        None => parse_digits(input),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(spec: &str) -> CronSchedule {
+        parse_schedule(spec).unwrap().1
+    }
+
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd(year, month, day).and_hms(hour, minute, 0)
+    }
+
+    #[test]
+    fn test_display() {
+        for (spec, expected) in &[
+            ("0 9 * * MON", "0 9 * * 1"),
+            ("*/15 * * * *", "*/15 * * * *"),
+            ("0,30 8-17 1,15 jan-mar,dec 7", "0,30 8-17 1,15 1-3,12 0"),
+            ("5 4 */10,3 * *", "5 4 */10,3 * *"),
+            ("5 4 */31,3-5 * *", "5 4 */31,3-5 * *"),
+            ("0 0 * * */2", "0 0 * * */2"),
+        ] {
+            assert_eq!(schedule(spec).to_string(), *expected);
+            assert_eq!(schedule(expected), schedule(spec), "{}", spec);
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("next '0 9 * * MON'").unwrap().1 .1, 1);
+        assert_eq!(parse("next 3 '0 9 * * MON'").unwrap().1 .1, 3);
+        assert_eq!(parse("next \"*/15 * * * *\"").unwrap().0, "");
+        assert!(parse("next '0 9 * *'").is_err());
+        assert!(parse("next '60 9 * * *'").is_err());
+        assert!(parse("next '0 9 * * FUNDAY'").is_err());
+    }
+
+    #[test]
+    fn test_next_after() {
+        // January 3, 2022 is a Monday
+        assert_eq!(
+            schedule("0 9 * * MON").next_after(at(2022, 1, 1, 0, 0)),
+            Some(at(2022, 1, 3, 9, 0))
+        );
+
+        assert_eq!(
+            schedule("0 9 * * MON").next_after(at(2022, 1, 3, 9, 0)),
+            Some(at(2022, 1, 10, 9, 0))
+        );
+
+        assert_eq!(
+            schedule("*/15 * * * *").next_after(at(2022, 1, 3, 9, 7)),
+            Some(at(2022, 1, 3, 9, 15))
+        );
+
+        assert_eq!(
+            schedule("30 23 31 * *").next_after(at(2022, 2, 1, 0, 0)),
+            Some(at(2022, 3, 31, 23, 30))
+        );
+
+        assert_eq!(
+            schedule("0 0 29 feb *").next_after(at(2022, 1, 1, 0, 0)),
+            Some(at(2024, 2, 29, 0, 0))
+        );
+
+        assert_eq!(
+            schedule("0 0 31 2 *").next_after(at(2022, 1, 1, 0, 0)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_day_of_month_or_day_of_week() {
+        // When both day fields are restricted, either one matching is enough
+        assert_eq!(
+            schedule("0 0 15 * FRI").next_after(at(2022, 1, 1, 0, 0)),
+            Some(at(2022, 1, 7, 0, 0))
+        );
+
+        assert_eq!(
+            schedule("0 0 * * 7").next_after(at(2022, 1, 1, 0, 0)),
+            Some(at(2022, 1, 2, 0, 0))
+        );
+    }
+
+    #[test]
+    fn test_upcoming() {
+        assert_eq!(
+            schedule("0 9 * * 1-5").upcoming(NaiveDate::from_ymd(2022, 1, 7), 3),
+            vec![
+                at(2022, 1, 7, 9, 0),
+                at(2022, 1, 10, 9, 0),
+                at(2022, 1, 11, 9, 0)
+            ]
+        );
+        assert_eq!(
+            schedule("0 0 * * *").upcoming(chrono::naive::MIN_DATE, 2),
+            vec![
+                chrono::naive::MIN_DATE.and_hms(0, 0, 0),
+                chrono::naive::MIN_DATE.succ().and_hms(0, 0, 0)
+            ]
+        );
+        assert_eq!(
+            schedule("* * * * *").upcoming_after(chrono::naive::MAX_DATETIME, 1),
+            vec![]
+        );
+    }
+}
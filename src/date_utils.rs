@@ -0,0 +1,337 @@
+use crate::config::{LeapDayPolicy, ShortMonthPolicy};
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use std::convert::TryFrom;
+
+/// The earliest year dates may fall in, 10000 BC.
+pub const MIN_YEAR: i32 = -9999;
+/// The latest year dates may fall in.
+pub const MAX_YEAR: i32 = 9999;
+
+/// At least as many days as the supported years span, so a move of more days than this
+/// can't start and end in range.
+pub(crate) const SUPPORTED_DAYS: i64 = (MAX_YEAR - MIN_YEAR + 1) as i64 * 366;
+
+// chrono itself reaches about 262,000 years either way, but panics rather than failing
+// when a date near its limits is moved, so dates are kept well inside them.
+pub(crate) fn in_supported_range(date: NaiveDate) -> bool {
+    (MIN_YEAR..=MAX_YEAR).contains(&date.year())
+}
+
+pub(crate) fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = shift_month(year, month, 1);
+
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .and_then(|first| first.pred_opt())
+        .map(|last| last.day())
+        .unwrap_or(31)
+}
+
+pub(crate) fn shift_month(year: i32, month: u32, offset: i32) -> (i32, u32) {
+    let index = year * 12 + month as i32 - 1 + offset;
+
+    (index.div_euclid(12), index.rem_euclid(12) as u32 + 1)
+}
+
+/// Moves `date` by a number of days, or `None` past chrono's limits. Unlike adding a
+/// `Duration`, this doesn't panic on huge counts.
+pub(crate) fn add_days(date: NaiveDate, days: i64) -> Option<NaiveDate> {
+    NaiveDate::from_num_days_from_ce_opt(
+        date.num_days_from_ce()
+            .checked_add(i32::try_from(days).ok()?)?,
+    )
+}
+
+/// Moves `date` by a number of calendar months, using the last day of the resulting month
+/// if it's too short for the day, e.g. January 31 + 1 month is February 28.
+pub(crate) fn add_months(date: NaiveDate, months: i64) -> Option<NaiveDate> {
+    let index = (date.year() as i64 * 12 + date.month0() as i64).checked_add(months)?;
+    let year = i32::try_from(index.div_euclid(12)).ok()?;
+    let month = index.rem_euclid(12) as u32 + 1;
+    let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+
+    first.with_day(date.day().min(days_in_month(year, month)))
+}
+
+pub(crate) fn day_of_month(
+    year: i32,
+    month: u32,
+    day: u32,
+    policy: ShortMonthPolicy,
+) -> Option<NaiveDate> {
+    match (NaiveDate::from_ymd_opt(year, month, day), policy) {
+        (Some(date), _) => Some(date),
+        (None, ShortMonthPolicy::Clamp) => {
+            NaiveDate::from_ymd_opt(year, month, days_in_month(year, month))
+        }
+        (None, ShortMonthPolicy::Skip) => None,
+    }
+}
+
+pub(crate) fn anniversary(date: NaiveDate, year: i32, policy: LeapDayPolicy) -> Option<NaiveDate> {
+    date.with_year(year).or_else(|| match policy {
+        LeapDayPolicy::February28 => NaiveDate::from_ymd_opt(year, 2, 28),
+        LeapDayPolicy::March1 => NaiveDate::from_ymd_opt(year, 3, 1),
+    })
+}
+
+pub(crate) fn completed_years(from: NaiveDate, to: NaiveDate, policy: LeapDayPolicy) -> usize {
+    let years = to.year() - from.year();
+
+    match anniversary(from, to.year(), policy) {
+        Some(date) if date > to => (years - 1).max(0) as usize,
+        _ => years.max(0) as usize,
+    }
+}
+
+/// The number of complete calendar months from `earlier` to `later`, rounded down.
+pub(crate) fn whole_months_between(earlier: NaiveDate, later: NaiveDate) -> u32 {
+    let months =
+        (later.year() - earlier.year()) * 12 + later.month() as i32 - earlier.month() as i32;
+
+    if later.day() < earlier.day() {
+        (months - 1).max(0) as u32
+    } else {
+        months.max(0) as u32
+    }
+}
+
+/// The first `weekday` on or after `date`.
+pub(crate) fn upcoming_weekday(date: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let days = (7 + weekday.num_days_from_monday() - date.weekday().num_days_from_monday()) % 7;
+
+    date + Duration::days(days.into())
+}
+
+/// The first day of the week containing `date`, for weeks starting on `week_start`.
+pub(crate) fn start_of_week(date: NaiveDate, week_start: Weekday) -> NaiveDate {
+    let days = (7 + date.weekday().num_days_from_monday() - week_start.num_days_from_monday()) % 7;
+
+    date - Duration::days(days.into())
+}
+
+pub(crate) fn weekday_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    }
+}
+
+/// The date a spreadsheet serial number stands for. Spreadsheets count 1900-01-01 as day
+/// 1 and treat 1900 as a leap year, so serial 60 is a February 29th that never happened.
+pub(crate) fn from_excel_serial(serial: i64) -> Option<NaiveDate> {
+    match serial {
+        1..=59 => Some(NaiveDate::from_ymd(1899, 12, 31) + Duration::days(serial)),
+        60 => None,
+        // Counted in whole days rather than through a `Duration`, which panics on huge ones.
+        _ if serial > 60 => NaiveDate::from_num_days_from_ce_opt(
+            NaiveDate::from_ymd(1899, 12, 30)
+                .num_days_from_ce()
+                .checked_add(i32::try_from(serial).ok()?)?,
+        ),
+        _ => None,
+    }
+}
+
+/// Which days of the week are counted by "weekdays between" and "weekend days between".
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DayKind {
+    /// Monday through Friday
+    Weekday,
+    /// Saturday and Sunday
+    Weekend,
+}
+
+/// The days of `kind` from the earlier of two dates up to, but not including, the later,
+/// so weekdays and weekend days add up to the plain difference between them.
+pub(crate) fn count_days(kind: DayKind, first: NaiveDate, second: NaiveDate) -> usize {
+    let (start, end) = (first.min(second), first.max(second));
+    let days = (end - start).num_days();
+    let whole_weeks = days / 7;
+    let weekend = whole_weeks * 2
+        + (whole_weeks * 7..days)
+            .filter(|day| is_weekend(start + Duration::days(*day)))
+            .count() as i64;
+
+    match kind {
+        DayKind::Weekday => (days - weekend) as usize,
+        DayKind::Weekend => weekend as usize,
+    }
+}
+
+fn is_weekend(date: NaiveDate) -> bool {
+    matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+/// The spreadsheet serial number of `date`, the inverse of `from_excel_serial`.
+#[cfg(feature = "cli")]
+pub(crate) fn to_excel_serial(date: NaiveDate) -> Option<i64> {
+    let serial = (date - NaiveDate::from_ymd(1899, 12, 30)).num_days();
+
+    match serial {
+        61.. => Some(serial),
+        2..=60 => Some(serial - 1),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_leap_day_anniversary() {
+        let leap_day = NaiveDate::from_ymd(2020, 2, 29);
+
+        assert_eq!(
+            anniversary(leap_day, 2021, LeapDayPolicy::February28),
+            NaiveDate::from_ymd_opt(2021, 2, 28)
+        );
+        assert_eq!(
+            anniversary(leap_day, 2021, LeapDayPolicy::March1),
+            NaiveDate::from_ymd_opt(2021, 3, 1)
+        );
+        assert_eq!(
+            anniversary(leap_day, 2024, LeapDayPolicy::March1),
+            NaiveDate::from_ymd_opt(2024, 2, 29)
+        );
+    }
+
+    #[test]
+    fn test_excel_serial() {
+        assert_eq!(from_excel_serial(1), NaiveDate::from_ymd_opt(1900, 1, 1));
+        assert_eq!(from_excel_serial(59), NaiveDate::from_ymd_opt(1900, 2, 28));
+        assert_eq!(from_excel_serial(60), None);
+        assert_eq!(from_excel_serial(61), NaiveDate::from_ymd_opt(1900, 3, 1));
+        assert_eq!(
+            from_excel_serial(44575),
+            NaiveDate::from_ymd_opt(2022, 1, 14)
+        );
+        assert_eq!(from_excel_serial(0), None);
+        assert_eq!(from_excel_serial(9_999_999_999_999), None);
+        assert_eq!(from_excel_serial(i64::MAX), None);
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_to_excel_serial() {
+        for serial in [1, 59, 61, 44575] {
+            assert_eq!(
+                from_excel_serial(serial).and_then(to_excel_serial),
+                Some(serial)
+            );
+        }
+        assert_eq!(to_excel_serial(NaiveDate::from_ymd(1899, 12, 31)), None);
+    }
+
+    #[test]
+    fn test_start_of_week() {
+        // March 3, 2022 is a Thursday
+        let date = NaiveDate::from_ymd(2022, 3, 3);
+
+        assert_eq!(
+            start_of_week(date, Weekday::Mon),
+            NaiveDate::from_ymd(2022, 2, 28)
+        );
+        assert_eq!(
+            start_of_week(date, Weekday::Sun),
+            NaiveDate::from_ymd(2022, 2, 27)
+        );
+        assert_eq!(start_of_week(date, Weekday::Thu), date);
+    }
+
+    #[test]
+    fn test_add_months() {
+        let date = NaiveDate::from_ymd(2022, 1, 31);
+
+        assert_eq!(add_months(date, 1), Some(NaiveDate::from_ymd(2022, 2, 28)));
+        assert_eq!(add_months(date, 25), Some(NaiveDate::from_ymd(2024, 2, 29)));
+        assert_eq!(
+            add_months(date, -2),
+            Some(NaiveDate::from_ymd(2021, 11, 30))
+        );
+        assert_eq!(add_months(date, i64::MAX), None);
+    }
+
+    #[test]
+    fn test_whole_months_between() {
+        let start = NaiveDate::from_ymd(2022, 1, 15);
+
+        assert_eq!(
+            whole_months_between(start, NaiveDate::from_ymd(2022, 2, 14)),
+            0
+        );
+        assert_eq!(
+            whole_months_between(start, NaiveDate::from_ymd(2022, 2, 15)),
+            1
+        );
+        assert_eq!(
+            whole_months_between(start, NaiveDate::from_ymd(2023, 3, 1)),
+            13
+        );
+    }
+
+    #[test]
+    fn test_completed_years() {
+        let birth = NaiveDate::from_ymd(1990, 5, 4);
+
+        assert_eq!(
+            completed_years(
+                birth,
+                NaiveDate::from_ymd(2030, 1, 1),
+                LeapDayPolicy::default()
+            ),
+            39
+        );
+        assert_eq!(
+            completed_years(
+                birth,
+                NaiveDate::from_ymd(2030, 5, 4),
+                LeapDayPolicy::default()
+            ),
+            40
+        );
+        assert_eq!(
+            completed_years(
+                birth,
+                NaiveDate::from_ymd(1980, 5, 4),
+                LeapDayPolicy::default()
+            ),
+            0
+        );
+
+        let leap_day = NaiveDate::from_ymd(2020, 2, 29);
+        let feb_28 = NaiveDate::from_ymd(2021, 2, 28);
+
+        assert_eq!(
+            completed_years(leap_day, feb_28, LeapDayPolicy::February28),
+            1
+        );
+        assert_eq!(completed_years(leap_day, feb_28, LeapDayPolicy::March1), 0);
+    }
+
+    #[test]
+    fn test_count_days() {
+        // A Monday through the Monday two weeks and three days later
+        let monday = NaiveDate::from_ymd(2022, 3, 7);
+        let thursday = NaiveDate::from_ymd(2022, 3, 24);
+
+        assert_eq!(count_days(DayKind::Weekday, monday, thursday), 13);
+        assert_eq!(count_days(DayKind::Weekend, monday, thursday), 4);
+        assert_eq!(count_days(DayKind::Weekday, thursday, monday), 13);
+
+        let saturday = NaiveDate::from_ymd(2022, 3, 12);
+
+        assert_eq!(count_days(DayKind::Weekend, saturday, saturday), 0);
+        assert_eq!(count_days(DayKind::Weekend, saturday, saturday.succ()), 1);
+        assert_eq!(
+            count_days(DayKind::Weekday, saturday, saturday + Duration::days(2)),
+            0
+        );
+    }
+}
@@ -0,0 +1,401 @@
+use crate::{
+    config::{Config, DiffFormat, MonthEndConvention, Thresholds},
+    date_utils, ComputeError, ComputeOutcome, Period, PeriodOp, Step,
+};
+use chrono::{Datelike, NaiveDate};
+use std::convert::{TryFrom, TryInto};
+
+/// The units periods are converted to, e.g. "days" in "3 weeks in days".
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Conversion {
+    Days,
+    /// Whole weeks, rounded down
+    Weeks,
+    WeeksAndDays,
+    Months,
+    /// Whole years, rounded down
+    Years,
+    YearsAndMonths,
+}
+
+impl Conversion {
+    pub fn name(self) -> &'static str {
+        match self {
+            Conversion::Days => "days",
+            Conversion::Weeks => "weeks",
+            Conversion::WeeksAndDays => "weeks and days",
+            Conversion::Months => "months",
+            Conversion::Years => "years",
+            Conversion::YearsAndMonths => "years and months",
+        }
+    }
+}
+
+pub(crate) fn compute(
+    from: NaiveDate,
+    to: NaiveDate,
+    config: &Config,
+) -> Result<ComputeOutcome, ComputeError> {
+    let (earlier, later) = (from.min(to), from.max(to));
+    let days: usize = (later - earlier)
+        .num_days()
+        .try_into()
+        .map_err(|_| ComputeError::OutOfRange)?;
+    let months = whole_months(earlier, later, config.month_ends);
+
+    Ok(match config.diff_format {
+        DiffFormat::Days => ComputeOutcome::DifferenceInDays(days),
+        DiffFormat::Auto => ComputeOutcome::Duration(humanize(months, days, config.thresholds)),
+        DiffFormat::WeeksAndDays => ComputeOutcome::Duration(weeks_and_days(days)),
+        DiffFormat::Months => ComputeOutcome::Duration(vec![Period::Month(months)]),
+    })
+}
+
+/// The total length of the operations in other units. Days and weeks convert to each
+/// other, as do months and years; converting between the two needs approximate month
+/// lengths, and only goes from months to days.
+pub(crate) fn convert(
+    operations: &[PeriodOp],
+    conversion: Conversion,
+    config: &Config,
+) -> Result<ComputeOutcome, ComputeError> {
+    let (mut days, mut months) = (0i64, 0i64);
+
+    for op in operations {
+        let (total, count) = match op.period().to_step(config)? {
+            Step::Days(count) => (&mut days, count),
+            Step::Months(count) => (&mut months, count),
+            Step::Years(count) => (&mut months, count.saturating_mul(12)),
+            Step::BusinessDays(_) => return Err(ComputeError::NoFixedLength),
+        };
+        // Steps saturate at `usize::MAX`, which doesn't fit, so overflowing counts are
+        // reported here rather than converted.
+        let count = i64::try_from(count).map_err(|_| ComputeError::OutOfRange)?;
+
+        *total = if op.is_subtraction() {
+            total.checked_sub(count)
+        } else {
+            total.checked_add(count)
+        }
+        .ok_or(ComputeError::OutOfRange)?;
+    }
+
+    let days = usize::try_from(days.unsigned_abs()).map_err(|_| ComputeError::OutOfRange)?;
+    let months = usize::try_from(months.unsigned_abs()).map_err(|_| ComputeError::OutOfRange)?;
+
+    Ok(ComputeOutcome::Duration(match conversion {
+        _ if days > 0 && months > 0 => return Err(ComputeError::NoFixedLength),
+        Conversion::Days if months == 0 => vec![Period::Day(days)],
+        Conversion::Weeks if months == 0 => vec![Period::Week(days / 7)],
+        Conversion::WeeksAndDays if months == 0 => weeks_and_days(days),
+        Conversion::Months if days == 0 => vec![Period::Month(months)],
+        Conversion::Years if days == 0 => vec![Period::Year(months / 12)],
+        Conversion::YearsAndMonths if days == 0 => years_and_months(months),
+        _ => return Err(ComputeError::NoFixedLength),
+    }))
+}
+
+/// Describes `date` relative to `today`, e.g. "in 3 days" or "2 weeks ago".
+#[cfg(feature = "cli")]
+pub(crate) fn relative(
+    date: NaiveDate,
+    today: NaiveDate,
+    config: &Config,
+) -> Result<String, ComputeError> {
+    let difference = compute(date, today, config)?;
+
+    Ok(match date.cmp(&today) {
+        std::cmp::Ordering::Less => format!("{} ago", difference),
+        std::cmp::Ordering::Equal => "today".to_string(),
+        std::cmp::Ordering::Greater => format!("in {}", difference),
+    })
+}
+
+fn whole_months(earlier: NaiveDate, later: NaiveDate, convention: MonthEndConvention) -> usize {
+    let months = date_utils::whole_months_between(earlier, later) as usize;
+    let is_month_end =
+        |date: NaiveDate| date.day() == date_utils::days_in_month(date.year(), date.month());
+
+    match convention {
+        MonthEndConvention::MonthEnd
+            if is_month_end(earlier) && is_month_end(later) && later.day() < earlier.day() =>
+        {
+            months + 1
+        }
+        _ => months,
+    }
+}
+
+fn humanize(months: usize, days: usize, thresholds: Thresholds) -> Vec<Period> {
+    if days >= thresholds.years && months >= 12 {
+        let mut periods = vec![Period::Year(months / 12)];
+
+        if !months.is_multiple_of(12) {
+            periods.push(Period::Month(months % 12));
+        }
+
+        periods
+    } else if days >= thresholds.months && months > 0 {
+        vec![Period::Month(months)]
+    } else if days >= thresholds.weeks && days >= 7 {
+        vec![Period::Week(days / 7)]
+    } else {
+        vec![Period::Day(days)]
+    }
+}
+
+fn weeks_and_days(days: usize) -> Vec<Period> {
+    match (days / 7, days % 7) {
+        (0, days) => vec![Period::Day(days)],
+        (weeks, 0) => vec![Period::Week(weeks)],
+        (weeks, days) => vec![Period::Week(weeks), Period::Day(days)],
+    }
+}
+
+fn years_and_months(months: usize) -> Vec<Period> {
+    match (months / 12, months % 12) {
+        (0, months) => vec![Period::Month(months)],
+        (years, 0) => vec![Period::Year(years)],
+        (years, months) => vec![Period::Year(years), Period::Month(months)],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn humanized(from: NaiveDate, to: NaiveDate) -> String {
+        compute(from, to, &Config::default().diff_format(DiffFormat::Auto))
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test_days() {
+        assert_eq!(
+            compute(
+                NaiveDate::from_ymd(2022, 1, 31),
+                NaiveDate::from_ymd(2022, 1, 1),
+                &Config::default()
+            ),
+            Ok(ComputeOutcome::DifferenceInDays(30))
+        );
+    }
+
+    #[test]
+    fn test_convert() {
+        let converted = |operations: &[PeriodOp], conversion, config: &Config| {
+            convert(operations, conversion, config).map(|outcome| outcome.to_string())
+        };
+        let config = Config::default();
+
+        assert_eq!(
+            converted(&[PeriodOp::Add(Period::Week(3))], Conversion::Days, &config),
+            Ok("21 days".to_string())
+        );
+        assert_eq!(
+            converted(
+                &[PeriodOp::Add(Period::Month(18))],
+                Conversion::YearsAndMonths,
+                &config
+            ),
+            Ok("1 year and 6 months".to_string())
+        );
+        assert_eq!(
+            converted(
+                &[
+                    PeriodOp::Add(Period::Year(1)),
+                    PeriodOp::Subtract(Period::Month(1))
+                ],
+                Conversion::Years,
+                &config
+            ),
+            Ok("0 years".to_string())
+        );
+        assert_eq!(
+            converted(
+                &[PeriodOp::Add(Period::Month(2))],
+                Conversion::Days,
+                &config
+            ),
+            Err(ComputeError::NoFixedLength)
+        );
+        assert_eq!(
+            converted(
+                &[PeriodOp::Add(Period::Month(2))],
+                Conversion::Days,
+                &config.clone().approximate(true)
+            ),
+            Ok("60 days".to_string())
+        );
+        assert_eq!(
+            converted(
+                &[PeriodOp::Add(Period::Day(45))],
+                Conversion::Months,
+                &config
+            ),
+            Err(ComputeError::NoFixedLength)
+        );
+    }
+
+    #[test]
+    fn test_auto() {
+        let start = NaiveDate::from_ymd(2022, 1, 1);
+
+        assert_eq!(humanized(start, NaiveDate::from_ymd(2022, 1, 7)), "6 days");
+        assert_eq!(
+            humanized(start, NaiveDate::from_ymd(2022, 1, 23)),
+            "3 weeks"
+        );
+        assert_eq!(
+            humanized(start, NaiveDate::from_ymd(2022, 3, 10)),
+            "2 months"
+        );
+        assert_eq!(
+            humanized(NaiveDate::from_ymd(2023, 5, 2), start),
+            "1 year and 4 months"
+        );
+        assert_eq!(humanized(start, NaiveDate::from_ymd(2024, 1, 1)), "2 years");
+    }
+
+    #[test]
+    fn test_weeks_and_days() {
+        let config = Config::default().diff_format(DiffFormat::WeeksAndDays);
+        let start = NaiveDate::from_ymd(2022, 1, 1);
+        let weeks_and_days = |to| compute(start, to, &config).unwrap().to_string();
+
+        assert_eq!(
+            weeks_and_days(NaiveDate::from_ymd(2022, 1, 26)),
+            "3 weeks and 4 days"
+        );
+        assert_eq!(weeks_and_days(NaiveDate::from_ymd(2022, 1, 15)), "2 weeks");
+        assert_eq!(weeks_and_days(NaiveDate::from_ymd(2022, 1, 4)), "3 days");
+    }
+
+    #[test]
+    fn test_months() {
+        let config = Config::default().diff_format(DiffFormat::Months);
+        let months = |from, to| compute(from, to, &config).unwrap().to_string();
+
+        assert_eq!(
+            months(
+                NaiveDate::from_ymd(2022, 1, 15),
+                NaiveDate::from_ymd(2022, 4, 14)
+            ),
+            "2 months"
+        );
+        assert_eq!(
+            months(
+                NaiveDate::from_ymd(2022, 1, 15),
+                NaiveDate::from_ymd(2023, 1, 15)
+            ),
+            "12 months"
+        );
+        assert_eq!(
+            months(
+                NaiveDate::from_ymd(2022, 1, 31),
+                NaiveDate::from_ymd(2022, 2, 28)
+            ),
+            "0 months"
+        );
+    }
+
+    #[test]
+    fn test_months_between_month_ends() {
+        let months = |convention, from, to| {
+            compute(
+                from,
+                to,
+                &Config::default()
+                    .diff_format(DiffFormat::Months)
+                    .month_ends(convention),
+            )
+            .unwrap()
+            .to_string()
+        };
+        let january = NaiveDate::from_ymd(2022, 1, 31);
+
+        assert_eq!(
+            months(
+                MonthEndConvention::Calendar,
+                january,
+                NaiveDate::from_ymd(2022, 2, 28)
+            ),
+            "0 months"
+        );
+        assert_eq!(
+            months(
+                MonthEndConvention::MonthEnd,
+                january,
+                NaiveDate::from_ymd(2022, 2, 28)
+            ),
+            "1 month"
+        );
+        assert_eq!(
+            months(
+                MonthEndConvention::MonthEnd,
+                january,
+                NaiveDate::from_ymd(2022, 4, 30)
+            ),
+            "3 months"
+        );
+        assert_eq!(
+            months(
+                MonthEndConvention::MonthEnd,
+                january,
+                NaiveDate::from_ymd(2022, 2, 27)
+            ),
+            "0 months"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_relative() {
+        let today = NaiveDate::from_ymd(2022, 2, 1);
+
+        assert_eq!(
+            relative(NaiveDate::from_ymd(2022, 7, 16), today, &Config::default()),
+            Ok("in 165 days".to_string())
+        );
+        assert_eq!(
+            relative(NaiveDate::from_ymd(2022, 1, 31), today, &Config::default()),
+            Ok("1 day ago".to_string())
+        );
+        assert_eq!(
+            relative(today, today, &Config::default()),
+            Ok("today".to_string())
+        );
+        assert_eq!(
+            relative(
+                NaiveDate::from_ymd(2022, 5, 1),
+                today,
+                &Config::default().diff_format(DiffFormat::Auto)
+            ),
+            Ok("in 3 months".to_string())
+        );
+    }
+
+    #[test]
+    fn test_auto_thresholds() {
+        let config = Config::default()
+            .diff_format(DiffFormat::Auto)
+            .thresholds(Thresholds {
+                weeks: 7,
+                months: 30,
+                years: 365,
+            });
+
+        assert_eq!(
+            compute(
+                NaiveDate::from_ymd(2022, 1, 1),
+                NaiveDate::from_ymd(2022, 1, 8),
+                &config
+            )
+            .unwrap()
+            .to_string(),
+            "1 week"
+        );
+    }
+}
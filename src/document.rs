@@ -0,0 +1,294 @@
+//! YAML and TOML renderings of the same documents printed as JSON, for users templating
+//! config files like front matter directly from results.
+//!
+//! Strings are written with JSON's escapes, which YAML's and TOML's double-quoted
+//! strings both read the same way.
+
+use crate::json::Json;
+
+pub(crate) fn to_yaml(value: &Json) -> String {
+    to_yaml_at(value, 0)
+}
+
+fn write_yaml_fields(output: &mut String, fields: &[(&'static str, Json)], indent: usize) {
+    for (key, value) in fields {
+        output.push_str(&format!("{:indent$}{}:", "", key, indent = indent));
+
+        match value {
+            Json::Object(fields) if !fields.is_empty() => {
+                output.push('\n');
+                write_yaml_fields(output, fields, indent + 2);
+            }
+            Json::Array(values) if !values.is_empty() => {
+                output.push('\n');
+                write_yaml_items(output, values, indent + 2);
+            }
+            scalar => output.push_str(&format!(" {}\n", yaml_scalar(scalar))),
+        }
+    }
+}
+
+// Each item is written as if it were nested one level deeper, then the start of its first
+// line is replaced with the item marker.
+fn write_yaml_items(output: &mut String, values: &[Json], indent: usize) {
+    for value in values {
+        let item = to_yaml_at(value, indent + 2);
+
+        output.push_str(&format!("{:indent$}- ", "", indent = indent));
+        output.push_str(item.trim_start());
+    }
+}
+
+fn to_yaml_at(value: &Json, indent: usize) -> String {
+    let mut output = String::new();
+
+    match value {
+        Json::Object(fields) if !fields.is_empty() => {
+            write_yaml_fields(&mut output, fields, indent)
+        }
+        Json::Array(values) if !values.is_empty() => write_yaml_items(&mut output, values, indent),
+        scalar => output.push_str(&format!("{}\n", yaml_scalar(scalar))),
+    }
+
+    output
+}
+
+// JSON's scalars, including its quoted strings, are valid YAML, apart from the numbers
+// JSON has no way to write. Only empty collections need the flow style.
+fn yaml_scalar(value: &Json) -> String {
+    match value {
+        Json::Object(_) => "{}".to_string(),
+        Json::Array(_) => "[]".to_string(),
+        Json::Number(value) if value.is_nan() => ".nan".to_string(),
+        Json::Number(value) if value.is_infinite() && *value > 0.0 => ".inf".to_string(),
+        Json::Number(value) if value.is_infinite() => "-.inf".to_string(),
+        scalar => scalar.to_string(),
+    }
+}
+
+/// TOML has no null, so fields that are null are left out, as TOML documents leave out
+/// settings without a value. A null in a list can't be left out without moving what
+/// follows it, so the document can't be written and `None` is returned. Only objects can
+/// be documents, so anything else is wrapped in a `value` key.
+pub(crate) fn to_toml(value: &Json) -> Option<String> {
+    if has_null_item(value) {
+        return None;
+    }
+
+    let mut output = String::new();
+
+    match value {
+        Json::Object(fields) => write_toml_table(&mut output, fields, &[]),
+        value => write_toml_table(&mut output, &[("value", value.clone())], &[]),
+    }
+
+    Some(output)
+}
+
+fn has_null_item(value: &Json) -> bool {
+    match value {
+        Json::Array(values) => values
+            .iter()
+            .any(|value| *value == Json::Null || has_null_item(value)),
+        Json::Object(fields) => fields.iter().any(|(_, value)| has_null_item(value)),
+        _ => false,
+    }
+}
+
+fn write_toml_table(output: &mut String, fields: &[(&'static str, Json)], path: &[&str]) {
+    // Plain keys have to come before any sub-table of the same table.
+    for (key, value) in fields {
+        if !is_toml_table(value) && !is_toml_table_array(value) && *value != Json::Null {
+            output.push_str(&format!("{} = {}\n", key, toml_value(value)));
+        }
+    }
+
+    for (key, value) in fields {
+        let path = [path, &[*key]].concat();
+
+        match value {
+            Json::Object(fields) => {
+                output.push_str(&format!("\n[{}]\n", path.join(".")));
+                write_toml_table(output, fields, &path);
+            }
+            Json::Array(values) if is_toml_table_array(value) => {
+                for value in values {
+                    if let Json::Object(fields) = value {
+                        output.push_str(&format!("\n[[{}]]\n", path.join(".")));
+                        write_toml_table(output, fields, &path);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn is_toml_table(value: &Json) -> bool {
+    matches!(value, Json::Object(_))
+}
+
+fn is_toml_table_array(value: &Json) -> bool {
+    match value {
+        Json::Array(values) => !values.is_empty() && values.iter().all(is_toml_table),
+        _ => false,
+    }
+}
+
+fn toml_value(value: &Json) -> String {
+    match value {
+        Json::Array(values) => format!(
+            "[{}]",
+            values.iter().map(toml_value).collect::<Vec<_>>().join(", ")
+        ),
+        Json::Object(fields) => {
+            let fields: Vec<_> = fields
+                .iter()
+                .filter(|(_, value)| *value != Json::Null)
+                .map(|(key, value)| format!("{} = {}", key, toml_value(value)))
+                .collect();
+
+            if fields.is_empty() {
+                "{}".to_string()
+            } else {
+                format!("{{ {} }}", fields.join(", "))
+            }
+        }
+        Json::Number(value) if value.is_nan() => "nan".to_string(),
+        Json::Number(value) if value.is_infinite() && *value > 0.0 => "inf".to_string(),
+        Json::Number(value) if value.is_infinite() => "-inf".to_string(),
+        // TOML integers stop at 64 bits, so larger whole numbers are written as floats.
+        Json::Number(value) if value.fract() == 0.0 && value.abs() >= 2f64.powi(63) => {
+            format!("{:e}", value)
+        }
+        scalar => scalar.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::object;
+
+    fn document() -> Json {
+        object(vec![
+            ("today", Json::String("2022-01-01".to_string())),
+            ("anchor", Json::Null),
+            (
+                "result",
+                object(vec![
+                    ("type", Json::String("list".to_string())),
+                    (
+                        "items",
+                        Json::Array(vec![
+                            object(vec![("date", Json::String("2022-01-15".to_string()))]),
+                            object(vec![("date", Json::String("2022-02-01".to_string()))]),
+                        ]),
+                    ),
+                    (
+                        "tags",
+                        Json::Array(vec![Json::Number(1.0), Json::Bool(true)]),
+                    ),
+                ]),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_yaml() {
+        assert_eq!(
+            to_yaml(&document()),
+            "today: \"2022-01-01\"\n\
+             anchor: null\n\
+             result:\n  \
+               type: \"list\"\n  \
+               items:\n    \
+                 - date: \"2022-01-15\"\n    \
+                 - date: \"2022-02-01\"\n  \
+               tags:\n    \
+                 - 1\n    \
+                 - true\n"
+        );
+    }
+
+    #[test]
+    fn test_yaml_scalars() {
+        assert_eq!(
+            to_yaml(&Json::String("say \"no\"\n\tthen: yes # ok".to_string())),
+            "\"say \\\"no\\\"\\n\\u0009then: yes # ok\"\n"
+        );
+        assert_eq!(to_yaml(&Json::String("null".to_string())), "\"null\"\n");
+        assert_eq!(
+            to_yaml(&Json::Array(vec![
+                Json::Number(f64::NAN),
+                Json::Number(f64::INFINITY),
+                Json::Number(-1.5),
+                Json::Array(vec![]),
+                Json::Null,
+            ])),
+            "- .nan\n- .inf\n- -1.5\n- []\n- null\n"
+        );
+        assert_eq!(to_yaml(&object(vec![])), "{}\n");
+    }
+
+    #[test]
+    fn test_toml_values() {
+        assert_eq!(
+            to_toml(&object(vec![
+                ("text", Json::String("a \"b\"\\c\nd".to_string())),
+                ("large", Json::Number(1e20)),
+                ("ratio", Json::Number(f64::NEG_INFINITY)),
+                ("empty", Json::Array(vec![])),
+                (
+                    "inline",
+                    Json::Array(vec![object(vec![]), Json::Number(2.0)])
+                ),
+            ])),
+            Some(
+                "text = \"a \\\"b\\\"\\\\c\\nd\"\n\
+                 large = 1e20\n\
+                 ratio = -inf\n\
+                 empty = []\n\
+                 inline = [{}, 2]\n"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            to_toml(&Json::String("2022-01-01".to_string())),
+            Some("value = \"2022-01-01\"\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_toml_nulls() {
+        assert_eq!(
+            to_toml(&object(vec![("anchor", Json::Null)])),
+            Some(String::new())
+        );
+        assert_eq!(
+            to_toml(&object(vec![(
+                "days",
+                Json::Array(vec![Json::Number(1.0), Json::Null, Json::Number(3.0)])
+            )])),
+            None
+        );
+    }
+
+    #[test]
+    fn test_toml() {
+        assert_eq!(
+            to_toml(&document()).unwrap(),
+            "today = \"2022-01-01\"\n\
+             \n\
+             [result]\n\
+             type = \"list\"\n\
+             tags = [1, true]\n\
+             \n\
+             [[result.items]]\n\
+             date = \"2022-01-15\"\n\
+             \n\
+             [[result.items]]\n\
+             date = \"2022-02-01\"\n"
+        );
+    }
+}
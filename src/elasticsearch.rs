@@ -0,0 +1,189 @@
+//! Elasticsearch date math, like "now-1d/d" or "2022-01-01||+1y-1d", read into the same
+//! expressions as the rest of the grammar.
+
+#[cfg(feature = "cli")]
+use crate::syntax::Form;
+use crate::{
+    calculated_date,
+    parser_utils::*,
+    splunk::{Modifier, SplunkTime, TimeUnit},
+    CalculatedDate, DateMath, Period, PeriodOp,
+};
+use chrono::{NaiveDate, Weekday};
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{char, one_of},
+    combinator::{map, map_opt, value},
+    multi::many0,
+    sequence::{pair, preceded, terminated, tuple},
+    IResult,
+};
+
+/// Elasticsearch date math, as `--help-syntax` describes it.
+#[cfg(feature = "cli")]
+pub(crate) const SYNTAX: &[Form] = &[Form {
+    pattern: "(now|<date>||)[(+|-)<count>(y|M|w|d|h|m|s)|/(y|M|w|d|h|m|s)]...",
+    description: "Elasticsearch date math, rounding down after '/', with a time of \
+                      day from now for hours, minutes, or seconds",
+    examples: &["now+1M/d", "2022-01-01||+1y-1d", "now-1d/M", "now-6h"],
+}];
+
+/// Parses an anchor of "now" or a date followed by "||", then periods like "+1M" and
+/// rounding like "/d", each applied in turn. From "now", these are the same steps as a
+/// Splunk relative time, so hours, minutes, and seconds work from the reference time.
+/// A date written out has no time of day, so after one only whole days are accepted.
+pub(crate) fn parse(input: &str) -> IResult<&str, DateMath> {
+    alt((
+        map(preceded(tag("now"), parse_modifiers), |modifiers| {
+            if modifiers.is_empty() {
+                DateMath::Start(CalculatedDate::Today)
+            } else {
+                DateMath::Splunk(SplunkTime(modifiers))
+            }
+        }),
+        map_opt(
+            pair(
+                terminated(calculated_date::parse_literal, tag("||")),
+                parse_modifiers,
+            ),
+            |(date, modifiers)| from_date(date, modifiers),
+        ),
+    ))(input)
+}
+
+// Weeks start on Monday, as in Elasticsearch.
+fn parse_modifiers(input: &str) -> IResult<&str, Vec<Modifier>> {
+    many0(alt((
+        map(
+            tuple((one_of("+-"), parse_digits::<u32>, parse_unit)),
+            |(sign, count, unit)| {
+                let count = i64::from(count);
+
+                Modifier::Offset(if sign == '-' { -count } else { count }, unit)
+            },
+        ),
+        map(preceded(char('/'), parse_unit), |unit| match unit {
+            TimeUnit::Week => Modifier::SnapToWeekday(Weekday::Mon),
+            unit => Modifier::Snap(unit),
+        }),
+    )))(input)
+}
+
+fn parse_unit(input: &str) -> IResult<&str, TimeUnit> {
+    alt((
+        value(TimeUnit::Year, char('y')),
+        value(TimeUnit::Month, char('M')),
+        value(TimeUnit::Week, char('w')),
+        value(TimeUnit::Day, char('d')),
+        value(TimeUnit::Hour, one_of("hH")),
+        value(TimeUnit::Minute, char('m')),
+        value(TimeUnit::Second, char('s')),
+    ))(input)
+}
+
+// Periods after a date are kept as they're written, so the expression reads like the rest
+// of the grammar. Rounding is applied to the date they've reached, which is then the new
+// start.
+fn from_date(mut date: NaiveDate, modifiers: Vec<Modifier>) -> Option<DateMath> {
+    let mut operations: Vec<PeriodOp> = vec![];
+
+    for modifier in modifiers {
+        let (count, unit) = match modifier {
+            Modifier::Offset(count, unit) => (count, unit),
+            snap => {
+                let reached = operations
+                    .drain(..)
+                    .try_fold(date, |date, op| op.apply(date))?;
+
+                date = snap.apply(reached.and_hms(0, 0, 0))?.date();
+                continue;
+            }
+        };
+        let magnitude = count.unsigned_abs() as usize;
+        let period = match unit {
+            TimeUnit::Year => Period::Year(magnitude),
+            TimeUnit::Month => Period::Month(magnitude),
+            TimeUnit::Week => Period::Week(magnitude),
+            TimeUnit::Day => Period::Day(magnitude),
+            _ => return None,
+        };
+
+        operations.push(if count < 0 {
+            PeriodOp::Subtract(period)
+        } else {
+            PeriodOp::Add(period)
+        });
+    }
+
+    let mut operations = operations.into_iter();
+
+    Some(match operations.next() {
+        Some(first) => {
+            DateMath::StartWithPeriods(CalculatedDate::Raw(date), first, operations.collect())
+        }
+        None => DateMath::Start(CalculatedDate::Raw(date)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(input: &str) -> Option<DateMath> {
+        match parse(input) {
+            Ok(("", math)) => Some(math),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let date = NaiveDate::from_ymd(2022, 1, 1);
+
+        assert_eq!(parsed("now"), Some(DateMath::Start(CalculatedDate::Today)));
+        assert_eq!(
+            parsed("2022-01-01||+1y-1d"),
+            Some(DateMath::StartWithPeriods(
+                CalculatedDate::Raw(date),
+                PeriodOp::Add(Period::Year(1)),
+                vec![PeriodOp::Subtract(Period::Day(1))].into()
+            ))
+        );
+        assert_eq!(
+            parsed("now+1M/d"),
+            Some(DateMath::Splunk(SplunkTime(vec![
+                Modifier::Offset(1, TimeUnit::Month),
+                Modifier::Snap(TimeUnit::Day)
+            ])))
+        );
+        assert_eq!(
+            parsed("now-6H/w"),
+            Some(DateMath::Splunk(SplunkTime(vec![
+                Modifier::Offset(-6, TimeUnit::Hour),
+                Modifier::SnapToWeekday(Weekday::Mon)
+            ])))
+        );
+        assert_eq!(
+            parsed("2022-03-31||/w"),
+            Some(DateMath::Start(CalculatedDate::Raw(NaiveDate::from_ymd(
+                2022, 3, 28
+            ))))
+        );
+        assert_eq!(
+            parsed("2022-03-31||+1d/M+1w"),
+            Some(DateMath::StartWithPeriods(
+                CalculatedDate::Raw(NaiveDate::from_ymd(2022, 4, 1)),
+                PeriodOp::Add(Period::Week(1)),
+                Default::default()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_unsupported() {
+        assert_eq!(parsed("2022-03-31||-1h"), None);
+        assert_eq!(parsed("now/q"), None);
+        assert_eq!(parsed("now-1x"), None);
+    }
+}
@@ -0,0 +1,107 @@
+use crate::{parse, parse_whole, ComputeError, ComputeOutcome, Config, DateMath, ParseError};
+use chrono::NaiveDate;
+use std::str::FromStr;
+
+/// An expression parsed once, owning both its source and parsed form, so it can be
+/// cached and evaluated against any number of reference dates.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParsedExpression {
+    source: String,
+    math: DateMath,
+}
+
+impl ParsedExpression {
+    /// Parses all of `input`, apart from surrounding whitespace.
+    pub fn new(input: &str) -> Result<Self, ParseError> {
+        parse_whole(input, parse).map(|math| ParsedExpression {
+            source: input.trim().to_string(),
+            math,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn math(&self) -> &DateMath {
+        &self.math
+    }
+
+    pub fn into_math(self) -> DateMath {
+        self.math
+    }
+
+    pub fn evaluate(&self, today: NaiveDate) -> Result<ComputeOutcome, ComputeError> {
+        self.evaluate_with_config(today, &Config::default())
+    }
+
+    pub fn evaluate_with_config(
+        &self,
+        today: NaiveDate,
+        config: &Config,
+    ) -> Result<ComputeOutcome, ComputeError> {
+        self.math.try_compute_with_config(today, config)
+    }
+}
+
+impl FromStr for ParsedExpression {
+    type Err = ParseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        ParsedExpression::new(value)
+    }
+}
+
+impl std::fmt::Display for ParsedExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cached() -> ParsedExpression {
+        // Parsed from a temporary, so nothing is borrowed from the input.
+        "  today + 2 weeks ".to_string().parse().unwrap()
+    }
+
+    #[test]
+    fn test_evaluate_repeatedly() {
+        let expression = cached();
+
+        assert_eq!(expression.source(), "today + 2 weeks");
+        assert_eq!(
+            expression.evaluate(NaiveDate::from_ymd(2022, 1, 1)),
+            Ok(ComputeOutcome::Date(NaiveDate::from_ymd(2022, 1, 15)))
+        );
+        assert_eq!(
+            expression.evaluate(NaiveDate::from_ymd(2022, 6, 1)),
+            Ok(ComputeOutcome::Date(NaiveDate::from_ymd(2022, 6, 15)))
+        );
+    }
+
+    #[test]
+    fn test_shared_across_threads() {
+        let expression = std::sync::Arc::new(cached());
+        let handle = {
+            let expression = expression.clone();
+            std::thread::spawn(move || expression.evaluate(NaiveDate::from_ymd(2022, 3, 1)))
+        };
+
+        assert_eq!(
+            handle.join().unwrap(),
+            Ok(ComputeOutcome::Date(NaiveDate::from_ymd(2022, 3, 15)))
+        );
+    }
+
+    #[test]
+    fn test_rejects_partial_input() {
+        assert_eq!(
+            ParsedExpression::new("today + 2 wekks"),
+            Err(ParseError { offset: 5 })
+        );
+    }
+}
@@ -0,0 +1,308 @@
+//! Grafana time ranges, like "now-6h to now" or "from=now/d&to=now/d", with both ends
+//! worked out from the reference date and time.
+
+#[cfg(feature = "cli")]
+use crate::syntax::Form;
+use crate::{
+    calculated_date,
+    parser_utils::*,
+    splunk::{Modifier, TimeUnit},
+};
+use chrono::{Duration, NaiveDate, NaiveDateTime, Weekday};
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{char, one_of},
+    combinator::{map, map_opt, opt, value},
+    multi::many0,
+    sequence::{pair, preceded, separated_pair, tuple},
+    IResult,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimeRange {
+    pub(crate) from: Endpoint,
+    pub(crate) to: Endpoint,
+    /// Whether the length of the range is reported along with its ends
+    pub(crate) length: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) enum Endpoint {
+    /// "now", moved and rounded, e.g. "now-6h" or "now/d"
+    Now(Vec<Modifier>),
+    Date(NaiveDate),
+    /// Milliseconds since the Unix epoch, as in a dashboard URL
+    Epoch(NaiveDateTime),
+}
+
+impl TimeRange {
+    /// Both ends of the range. Rounding goes back to the start of the unit for the first,
+    /// and on to its last second for the second, as in Grafana, so "now/d to now/d" is
+    /// the whole of today. Weeks start on `week_start`.
+    pub fn calculate(
+        &self,
+        now: NaiveDateTime,
+        week_start: Weekday,
+    ) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        let (from, end, rounded) = self.bounds(now, week_start)?;
+
+        if rounded {
+            Some((from, end.checked_sub_signed(Duration::seconds(1))?))
+        } else {
+            Some((from, end))
+        }
+    }
+
+    /// The time between the ends. A rounded `to` covers the whole of its last second, so
+    /// "now/d to now/d" is a full day.
+    pub fn length(&self, now: NaiveDateTime, week_start: Weekday) -> Option<Duration> {
+        let (from, end, _) = self.bounds(now, week_start)?;
+
+        Some(end - from)
+    }
+
+    // The end is exclusive when it was rounded up, at the start of the next unit.
+    fn bounds(
+        &self,
+        now: NaiveDateTime,
+        week_start: Weekday,
+    ) -> Option<(NaiveDateTime, NaiveDateTime, bool)> {
+        let (from, _) = self.from.calculate(now, week_start, false)?;
+        let (end, rounded) = self.to.calculate(now, week_start, true)?;
+
+        Some((from, end, rounded))
+    }
+}
+
+impl Endpoint {
+    // Rounding up goes on to the start of the next unit, and reports that it did.
+    fn calculate(
+        &self,
+        now: NaiveDateTime,
+        week_start: Weekday,
+        round_up: bool,
+    ) -> Option<(NaiveDateTime, bool)> {
+        let modifiers = match self {
+            Endpoint::Date(date) => return Some((date.and_hms(0, 0, 0), false)),
+            Endpoint::Epoch(time) => return Some((*time, false)),
+            Endpoint::Now(modifiers) => modifiers,
+        };
+
+        modifiers
+            .iter()
+            .try_fold((now, false), |(now, rounded), modifier| {
+                let (snap, unit) = match modifier {
+                    Modifier::Snap(TimeUnit::Week) => {
+                        (Modifier::SnapToWeekday(week_start), TimeUnit::Week)
+                    }
+                    Modifier::Snap(unit) => (*modifier, *unit),
+                    _ => return Some((modifier.apply(now)?, rounded)),
+                };
+                let start = snap.apply(now)?;
+
+                if round_up {
+                    Some((Modifier::Offset(1, unit).apply(start)?, true))
+                } else {
+                    Some((start, rounded))
+                }
+            })
+    }
+}
+
+impl std::fmt::Display for TimeRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} to {}", self.from, self.to)?;
+
+        if self.length {
+            write!(f, " with length")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let modifiers = match self {
+            Endpoint::Date(date) => return write!(f, "{}", date.format("%Y-%m-%d")),
+            Endpoint::Epoch(time) => return write!(f, "{}", time.timestamp_millis()),
+            Endpoint::Now(modifiers) => modifiers,
+        };
+
+        write!(f, "now")?;
+
+        for modifier in modifiers {
+            match modifier {
+                Modifier::Offset(count, unit) => write!(f, "{:+}{}", count, unit_letter(*unit))?,
+                Modifier::Snap(unit) => write!(f, "/{}", unit_letter(*unit))?,
+                Modifier::SnapToWeekday(_) => write!(f, "/w")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn unit_letter(unit: TimeUnit) -> char {
+    match unit {
+        TimeUnit::Second => 's',
+        TimeUnit::Minute => 'm',
+        TimeUnit::Hour => 'h',
+        TimeUnit::Day => 'd',
+        TimeUnit::Week => 'w',
+        TimeUnit::Month => 'M',
+        TimeUnit::Quarter => 'Q',
+        TimeUnit::Year => 'y',
+    }
+}
+
+/// Grafana time ranges.
+#[cfg(feature = "cli")]
+pub(crate) const SYNTAX: &[Form] = &[Form {
+    pattern: "<time> to <time> [with length] | from=<time>&to=<time> [with length]",
+    description: "a Grafana time range, where each time is a date or now followed \
+                      by (+|-)<count><unit> or /<unit>",
+    examples: &["now-6h to now", "from=now/d&to=now/d with length"],
+}];
+
+/// Parses "<from> to <to>" or the "from=<from>&to=<to>" of a dashboard URL, optionally
+/// followed by "with length".
+pub(crate) fn parse(input: &str) -> IResult<&str, TimeRange> {
+    map(
+        pair(
+            alt((
+                separated_pair(parse_endpoint, tag(" to "), parse_endpoint),
+                preceded(
+                    tag("from="),
+                    separated_pair(parse_endpoint, tag("&to="), parse_endpoint),
+                ),
+            )),
+            opt(tag(" with length")),
+        ),
+        |((from, to), length)| TimeRange {
+            from,
+            to,
+            length: length.is_some(),
+        },
+    )(input)
+}
+
+fn parse_endpoint(input: &str) -> IResult<&str, Endpoint> {
+    alt((
+        map(
+            preceded(
+                tag("now"),
+                many0(alt((
+                    map(
+                        tuple((one_of("+-"), parse_digits::<u32>, parse_unit)),
+                        |(sign, count, unit)| {
+                            let count = i64::from(count);
+
+                            Modifier::Offset(if sign == '-' { -count } else { count }, unit)
+                        },
+                    ),
+                    map(preceded(char('/'), parse_unit), Modifier::Snap),
+                ))),
+            ),
+            Endpoint::Now,
+        ),
+        map(calculated_date::parse_literal, Endpoint::Date),
+        map_opt(parse_digits::<i64>, |millis| {
+            NaiveDateTime::from_timestamp_opt(
+                millis.div_euclid(1000),
+                (millis.rem_euclid(1000) * 1_000_000) as u32,
+            )
+            .map(Endpoint::Epoch)
+        }),
+    ))(input)
+}
+
+fn parse_unit(input: &str) -> IResult<&str, TimeUnit> {
+    alt((
+        value(TimeUnit::Second, char('s')),
+        value(TimeUnit::Minute, char('m')),
+        value(TimeUnit::Hour, char('h')),
+        value(TimeUnit::Day, char('d')),
+        value(TimeUnit::Week, char('w')),
+        value(TimeUnit::Month, char('M')),
+        value(TimeUnit::Quarter, char('Q')),
+        value(TimeUnit::Year, char('y')),
+    ))(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calculate(input: &str) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        let (rest, range) = parse(input).unwrap();
+        assert_eq!(rest, "");
+
+        // A Thursday
+        range.calculate(
+            NaiveDate::from_ymd(2022, 3, 31).and_hms(14, 35, 0),
+            Weekday::Mon,
+        )
+    }
+
+    fn at(day: u32, hour: u32, minute: u32, second: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd(2022, 3, day).and_hms(hour, minute, second)
+    }
+
+    #[test]
+    fn test_ranges() {
+        assert_eq!(
+            calculate("now-6h to now"),
+            Some((at(31, 8, 35, 0), at(31, 14, 35, 0)))
+        );
+        assert_eq!(
+            calculate("now/d to now/d"),
+            Some((at(31, 0, 0, 0), at(31, 23, 59, 59)))
+        );
+        assert_eq!(
+            calculate("from=now-1w/w&to=now-1w/w"),
+            Some((at(21, 0, 0, 0), at(27, 23, 59, 59)))
+        );
+        assert_eq!(
+            calculate("2022-03-01 to now/M"),
+            Some((at(1, 0, 0, 0), at(31, 23, 59, 59)))
+        );
+        assert_eq!(
+            calculate("from=1648737300000&to=now"),
+            Some((at(31, 14, 35, 0), at(31, 14, 35, 0)))
+        );
+    }
+
+    #[test]
+    fn test_length() {
+        let length = |input| {
+            let (_, range) = parse(input).unwrap();
+
+            range
+                .length(
+                    NaiveDate::from_ymd(2022, 3, 31).and_hms(14, 35, 0),
+                    Weekday::Mon,
+                )
+                .map(|length| length.num_seconds())
+        };
+
+        assert_eq!(length("now-6h to now"), Some(6 * 3600));
+        assert_eq!(length("now/d to now/d"), Some(86_400));
+        assert_eq!(length("now-1y/y to now-1y/y"), Some(365 * 86_400));
+    }
+
+    #[test]
+    fn test_display() {
+        let (_, range) = parse("from=now-1M/M&to=now with length").unwrap();
+
+        assert_eq!(range.to_string(), "now-1M/M to now with length");
+        assert!(range.length);
+
+        let (_, range) = parse("from=1648737300000&to=now").unwrap();
+
+        assert_eq!(range.to_string(), "1648737300000 to now");
+    }
+}
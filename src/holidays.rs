@@ -0,0 +1,199 @@
+use crate::date_utils;
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+pub trait HolidayCalendar: std::fmt::Debug + Send + Sync {
+    fn is_holiday(&self, date: NaiveDate) -> bool;
+
+    fn is_business_day(&self, date: NaiveDate) -> bool {
+        !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !self.is_holiday(date)
+    }
+}
+
+/// Treats every weekday as a business day.
+#[derive(Clone, Debug, Default)]
+pub struct NoHolidays;
+
+impl HolidayCalendar for NoHolidays {
+    fn is_holiday(&self, _date: NaiveDate) -> bool {
+        false
+    }
+}
+
+/// A fixed set of holiday dates, e.g. provided on the command line.
+#[derive(Clone, Debug, Default)]
+pub struct HolidayList(Vec<NaiveDate>);
+
+impl HolidayList {
+    pub fn new(dates: Vec<NaiveDate>) -> Self {
+        HolidayList(dates)
+    }
+}
+
+impl HolidayCalendar for HolidayList {
+    fn is_holiday(&self, date: NaiveDate) -> bool {
+        self.0.contains(&date)
+    }
+}
+
+/// Combines two calendars, e.g. a market's holidays with a list of office closures.
+impl<A: HolidayCalendar, B: HolidayCalendar> HolidayCalendar for (A, B) {
+    fn is_holiday(&self, date: NaiveDate) -> bool {
+        self.0.is_holiday(date) || self.1.is_holiday(date)
+    }
+}
+
+/// More business days than there are days in the supported years, so larger counts are
+/// rejected before stepping through them one at a time.
+const MAX_BUSINESS_DAYS: usize = date_utils::SUPPORTED_DAYS as usize;
+
+pub(crate) fn next_business_day(
+    date: NaiveDate,
+    calendar: &dyn HolidayCalendar,
+) -> Option<NaiveDate> {
+    step_to_business_day(date, Duration::days(1), calendar)
+}
+
+pub(crate) fn previous_business_day(
+    date: NaiveDate,
+    calendar: &dyn HolidayCalendar,
+) -> Option<NaiveDate> {
+    step_to_business_day(date, Duration::days(-1), calendar)
+}
+
+/// The date `days` business days after `date`, or `None` past the supported years.
+pub(crate) fn add_business_days(
+    date: NaiveDate,
+    days: usize,
+    calendar: &dyn HolidayCalendar,
+) -> Option<NaiveDate> {
+    if days > MAX_BUSINESS_DAYS {
+        return None;
+    }
+
+    (0..days).try_fold(date, |acc, _| next_business_day(acc, calendar))
+}
+
+pub(crate) fn subtract_business_days(
+    date: NaiveDate,
+    days: usize,
+    calendar: &dyn HolidayCalendar,
+) -> Option<NaiveDate> {
+    if days > MAX_BUSINESS_DAYS {
+        return None;
+    }
+
+    (0..days).try_fold(date, |acc, _| previous_business_day(acc, calendar))
+}
+
+/// How far a business day is looked for, so a calendar that never opens, e.g. one listing
+/// every day as a holiday, has none rather than being searched forever.
+const MAX_CLOSED_DAYS: usize = 366;
+
+fn step_to_business_day(
+    date: NaiveDate,
+    step: Duration,
+    calendar: &dyn HolidayCalendar,
+) -> Option<NaiveDate> {
+    let mut current = date;
+
+    for _ in 0..MAX_CLOSED_DAYS {
+        current = current.checked_add_signed(step)?;
+
+        if calendar.is_business_day(current) {
+            return Some(current).filter(|date| date_utils::in_supported_range(*date));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_business_day() {
+        // July 4, 2025 is a Friday
+        let independence_day = NaiveDate::from_ymd(2025, 7, 4);
+
+        assert_eq!(
+            next_business_day(independence_day, &NoHolidays),
+            Some(NaiveDate::from_ymd(2025, 7, 7))
+        );
+        assert_eq!(
+            next_business_day(NaiveDate::from_ymd(2025, 7, 3), &NoHolidays),
+            Some(independence_day)
+        );
+        assert_eq!(
+            next_business_day(
+                NaiveDate::from_ymd(2025, 7, 3),
+                &HolidayList::new(vec![independence_day])
+            ),
+            Some(NaiveDate::from_ymd(2025, 7, 7))
+        );
+    }
+
+    #[test]
+    fn test_add_business_days() {
+        // March 1, 2024 is a Friday
+        let trade_date = NaiveDate::from_ymd(2024, 3, 1);
+
+        assert_eq!(
+            add_business_days(trade_date, 0, &NoHolidays),
+            Some(trade_date)
+        );
+        assert_eq!(
+            add_business_days(trade_date, 2, &NoHolidays),
+            Some(NaiveDate::from_ymd(2024, 3, 5))
+        );
+        assert_eq!(
+            add_business_days(
+                trade_date,
+                2,
+                &HolidayList::new(vec![NaiveDate::from_ymd(2024, 3, 4)])
+            ),
+            Some(NaiveDate::from_ymd(2024, 3, 6))
+        );
+        assert_eq!(
+            add_business_days(trade_date, 99_999_999_999, &NoHolidays),
+            None
+        );
+        assert_eq!(
+            subtract_business_days(trade_date, 9_000_000, &NoHolidays),
+            None
+        );
+    }
+
+    #[test]
+    fn test_previous_business_day() {
+        assert_eq!(
+            previous_business_day(NaiveDate::from_ymd(2025, 7, 7), &NoHolidays),
+            Some(NaiveDate::from_ymd(2025, 7, 4))
+        );
+        assert_eq!(
+            previous_business_day(
+                NaiveDate::from_ymd(2025, 7, 7),
+                &HolidayList::new(vec![NaiveDate::from_ymd(2025, 7, 4)])
+            ),
+            Some(NaiveDate::from_ymd(2025, 7, 3))
+        );
+    }
+
+    #[derive(Debug)]
+    struct AlwaysClosed;
+
+    impl HolidayCalendar for AlwaysClosed {
+        fn is_holiday(&self, _date: NaiveDate) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_no_business_days() {
+        let date = NaiveDate::from_ymd(2025, 7, 7);
+
+        assert_eq!(next_business_day(date, &AlwaysClosed), None);
+        assert_eq!(previous_business_day(date, &AlwaysClosed), None);
+        assert_eq!(add_business_days(date, 0, &AlwaysClosed), Some(date));
+    }
+}
@@ -0,0 +1,282 @@
+//! Jira JQL dates, like "startOfMonth(-1)" or "-7d", so the dates a saved filter looks at
+//! can be worked out.
+
+#[cfg(feature = "cli")]
+use crate::syntax::Form;
+use crate::{
+    calculated_date::{self, Edge, Span},
+    date_utils,
+    parser_utils::*,
+    Config, Period, PeriodOp, Unit,
+};
+use chrono::NaiveDate;
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{char, one_of, satisfy, space1},
+    combinator::{map, not, opt, value},
+    multi::separated_list1,
+    sequence::{delimited, pair, terminated, tuple},
+    IResult,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum JqlDate {
+    /// Calendar days from today, e.g. "-7d" or "+1w 2d"
+    Relative(PeriodOp),
+    /// The start or end of the day, week, month, or year, with an optional increment, e.g.
+    /// "endOfWeek(-1)" or "startOfMonth(+14d)"
+    Function(Edge, Unit, Option<PeriodOp>),
+}
+
+impl JqlDate {
+    /// The date in JQL's terms, or `None` when it's past the supported years. An increment
+    /// in the function's own unit picks another day, week, month, or year, so
+    /// "endOfMonth(-1)" is the end of last month; any other is added to the start or end of
+    /// the current one, so "startOfMonth(+14d)" is the 15th.
+    pub fn calculate(&self, today: NaiveDate, config: &Config) -> Option<NaiveDate> {
+        let (unit, edge, increment) = match self {
+            JqlDate::Relative(op) => return op.apply_with_config(today, config),
+            JqlDate::Function(edge, unit, increment) => (*unit, *edge, *increment),
+        };
+        let (before, after) = match increment {
+            Some(op) if op.period().unit() == unit => (Some(op), None),
+            increment => (None, increment),
+        };
+        let date = match before {
+            Some(op) => op
+                .apply_with_config(today, config)
+                .filter(|date| date_utils::in_supported_range(*date))?,
+            None => today,
+        };
+        let date = match span(unit) {
+            Some(span) => calculated_date::boundary(date, edge, span, config)?,
+            None => date,
+        };
+
+        match after {
+            Some(op) => op.apply_with_config(date, config),
+            None => Some(date),
+        }
+    }
+}
+
+impl std::fmt::Display for JqlDate {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            JqlDate::Relative(op) => {
+                write!(f, "{}{}d", sign(*op), op.period().count().unwrap_or(0))
+            }
+            JqlDate::Function(edge, unit, increment) => {
+                let edge = match edge {
+                    Edge::Start => "startOf",
+                    Edge::End => "endOf",
+                };
+                let name = match unit {
+                    Unit::Week => "Week",
+                    Unit::Month => "Month",
+                    Unit::Year => "Year",
+                    _ => "Day",
+                };
+                let increment = increment.map_or(String::new(), |op| {
+                    let period = op.period();
+                    let suffix = if period.unit() == *unit {
+                        String::new()
+                    } else {
+                        unit_letter(period.unit()).to_string()
+                    };
+
+                    format!("{}{}{}", sign(op), period.count().unwrap_or(0), suffix)
+                });
+
+                write!(f, "{}{}({})", edge, name, increment)
+            }
+        }
+    }
+}
+
+fn span(unit: Unit) -> Option<Span> {
+    match unit {
+        Unit::Week => Some(Span::Week),
+        Unit::Month => Some(Span::Month),
+        Unit::Year => Some(Span::Year),
+        _ => None,
+    }
+}
+
+fn sign(op: PeriodOp) -> char {
+    if op.is_subtraction() {
+        '-'
+    } else {
+        '+'
+    }
+}
+
+fn unit_letter(unit: Unit) -> char {
+    match unit {
+        Unit::Week => 'w',
+        Unit::Month => 'M',
+        Unit::Year => 'y',
+        _ => 'd',
+    }
+}
+
+/// JQL functions and relative dates, read wherever a date is.
+#[cfg(feature = "cli")]
+pub(crate) const SYNTAX: &[Form] = &[Form {
+    pattern: "(startOf|endOf)(Day|Week|Month|Year)([(+|-)<count>[y|M|w|d]]) | (+|-)<count>(w|d)",
+    description: "Jira JQL date functions and relative dates",
+    examples: &[
+        "startOfMonth()",
+        "endOfWeek(-1)",
+        "startOfMonth(\"+14d\")",
+        "-7d",
+    ],
+}];
+
+/// Parses JQL's date functions, like "startOfMonth()" and "endOfWeek(-1)", and its signed
+/// relative dates, like "-7d". Hours and minutes aren't accepted, since dates have no time.
+pub(crate) fn parse(input: &str) -> IResult<&str, JqlDate> {
+    alt((
+        map(
+            pair(
+                one_of("+-"),
+                separated_list1(
+                    space1,
+                    terminated(
+                        pair(parse_digits, one_of("wd")),
+                        not(satisfy(char::is_alphanumeric)),
+                    ),
+                ),
+            ),
+            |(sign, parts): (char, Vec<(usize, char)>)| {
+                let days = parts.into_iter().fold(0usize, |total, (count, unit)| {
+                    let days = if unit == 'w' {
+                        count.saturating_mul(7)
+                    } else {
+                        count
+                    };
+
+                    total.saturating_add(days)
+                });
+
+                JqlDate::Relative(operation(sign, Period::Day(days)))
+            },
+        ),
+        map(
+            tuple((
+                alt((
+                    value(Edge::Start, tag("startOf")),
+                    value(Edge::End, tag("endOf")),
+                )),
+                alt((
+                    value(Unit::Day, tag("Day")),
+                    value(Unit::Week, tag("Week")),
+                    value(Unit::Month, tag("Month")),
+                    value(Unit::Year, tag("Year")),
+                )),
+                delimited(
+                    char('('),
+                    opt(alt((
+                        delimited(char('"'), parse_increment, char('"')),
+                        parse_increment,
+                    ))),
+                    char(')'),
+                ),
+            )),
+            |(edge, unit, increment)| {
+                JqlDate::Function(
+                    edge,
+                    unit,
+                    increment.map(|(sign, count, letter)| {
+                        let unit = match letter {
+                            Some('y') => Unit::Year,
+                            Some('M') => Unit::Month,
+                            Some('w') => Unit::Week,
+                            Some(_) => Unit::Day,
+                            None => unit,
+                        };
+
+                        operation(sign.unwrap_or('+'), unit.of(count))
+                    }),
+                )
+            },
+        ),
+    ))(input)
+}
+
+// An increment like "-1" or "+14d", with the unit left to the function when it's missing.
+fn parse_increment(input: &str) -> IResult<&str, (Option<char>, usize, Option<char>)> {
+    tuple((opt(one_of("+-")), parse_digits, opt(one_of("yMwd"))))(input)
+}
+
+fn operation(sign: char, period: Period) -> PeriodOp {
+    if sign == '-' {
+        PeriodOp::Subtract(period)
+    } else {
+        PeriodOp::Add(period)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calculate(input: &str) -> Option<NaiveDate> {
+        let (rest, date) = parse(input).unwrap();
+        assert_eq!(rest, "");
+
+        date.calculate(NaiveDate::from_ymd(2022, 3, 31), &Config::default())
+    }
+
+    #[test]
+    fn test_functions() {
+        assert_eq!(
+            calculate("startOfMonth()"),
+            Some(NaiveDate::from_ymd(2022, 3, 1))
+        );
+        assert_eq!(
+            calculate("endOfMonth(-1)"),
+            Some(NaiveDate::from_ymd(2022, 2, 28))
+        );
+        assert_eq!(
+            calculate("startOfMonth(\"+14d\")"),
+            Some(NaiveDate::from_ymd(2022, 3, 15))
+        );
+        assert_eq!(
+            calculate("endOfWeek(-1)"),
+            Some(NaiveDate::from_ymd(2022, 3, 27))
+        );
+        assert_eq!(
+            calculate("startOfYear(1)"),
+            Some(NaiveDate::from_ymd(2023, 1, 1))
+        );
+        assert_eq!(
+            calculate("endOfDay(-1M)"),
+            Some(NaiveDate::from_ymd(2022, 2, 28))
+        );
+        assert_eq!(calculate("startOfMonth(-99999999M)"), None);
+    }
+
+    #[test]
+    fn test_relative() {
+        assert_eq!(calculate("-7d"), Some(NaiveDate::from_ymd(2022, 3, 24)));
+        assert_eq!(calculate("+1w 2d"), Some(NaiveDate::from_ymd(2022, 4, 9)));
+        assert!(parse("-4h").is_err());
+        assert!(parse("7d").is_err());
+        assert!(parse("startOfMonth(-1h)").is_err());
+    }
+
+    #[test]
+    fn test_display() {
+        for (input, expected) in &[
+            ("startOfMonth()", "startOfMonth()"),
+            ("endOfWeek(-1)", "endOfWeek(-1)"),
+            ("startOfMonth(\"14d\")", "startOfMonth(+14d)"),
+            ("+1w 2d", "+9d"),
+        ] {
+            assert_eq!(parse(input).unwrap().1.to_string(), *expected);
+        }
+    }
+}
@@ -0,0 +1,589 @@
+//! A minimal JSON representation of parsed expressions, for tools that consume the
+//! grammar's interpretation without evaluating it.
+
+use crate::{
+    calculated_date::{CalculatedDate, Edge, MonthOf, Span},
+    date_utils::{self, DayKind},
+    period::Quantity,
+    ComputeOutcome, DateMath, DiffFormat, Period, PeriodOp, Predicate,
+};
+use chrono::{NaiveDate, NaiveDateTime};
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(&'static str, Json)>),
+}
+
+pub(crate) trait ToJson {
+    fn to_json(&self) -> Json;
+}
+
+pub(crate) fn object(fields: Vec<(&'static str, Json)>) -> Json {
+    Json::Object(fields)
+}
+
+fn tagged(kind: &str, mut fields: Vec<(&'static str, Json)>) -> Json {
+    fields.insert(0, ("type", Json::String(kind.to_string())));
+    Json::Object(fields)
+}
+
+impl std::fmt::Display for Json {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(value) => write!(f, "{}", value),
+            // JSON has no infinities or NaN, so they're written as null.
+            Json::Number(value) if !value.is_finite() => write!(f, "null"),
+            Json::Number(value) => write!(f, "{}", value),
+            Json::String(value) => write_string(f, value),
+            Json::Array(values) => {
+                write!(f, "[")?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(fields) => {
+                write!(f, "{{")?;
+                for (index, (key, value)) in fields.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut std::fmt::Formatter, value: &str) -> std::fmt::Result {
+    write!(f, "\"")?;
+    for c in value.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+impl<T: ToJson> ToJson for Box<T> {
+    fn to_json(&self) -> Json {
+        (**self).to_json()
+    }
+}
+
+impl ToJson for NaiveDate {
+    fn to_json(&self) -> Json {
+        Json::String(self.to_string())
+    }
+}
+
+impl ToJson for NaiveDateTime {
+    fn to_json(&self) -> Json {
+        Json::String(self.format("%Y-%m-%dT%H:%M:%S").to_string())
+    }
+}
+
+impl ToJson for ComputeOutcome {
+    fn to_json(&self) -> Json {
+        match self {
+            ComputeOutcome::Date(date) => tagged("date", vec![("date", date.to_json())]),
+            ComputeOutcome::DateTime(datetime) => {
+                tagged("datetime", vec![("datetime", datetime.to_json())])
+            }
+            ComputeOutcome::DifferenceInDays(days) => {
+                tagged("days", vec![("days", Json::Number(*days as f64))])
+            }
+            ComputeOutcome::DifferenceInYears(years) => {
+                tagged("years", vec![("years", Json::Number(*years as f64))])
+            }
+            ComputeOutcome::Weekday(weekday) => tagged(
+                "weekday",
+                vec![(
+                    "weekday",
+                    Json::String(date_utils::weekday_name(*weekday).to_string()),
+                )],
+            ),
+            ComputeOutcome::Comparison(first, second) => tagged(
+                "comparison",
+                vec![
+                    ("first", first.to_json()),
+                    ("second", second.to_json()),
+                    ("days", Json::Number((*second - *first).num_days() as f64)),
+                ],
+            ),
+            ComputeOutcome::Duration(periods) => tagged(
+                "duration",
+                vec![(
+                    "periods",
+                    Json::Array(periods.iter().map(ToJson::to_json).collect()),
+                )],
+            ),
+            ComputeOutcome::Boolean(value) => {
+                tagged("boolean", vec![("value", Json::Bool(*value))])
+            }
+            ComputeOutcome::Percentage(value) => {
+                tagged("percentage", vec![("value", Json::Number(*value))])
+            }
+            ComputeOutcome::TimeSpan(seconds) => tagged(
+                "time_span",
+                vec![("seconds", Json::Number(*seconds as f64))],
+            ),
+            ComputeOutcome::List(outcomes) => tagged(
+                "list",
+                vec![(
+                    "items",
+                    Json::Array(outcomes.iter().map(ToJson::to_json).collect()),
+                )],
+            ),
+        }
+    }
+}
+
+impl ToJson for DateMath {
+    fn to_json(&self) -> Json {
+        let operations = |first: Option<Json>, rest: &[PeriodOp]| {
+            Json::Array(
+                first
+                    .into_iter()
+                    .chain(rest.iter().map(ToJson::to_json))
+                    .collect(),
+            )
+        };
+
+        match self {
+            DateMath::Periods(first, rest) => tagged(
+                "periods",
+                vec![
+                    ("anchor", Json::Null),
+                    (
+                        "operations",
+                        operations(Some(PeriodOp::Add(*first).to_json()), rest),
+                    ),
+                ],
+            ),
+            DateMath::Start(date) => tagged("date", vec![("anchor", date.to_json())]),
+            DateMath::StartWithPeriods(date, first, rest) => tagged(
+                "periods",
+                vec![
+                    ("anchor", date.to_json()),
+                    ("operations", operations(Some(first.to_json()), rest)),
+                ],
+            ),
+            DateMath::DateDiff(from, to) => tagged(
+                "difference",
+                vec![("from", from.to_json()), ("to", to.to_json())],
+            ),
+            DateMath::Schedule(schedule, anchor, count) => tagged(
+                "schedule",
+                vec![
+                    ("schedule", schedule.to_json()),
+                    ("anchor", anchor.to_json()),
+                    ("count", Json::Number(*count as f64)),
+                ],
+            ),
+            DateMath::Age(on, born) => {
+                tagged("age", vec![("on", on.to_json()), ("born", born.to_json())])
+            }
+            DateMath::WeekdayOf(math) => tagged("weekday_of", vec![("expression", math.to_json())]),
+            DateMath::Compare(first, second) => tagged(
+                "compare",
+                vec![("first", first.to_json()), ("second", second.to_json())],
+            ),
+            DateMath::DaysBetween(kind, first, second) => tagged(
+                "days_between",
+                vec![
+                    (
+                        "kind",
+                        Json::String(
+                            match kind {
+                                DayKind::Weekday => "weekday",
+                                DayKind::Weekend => "weekend",
+                            }
+                            .to_string(),
+                        ),
+                    ),
+                    ("first", first.to_json()),
+                    ("second", second.to_json()),
+                ],
+            ),
+            DateMath::Elapsed(span, date) => tagged(
+                "elapsed",
+                vec![("span", span.to_json()), ("date", date.to_json())],
+            ),
+            DateMath::DiffIn(math, format) => tagged(
+                "difference_in",
+                vec![
+                    ("expression", math.to_json()),
+                    (
+                        "format",
+                        Json::String(
+                            match format {
+                                DiffFormat::Days => "days",
+                                DiffFormat::Auto => "auto",
+                                DiffFormat::WeeksAndDays => "weeks",
+                                DiffFormat::Months => "months",
+                            }
+                            .to_string(),
+                        ),
+                    ),
+                ],
+            ),
+            DateMath::Convert(math, conversion) => tagged(
+                "convert",
+                vec![
+                    ("expression", math.to_json()),
+                    ("units", Json::String(conversion.name().to_string())),
+                ],
+            ),
+            DateMath::DaysIn(math) => tagged("days_in", vec![("expression", math.to_json())]),
+            DateMath::MonthDays(days, months) => tagged(
+                "month_days",
+                vec![
+                    (
+                        "days",
+                        Json::Array(
+                            days.days()
+                                .iter()
+                                .map(|day| Json::Number(*day as f64))
+                                .collect(),
+                        ),
+                    ),
+                    (
+                        "months",
+                        months.map_or(Json::Null, |months| Json::Number(months as f64)),
+                    ),
+                ],
+            ),
+            DateMath::DaysLeft(span) => tagged("days_left", vec![("span", span.to_json())]),
+            DateMath::DaysRemainingInCycle => tagged("days_remaining_in_cycle", vec![]),
+            DateMath::FractionOfCycleRemaining => tagged("fraction_of_cycle_remaining", vec![]),
+            DateMath::Splunk(time) => {
+                tagged("splunk", vec![("value", Json::String(time.to_string()))])
+            }
+            DateMath::TimeRange(range) => tagged(
+                "time_range",
+                vec![
+                    ("from", Json::String(range.from.to_string())),
+                    ("to", Json::String(range.to.to_string())),
+                    ("length", Json::Bool(range.length)),
+                ],
+            ),
+            DateMath::Is(subject, predicate) => tagged(
+                "is",
+                vec![
+                    ("subject", subject.to_json()),
+                    ("predicate", predicate.to_json()),
+                ],
+            ),
+        }
+    }
+}
+
+impl ToJson for CalculatedDate {
+    fn to_json(&self) -> Json {
+        match self {
+            CalculatedDate::Today => tagged("today", vec![]),
+            CalculatedDate::Yesterday => tagged("yesterday", vec![]),
+            CalculatedDate::Tomorrow => tagged("tomorrow", vec![]),
+            CalculatedDate::NextMonthDay(day) => {
+                tagged("next_month_day", vec![("day", Json::Number(*day as f64))])
+            }
+            CalculatedDate::PreviousMonthDay(day) => tagged(
+                "previous_month_day",
+                vec![("day", Json::Number(*day as f64))],
+            ),
+            CalculatedDate::NextMonth(month) => {
+                tagged("next_month", vec![("month", Json::Number(*month as f64))])
+            }
+            CalculatedDate::PreviousMonth(month) => tagged(
+                "previous_month",
+                vec![("month", Json::Number(*month as f64))],
+            ),
+            CalculatedDate::NextAnniversary(date) => {
+                tagged("next_anniversary", vec![("date", date.to_json())])
+            }
+            CalculatedDate::Boundary(edge, span) => tagged(
+                "boundary",
+                vec![
+                    (
+                        "edge",
+                        Json::String(
+                            match edge {
+                                Edge::Start => "start",
+                                Edge::End => "end",
+                            }
+                            .to_string(),
+                        ),
+                    ),
+                    ("span", span.to_json()),
+                ],
+            ),
+            CalculatedDate::FirstBusinessDayAfter(date) => {
+                tagged("first_business_day_after", vec![("date", date.to_json())])
+            }
+            CalculatedDate::LastBusinessDayBefore(date) => {
+                tagged("last_business_day_before", vec![("date", date.to_json())])
+            }
+            CalculatedDate::Settlement(days, date) => tagged(
+                "settlement",
+                vec![
+                    ("days", Json::Number(*days as f64)),
+                    ("date", date.to_json()),
+                ],
+            ),
+            CalculatedDate::BusinessDayOfMonth(nth, month) => tagged(
+                "business_day_of_month",
+                vec![
+                    ("nth", Json::Number(*nth as f64)),
+                    (
+                        "month",
+                        match month {
+                            MonthOf::Relative(offset) => {
+                                object(vec![("offset", Json::Number(*offset as f64))])
+                            }
+                            MonthOf::Named(month) => {
+                                object(vec![("month", Json::Number(*month as f64))])
+                            }
+                        },
+                    ),
+                ],
+            ),
+            CalculatedDate::UpcomingWeekday(weekday) => tagged(
+                "upcoming_weekday",
+                vec![(
+                    "weekday",
+                    Json::String(date_utils::weekday_name(*weekday).to_string()),
+                )],
+            ),
+            CalculatedDate::NextWeekend => tagged("next_weekend", vec![]),
+            CalculatedDate::PreviousWeekend => tagged("previous_weekend", vec![]),
+            CalculatedDate::WeekdayOfWeek(weekday, weeks) => tagged(
+                "weekday_of_week",
+                vec![
+                    (
+                        "weekday",
+                        Json::String(date_utils::weekday_name(*weekday).to_string()),
+                    ),
+                    ("weeks", Json::Number(*weeks as f64)),
+                ],
+            ),
+            CalculatedDate::Term(edge, name) => tagged(
+                "term",
+                vec![
+                    (
+                        "edge",
+                        Json::String(
+                            match edge {
+                                Edge::Start => "start",
+                                Edge::End => "end",
+                            }
+                            .to_string(),
+                        ),
+                    ),
+                    ("name", Json::String(name.clone())),
+                ],
+            ),
+            CalculatedDate::NextOccurrence(name) => tagged(
+                "next_occurrence",
+                vec![("name", Json::String(name.clone()))],
+            ),
+            CalculatedDate::Jql(date) => {
+                tagged("jql", vec![("value", Json::String(date.to_string()))])
+            }
+            CalculatedDate::Sprint(edge, number) => tagged(
+                "sprint",
+                vec![
+                    (
+                        "edge",
+                        Json::String(
+                            match edge {
+                                Edge::Start => "start",
+                                Edge::End => "end",
+                            }
+                            .to_string(),
+                        ),
+                    ),
+                    ("number", Json::Number(*number as f64)),
+                ],
+            ),
+            CalculatedDate::BillingDate(cycles) => tagged(
+                "billing_date",
+                vec![("cycles", Json::Number(*cycles as f64))],
+            ),
+            CalculatedDate::MonthAndDay(month, day) => tagged(
+                "month_and_day",
+                vec![
+                    ("month", Json::Number(*month as f64)),
+                    ("day", Json::Number(*day as f64)),
+                ],
+            ),
+            CalculatedDate::Raw(date) => tagged("date", vec![("date", date.to_json())]),
+        }
+    }
+}
+
+impl ToJson for Span {
+    fn to_json(&self) -> Json {
+        Json::String(match self {
+            Span::Week => "week".to_string(),
+            Span::Month => "month".to_string(),
+            Span::Year => "year".to_string(),
+            Span::Quarter(None) => "quarter".to_string(),
+            Span::Quarter(Some(quarter)) => format!("q{}", quarter),
+        })
+    }
+}
+
+impl ToJson for PeriodOp {
+    fn to_json(&self) -> Json {
+        let (op, period) = match self {
+            PeriodOp::Add(period) => ("add", period),
+            PeriodOp::Subtract(period) => ("subtract", period),
+        };
+
+        object(vec![
+            ("op", Json::String(op.to_string())),
+            ("period", period.to_json()),
+        ])
+    }
+}
+
+impl ToJson for Period {
+    fn to_json(&self) -> Json {
+        let unit = |unit: &str| ("unit", Json::String(unit.to_string()));
+
+        match self {
+            Period::Day(value) => object(vec![unit("day"), ("value", Json::Number(*value as f64))]),
+            Period::Week(value) => {
+                object(vec![unit("week"), ("value", Json::Number(*value as f64))])
+            }
+            Period::Month(value) => {
+                object(vec![unit("month"), ("value", Json::Number(*value as f64))])
+            }
+            Period::Year(value) => {
+                object(vec![unit("year"), ("value", Json::Number(*value as f64))])
+            }
+            Period::Sprint(value) => {
+                object(vec![unit("sprint"), ("value", Json::Number(*value as f64))])
+            }
+            Period::BusinessDay(value) => object(vec![
+                unit("business_day"),
+                ("value", Json::Number(*value as f64)),
+            ]),
+            Period::Work(work) => object(vec![
+                unit("work"),
+                ("value", Json::String(work.to_string())),
+            ]),
+            Period::FractionalMonths(value) => object(vec![
+                unit("month"),
+                ("value", Json::Number(*value as f64 / 1000.0)),
+            ]),
+            Period::Minute(value) => {
+                object(vec![unit("minute"), ("value", Json::Number(*value as f64))])
+            }
+            Period::Approximately(quantity, approximate_unit) => object(vec![
+                unit(approximate_unit.name()),
+                (
+                    "quantity",
+                    Json::String(
+                        match quantity {
+                            Quantity::Couple => "couple",
+                            Quantity::Few => "few",
+                        }
+                        .to_string(),
+                    ),
+                ),
+            ]),
+        }
+    }
+}
+
+impl ToJson for Predicate {
+    fn to_json(&self) -> Json {
+        let bound = |kind, math: &DateMath| tagged(kind, vec![("expression", math.to_json())]);
+
+        match self {
+            Predicate::Before(math) => bound("before", math),
+            Predicate::OnOrBefore(math) => bound("on_or_before", math),
+            Predicate::On(math) => bound("on", math),
+            Predicate::OnOrAfter(math) => bound("on_or_after", math),
+            Predicate::After(math) => bound("after", math),
+            Predicate::Between(first, second) => tagged(
+                "between",
+                vec![("first", first.to_json()), ("second", second.to_json())],
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_periods() {
+        assert_eq!(
+            crate::parse("today + 2 weeks - 1 day")
+                .unwrap()
+                .1
+                .to_json()
+                .to_string(),
+            r#"{"type":"periods","anchor":{"type":"today"},"operations":[{"op":"add","period":{"unit":"week","value":2}},{"op":"subtract","period":{"unit":"day","value":1}}]}"#
+        );
+    }
+
+    #[test]
+    fn test_outcomes() {
+        let today = NaiveDate::from_ymd(2022, 1, 31);
+        let outcome = |expression| {
+            crate::parse(expression)
+                .unwrap()
+                .1
+                .compute(today)
+                .to_json()
+                .to_string()
+        };
+
+        assert_eq!(
+            outcome("today + 2 weeks"),
+            r#"{"type":"date","date":"2022-02-14"}"#
+        );
+        assert_eq!(
+            outcome("is today after 2022-01-01"),
+            r#"{"type":"boolean","value":true}"#
+        );
+        assert_eq!(
+            outcome("next 1 '0 9 * * *'"),
+            r#"{"type":"list","items":[{"type":"datetime","datetime":"2022-01-31T09:00:00"}]}"#
+        );
+    }
+
+    #[test]
+    fn test_escaping() {
+        assert_eq!(
+            Json::String("a \"quoted\"\nline".to_string()).to_string(),
+            r#""a \"quoted\"\nline""#
+        );
+        assert_eq!(
+            Json::String("tab\there\\ \u{7} é".to_string()).to_string(),
+            r#""tab\u0009here\\ \u0007 é""#
+        );
+        assert_eq!(Json::Number(f64::NAN).to_string(), "null");
+        assert_eq!(Json::Number(f64::NEG_INFINITY).to_string(), "null");
+    }
+}
@@ -1,45 +1,208 @@
+mod ambiguity;
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impls;
+mod cadence;
 mod calculated_date;
+#[cfg(feature = "cli")]
+mod calendar_grid;
+#[cfg(feature = "calendars")]
+mod calendars;
+mod canonical;
+#[cfg(feature = "cli")]
 pub mod cli;
+mod clock;
+mod compiled;
+mod complete;
+mod confidence;
+mod config;
+mod cron;
+mod date_utils;
+mod difference;
+#[cfg(feature = "cli")]
+mod document;
+mod elasticsearch;
+mod expression;
+mod grafana;
+mod holidays;
+mod jql;
+#[cfg(feature = "cli")]
+mod json;
+mod locale;
+mod markets;
+mod month_days;
 mod parser_utils;
 mod period;
 mod period_operation;
+mod predicate;
+mod recover;
 mod relative_period;
+mod spanned;
+mod splunk;
+mod sql;
+#[cfg(feature = "cli")]
+mod syntax;
+#[cfg(feature = "cli")]
+mod template;
+mod token;
+pub mod visit;
 
-use calculated_date::CalculatedDate;
-use chrono::NaiveDate;
+pub use ambiguity::{parse_all, Interpretation};
+pub use cadence::Cadence;
+pub use calculated_date::{CalculatedDate, Edge, MonthOf, Span};
+#[cfg(feature = "calendars")]
+pub use calendars::OutputCalendar;
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+pub use clock::{FixedToday, SystemToday, TodaySource};
+pub use complete::completions;
+pub use confidence::{parse_lenient, Assumption, LenientParse};
+pub use config::{
+    Config, DiffFormat, FractionalMonthPolicy, FuzzyQuantities, LeapDayPolicy, MonthEndConvention,
+    ShortMonthPolicy, Thresholds,
+};
+use cron::CronSchedule;
+pub use date_utils::{DayKind, MAX_YEAR, MIN_YEAR};
+pub use difference::Conversion;
+pub use expression::ParsedExpression;
+pub use grafana::TimeRange;
+pub use holidays::{HolidayCalendar, HolidayList, NoHolidays};
+pub use jql::JqlDate;
+pub use markets::MarketCalendar;
+use month_days::MonthDays;
 use nom::{
     branch::alt,
-    bytes::complete::tag,
-    character::complete::space0,
-    combinator::map,
-    multi::many0,
-    sequence::{delimited, pair, separated_pair},
+    bytes::complete::{tag, take_until},
+    character::complete::{space0, space1},
+    combinator::{all_consuming, map, map_parser, not, opt, value, verify},
+    sequence::{delimited, pair, preceded},
     IResult,
 };
-use period::Period;
-use period_operation::PeriodOp;
-use std::convert::TryInto;
+pub use period::{Period, Quantity, Step, Unit, WorkDuration};
+pub use period_operation::{PeriodOp, PeriodOps};
+pub use predicate::Predicate;
+pub use recover::{parse_recovering, Diagnostic, Recovered};
+pub use spanned::{parse_spanned, NodeKind, Spanned};
+pub use splunk::SplunkTime;
+#[cfg(feature = "cli")]
+use syntax::Form;
+#[cfg(feature = "cli")]
+use token::time_unit;
+pub use token::{did_you_mean, tokenize, Token, TokenKind};
+use visit::Visit;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DateMath {
-    Periods(Period, Vec<PeriodOp>),
+    Periods(Period, PeriodOps),
     Start(CalculatedDate),
-    StartWithPeriods(CalculatedDate, PeriodOp, Vec<PeriodOp>),
+    StartWithPeriods(CalculatedDate, PeriodOp, PeriodOps),
     DateDiff(CalculatedDate, CalculatedDate),
+    Schedule(CronSchedule, CalculatedDate, usize),
+    /// Days recurring every month, e.g. "next of the 1st and 15th", or all of them over a
+    /// number of months when listing
+    MonthDays(MonthDays, Option<u32>),
+    Age(CalculatedDate, NaiveDate),
+    WeekdayOf(Box<DateMath>),
+    Compare(Box<DateMath>, Box<DateMath>),
+    Is(Box<DateMath>, Box<Predicate>),
+    Elapsed(Span, CalculatedDate),
+    DaysLeft(Span),
+    /// Days from today up to the next renewal of the billing cycle, e.g. "days remaining in
+    /// cycle"
+    DaysRemainingInCycle,
+    /// The share of the billing cycle from today up to the next renewal, e.g. "fraction of
+    /// cycle remaining"
+    FractionOfCycleRemaining,
+    /// The weekdays or weekend days between two dates, e.g. "weekdays between today and
+    /// end of month"
+    DaysBetween(DayKind, Box<DateMath>, Box<DateMath>),
+    /// A difference reported in a specific format, e.g. "... in weeks and days"
+    DiffIn(Box<DateMath>, DiffFormat),
+    /// Periods converted to other units, without a date, e.g. "3 weeks in days"
+    Convert(Box<DateMath>, Conversion),
+    /// The calendar days periods span from a date, e.g. "days in 3 months from Jan 31"
+    DaysIn(Box<DateMath>),
+    /// A Splunk relative time, e.g. "-7d@d", with a time of day when it works in hours,
+    /// minutes, or seconds
+    Splunk(SplunkTime),
+    /// Both ends of a Grafana time range, e.g. "now-6h to now", and its length when asked
+    /// for
+    TimeRange(TimeRange),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ComputeOutcome {
     Date(NaiveDate),
+    DateTime(NaiveDateTime),
     DifferenceInDays(usize),
+    DifferenceInYears(usize),
+    Weekday(Weekday),
+    Comparison(NaiveDate, NaiveDate),
+    Duration(Vec<Period>),
+    Boolean(bool),
+    Percentage(f64),
+    /// A length of time in seconds, e.g. of a Grafana time range
+    TimeSpan(i64),
+    List(Vec<ComputeOutcome>),
 }
 
 impl std::fmt::Display for ComputeOutcome {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             ComputeOutcome::Date(date) => write!(f, "{}", date),
+            ComputeOutcome::DateTime(datetime) => {
+                write!(f, "{}", datetime.format("%Y-%m-%d %H:%M"))
+            }
             ComputeOutcome::DifferenceInDays(1) => write!(f, "1 day"),
             ComputeOutcome::DifferenceInDays(days) => write!(f, "{} days", days),
+            ComputeOutcome::DifferenceInYears(1) => write!(f, "1 year"),
+            ComputeOutcome::DifferenceInYears(years) => write!(f, "{} years", years),
+            ComputeOutcome::Weekday(weekday) => write!(f, "{}", date_utils::weekday_name(*weekday)),
+            ComputeOutcome::Comparison(first, second) if first == second => {
+                write!(f, "both are {}", first)
+            }
+            ComputeOutcome::Comparison(first, second) => {
+                let (earlier, later) = (first.min(second), first.max(second));
+                let difference =
+                    ComputeOutcome::DifferenceInDays((*later - *earlier).num_days() as usize);
+
+                write!(f, "{} is {} earlier than {}", earlier, difference, later)
+            }
+            ComputeOutcome::Duration(periods) => {
+                let parts: Vec<String> = periods.iter().map(|v| v.to_string()).collect();
+
+                match &parts[..] {
+                    [] => write!(f, "0 days"),
+                    [only] => write!(f, "{}", only),
+                    [first, second] => write!(f, "{} and {}", first, second),
+                    [init @ .., last] => write!(f, "{}, and {}", init.join(", "), last),
+                }
+            }
+            ComputeOutcome::Boolean(value) => write!(f, "{}", value),
+            ComputeOutcome::Percentage(value) => write!(f, "{:.1}%", value),
+            ComputeOutcome::TimeSpan(seconds) => {
+                let parts: Vec<String> = [(86_400, "d"), (3_600, "h"), (60, "m"), (1, "s")]
+                    .iter()
+                    .scan(seconds.unsigned_abs(), |left, (size, unit)| {
+                        let count = *left / size;
+                        *left %= size;
+                        Some((count, unit))
+                    })
+                    .filter(|(count, _)| *count > 0)
+                    .map(|(count, unit)| format!("{}{}", count, unit))
+                    .collect();
+                let sign = if *seconds < 0 { "-" } else { "" };
+
+                if parts.is_empty() {
+                    write!(f, "0s")
+                } else {
+                    write!(f, "{}{}", sign, parts.join(" "))
+                }
+            }
+            ComputeOutcome::List(outcomes) => {
+                let lines: Vec<String> = outcomes.iter().map(|v| v.to_string()).collect();
+                write!(f, "{}", lines.join("\n"))
+            }
         }
     }
 }
@@ -50,30 +213,617 @@ impl From<NaiveDate> for ComputeOutcome {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ComputeError {
+    OutOfRange,
+    NotADate,
+    AmbiguousFraction,
+    VagueQuantity,
+    UnknownTerm,
+    NoBillingDay,
+    NoSprints,
+    UnknownCadence,
+    NoFixedLength,
+    /// A cron schedule asked for no times, or more than it lists at once
+    ScheduleCount,
+    /// A cron schedule that doesn't fire as many times as asked for before the supported
+    /// range ends
+    ScheduleExhausted,
+}
+
+impl std::fmt::Display for ComputeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ComputeError::OutOfRange => write!(
+                f,
+                "date is out of range; years must be between {} and {}",
+                date_utils::MIN_YEAR,
+                date_utils::MAX_YEAR
+            ),
+            ComputeError::NotADate => write!(f, "expression does not evaluate to a date"),
+            ComputeError::AmbiguousFraction => {
+                write!(
+                    f,
+                    "fractional months are ambiguous without a fractional month policy"
+                )
+            }
+            ComputeError::VagueQuantity => {
+                write!(
+                    f,
+                    "vague quantities like 'a few' are only accepted in lenient mode"
+                )
+            }
+            ComputeError::UnknownTerm => write!(f, "term is not defined in the configuration"),
+            ComputeError::NoBillingDay => {
+                write!(f, "billing dates need a billing day in the configuration")
+            }
+            ComputeError::NoSprints => {
+                write!(f, "sprints need a length and a start in the configuration")
+            }
+            ComputeError::UnknownCadence => {
+                write!(f, "cadence is not defined in the configuration")
+            }
+            ComputeError::NoFixedLength => write!(
+                f,
+                "periods have no fixed length in those units; months and years need \
+                 approximate lengths to convert to days"
+            ),
+            ComputeError::ScheduleCount => write!(
+                f,
+                "cron schedules list between 1 and {} times",
+                cron::MAX_COUNT
+            ),
+            ComputeError::ScheduleExhausted => write!(
+                f,
+                "cron schedule doesn't fire that many times within the supported range"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ComputeError {}
+
+impl ComputeOutcome {
+    pub fn date(&self) -> Option<NaiveDate> {
+        match self {
+            ComputeOutcome::Date(date) => Some(*date),
+            ComputeOutcome::DateTime(datetime) => Some(datetime.date()),
+            _ => None,
+        }
+    }
+
+    fn in_supported_range(&self) -> bool {
+        match self {
+            ComputeOutcome::Date(date) => date_utils::in_supported_range(*date),
+            ComputeOutcome::DateTime(datetime) => date_utils::in_supported_range(datetime.date()),
+            ComputeOutcome::Comparison(first, second) => {
+                date_utils::in_supported_range(*first) && date_utils::in_supported_range(*second)
+            }
+            ComputeOutcome::List(outcomes) => outcomes.iter().all(Self::in_supported_range),
+            _ => true,
+        }
+    }
+
+    fn into_weekday(self) -> Self {
+        match self {
+            ComputeOutcome::Date(date) => ComputeOutcome::Weekday(date.weekday()),
+            ComputeOutcome::DateTime(datetime) => ComputeOutcome::Weekday(datetime.weekday()),
+            ComputeOutcome::List(outcomes) => {
+                ComputeOutcome::List(outcomes.into_iter().map(Self::into_weekday).collect())
+            }
+            outcome => outcome,
+        }
+    }
+}
+
 impl DateMath {
+    /// Computes the expression against `today`.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the expression can't be computed, e.g. when the result is out of range;
+    /// `try_compute_with_config` reports that as a `ComputeError` instead.
     pub fn compute(&self, today: NaiveDate) -> ComputeOutcome {
-        match self {
-            DateMath::DateDiff(from, to) => ComputeOutcome::DifferenceInDays(
-                (from.calculate(today) - to.calculate(today))
-                    .num_days()
-                    .abs()
-                    .try_into()
-                    .unwrap(),
+        self.compute_with_config(today, &Config::default())
+    }
+
+    /// Computes the expression against the date `source` reports as today.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the expression can't be computed, as `compute` does.
+    pub fn compute_with(&self, source: &impl TodaySource) -> ComputeOutcome {
+        self.compute(source.today())
+    }
+
+    /// Computes the expression against `today` with `config`.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the expression can't be computed, as `compute` does.
+    pub fn compute_with_config(&self, today: NaiveDate, config: &Config) -> ComputeOutcome {
+        self.try_compute_with_config(today, config)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Prepares the expression for evaluating against many reference dates, e.g. in a
+    /// hot loop. Expressions made only of literal dates are computed once, and periods
+    /// are resolved up front, with runs of day-based periods merged.
+    ///
+    /// # Panics
+    ///
+    /// Like `compute`, the returned function panics when the expression can't be computed.
+    pub fn compile(&self) -> impl Fn(NaiveDate) -> ComputeOutcome + '_ {
+        self.compile_with_config(&Config::default())
+    }
+
+    /// Prepares the expression for evaluating against many reference dates with `config`.
+    ///
+    /// # Panics
+    ///
+    /// The returned function panics when the expression can't be computed, as `compile`'s
+    /// does.
+    pub fn compile_with_config(
+        &self,
+        config: &Config,
+    ) -> impl Fn(NaiveDate) -> ComputeOutcome + '_ {
+        let config = config.clone();
+        let compiled = compiled::Compiled::new(self, &config);
+
+        move |today| {
+            compiled
+                .evaluate(today, &config)
+                .unwrap_or_else(|e| panic!("{}", e))
+        }
+    }
+
+    pub fn try_compute_with_config(
+        &self,
+        today: NaiveDate,
+        config: &Config,
+    ) -> Result<ComputeOutcome, ComputeError> {
+        if !date_utils::in_supported_range(today) || !self.literals_in_supported_range() {
+            return Err(ComputeError::OutOfRange);
+        }
+
+        self.check_configuration(config)?;
+
+        let outcome = match self {
+            DateMath::DateDiff(from, to) => difference::compute(
+                from.calculate(today, config)?,
+                to.calculate(today, config)?,
+                config,
+            )?,
+            DateMath::DiffIn(math, format) => {
+                math.try_compute_with_config(today, &config.clone().diff_format(*format))?
+            }
+            // Folding may have turned the periods into operations from today.
+            DateMath::Convert(math, conversion) => {
+                let operations: PeriodOps = match &**math {
+                    DateMath::Periods(base, rest) => std::iter::once(PeriodOp::Add(*base))
+                        .chain(rest.iter().copied())
+                        .collect(),
+                    DateMath::StartWithPeriods(CalculatedDate::Today, base, rest) => {
+                        std::iter::once(*base).chain(rest.iter().copied()).collect()
+                    }
+                    DateMath::Start(CalculatedDate::Today) => PeriodOps::new(),
+                    _ => return Err(ComputeError::NoFixedLength),
+                };
+
+                difference::convert(&operations, *conversion, config)?
+            }
+            DateMath::DaysIn(math) => {
+                let anchor = match &**math {
+                    DateMath::StartWithPeriods(date, _, _) | DateMath::Start(date) => {
+                        date.calculate(today, config)?
+                    }
+                    _ => today,
+                };
+                let end = math.try_compute_date(today, config)?;
+
+                ComputeOutcome::DifferenceInDays((end - anchor).num_days().unsigned_abs() as usize)
+            }
+            DateMath::Start(v) => v.calculate(today, config)?.into(),
+            DateMath::WeekdayOf(math) => {
+                math.try_compute_with_config(today, config)?.into_weekday()
+            }
+            DateMath::Compare(first, second) => ComputeOutcome::Comparison(
+                first.try_compute_date(today, config)?,
+                second.try_compute_date(today, config)?,
             ),
-            DateMath::Start(v) => v.calculate(today).into(),
-            DateMath::StartWithPeriods(v, base, rest) => rest
-                .iter()
-                .fold(base.apply(v.calculate(today)), |acc, x| x.apply(acc))
-                .into(),
-            DateMath::Periods(base, rest) => rest
-                .iter()
-                .fold(
-                    chrono::Local::today().naive_local() + base.to_duration(),
-                    |acc, x| x.apply(acc),
+            DateMath::Is(subject, predicate) => ComputeOutcome::Boolean(predicate.matches(
+                subject.try_compute_date(today, config)?,
+                today,
+                config,
+            )?),
+            DateMath::Elapsed(span, date) => {
+                let date = date.calculate(today, config)?;
+                let start = calculated_date::boundary(date, Edge::Start, *span, config)
+                    .ok_or(ComputeError::OutOfRange)?;
+                let end = calculated_date::boundary(date, Edge::End, *span, config)
+                    .ok_or(ComputeError::OutOfRange)?;
+
+                ComputeOutcome::Percentage(
+                    100.0 * (date - start).num_days() as f64
+                        / ((end - start).num_days() + 1) as f64,
+                )
+            }
+            DateMath::DaysLeft(span) => {
+                let end = calculated_date::boundary(today, Edge::End, *span, config)
+                    .ok_or(ComputeError::OutOfRange)?;
+
+                ComputeOutcome::DifferenceInDays((end - today).num_days().max(0) as usize)
+            }
+            DateMath::DaysRemainingInCycle => {
+                let (_, next) = calculated_date::billing_cycle(today, config);
+
+                ComputeOutcome::DifferenceInDays((next - today).num_days() as usize)
+            }
+            DateMath::FractionOfCycleRemaining => {
+                let (start, next) = calculated_date::billing_cycle(today, config);
+
+                ComputeOutcome::Percentage(
+                    100.0 * (next - today).num_days() as f64 / (next - start).num_days() as f64,
                 )
-                .into(),
+            }
+            DateMath::DaysBetween(kind, first, second) => {
+                ComputeOutcome::DifferenceInDays(date_utils::count_days(
+                    *kind,
+                    first.try_compute_date(today, config)?,
+                    second.try_compute_date(today, config)?,
+                ))
+            }
+            DateMath::Age(on, born) => ComputeOutcome::DifferenceInYears(
+                date_utils::completed_years(*born, on.calculate(today, config)?, config.leap_day),
+            ),
+            DateMath::Schedule(schedule, anchor, count) => {
+                if !(1..=cron::MAX_COUNT).contains(count) {
+                    return Err(ComputeError::ScheduleCount);
+                }
+
+                let anchor = anchor.calculate(today, config)?;
+                let upcoming = match config.time {
+                    Some(time) if anchor == today => {
+                        schedule.upcoming_after(today.and_time(time), *count)
+                    }
+                    _ => schedule.upcoming(anchor, *count),
+                };
+
+                if upcoming.len() < *count {
+                    return Err(ComputeError::ScheduleExhausted);
+                }
+
+                ComputeOutcome::List(upcoming.into_iter().map(ComputeOutcome::DateTime).collect())
+            }
+            DateMath::Splunk(time) => {
+                let result = time
+                    .calculate(reference_time(today, config))
+                    .ok_or(ComputeError::OutOfRange)?;
+
+                if time.uses_time() {
+                    ComputeOutcome::DateTime(result)
+                } else {
+                    ComputeOutcome::Date(result.date())
+                }
+            }
+            DateMath::TimeRange(range) => {
+                let now = reference_time(today, config);
+                let (from, to) = range
+                    .calculate(now, config.week_start)
+                    .ok_or(ComputeError::OutOfRange)?;
+                let mut ends = vec![ComputeOutcome::DateTime(from), ComputeOutcome::DateTime(to)];
+
+                if range.length {
+                    let length = range
+                        .length(now, config.week_start)
+                        .ok_or(ComputeError::OutOfRange)?;
+
+                    ends.push(ComputeOutcome::TimeSpan(length.num_seconds()));
+                }
+
+                ComputeOutcome::List(ends)
+            }
+            DateMath::MonthDays(days, None) => ComputeOutcome::Date(
+                days.next_after(today, config)
+                    .ok_or(ComputeError::OutOfRange)?,
+            ),
+            DateMath::MonthDays(days, Some(months)) => ComputeOutcome::List(
+                days.within(today, *months, config)
+                    .ok_or(ComputeError::OutOfRange)?
+                    .into_iter()
+                    .map(ComputeOutcome::Date)
+                    .collect(),
+            ),
+            DateMath::StartWithPeriods(v, base, rest) => {
+                let start = v.calculate(today, config)?;
+                let start_time = match v {
+                    CalculatedDate::Today => reference_time(today, config),
+                    _ => start.and_hms(0, 0, 0),
+                };
+
+                apply_all(
+                    start,
+                    start_time,
+                    Some(base).into_iter().chain(rest),
+                    config,
+                )?
+            }
+            DateMath::Periods(base, rest) => {
+                let base = PeriodOp::Add(*base);
+                apply_all(
+                    today,
+                    reference_time(today, config),
+                    Some(&base).into_iter().chain(rest),
+                    config,
+                )?
+            }
+        };
+
+        if outcome.in_supported_range() {
+            Ok(outcome)
+        } else {
+            Err(ComputeError::OutOfRange)
+        }
+    }
+
+    pub(crate) fn try_compute_date(
+        &self,
+        today: NaiveDate,
+        config: &Config,
+    ) -> Result<NaiveDate, ComputeError> {
+        self.try_compute_with_config(today, config)?
+            .date()
+            .ok_or(ComputeError::NotADate)
+    }
+
+    /// Vague quantities in the expression, each paired with the period it's read as.
+    pub fn assumptions(&self, config: &Config) -> Vec<(Period, Period)> {
+        self.periods()
+            .into_iter()
+            .filter(|period| matches!(period, Period::Approximately(_, _)))
+            .filter_map(|period| Some((period, period.resolve(config).ok()?)))
+            .collect()
+    }
+
+    /// Periods given a fixed length rather than stepped through the calendar, each paired
+    /// with the days it's read as. Fractional months are always approximated, and whole
+    /// months and years are too with an approximate config.
+    pub fn approximations(&self, config: &Config) -> Vec<(Period, Period)> {
+        self.periods()
+            .into_iter()
+            .filter_map(|period| period.resolve(config).ok())
+            .filter(|period| match period {
+                Period::FractionalMonths(_) => true,
+                Period::Month(_) | Period::Year(_) => config.approximate,
+                _ => false,
+            })
+            .filter_map(|period| match period.to_step(config).ok()?.approximate() {
+                Step::Days(days) => Some((period, Period::Day(days))),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // Terms, billing cycles, sprints, and cadences only have dates once they're configured.
+    pub(crate) fn check_configuration(&self, config: &Config) -> Result<(), ComputeError> {
+        struct Configured<'a>(&'a Config, Result<(), ComputeError>);
+
+        impl Visit for Configured<'_> {
+            fn visit_calculated_date(&mut self, date: &CalculatedDate) {
+                match date {
+                    CalculatedDate::Term(_, name) if !self.0.terms.contains_key(name) => {
+                        self.1 = Err(ComputeError::UnknownTerm)
+                    }
+                    CalculatedDate::BillingDate(_) if self.0.billing_day.is_none() => {
+                        self.1 = Err(ComputeError::NoBillingDay)
+                    }
+                    CalculatedDate::Sprint(_, _) if self.0.sprints.is_none() => {
+                        self.1 = Err(ComputeError::NoSprints)
+                    }
+                    CalculatedDate::NextOccurrence(name) if !self.0.cadences.contains_key(name) => {
+                        self.1 = Err(ComputeError::UnknownCadence)
+                    }
+                    _ => {}
+                }
+
+                visit::walk_calculated_date(self, date)
+            }
+        }
+
+        let mut configured = Configured(config, Ok(()));
+        configured.visit_date_math(self);
+        configured.1
+    }
+
+    fn literals_in_supported_range(&self) -> bool {
+        struct Literals(bool);
+
+        impl Visit for Literals {
+            fn visit_date_math(&mut self, math: &DateMath) {
+                if let DateMath::Age(_, born) = math {
+                    self.0 &= date_utils::in_supported_range(*born);
+                }
+
+                visit::walk_date_math(self, math)
+            }
+
+            fn visit_calculated_date(&mut self, date: &CalculatedDate) {
+                if let CalculatedDate::Raw(date) | CalculatedDate::NextAnniversary(date) = date {
+                    self.0 &= date_utils::in_supported_range(*date);
+                }
+
+                visit::walk_calculated_date(self, date)
+            }
+        }
+
+        let mut literals = Literals(true);
+        literals.visit_date_math(self);
+        literals.0
+    }
+
+    fn periods(&self) -> Vec<Period> {
+        struct Periods(Vec<Period>);
+
+        impl Visit for Periods {
+            fn visit_period_op(&mut self, op: &PeriodOp) {
+                self.0.push(op.period());
+            }
+        }
+
+        let mut periods = Periods(vec![]);
+        periods.visit_date_math(self);
+        periods.0
+    }
+
+    /// Rewrites each run of days and weeks as weeks and then days, so expressions that
+    /// only differ in how those runs are written compare equal. Months and years keep
+    /// their place, since moving them can change the date.
+    pub fn normalize(self) -> DateMath {
+        self.rewrite_operations(period_operation::normalize)
+    }
+
+    /// Merges adjacent days and weeks into as few operations as possible, e.g. "+ 2 weeks
+    /// + 1 week - 3 days" into "+ 18 days", computing the same dates with fewer steps.
+    pub fn combine_like_units(self) -> DateMath {
+        self.rewrite_operations(period_operation::combine)
+    }
+
+    /// Writes the expression in one standard spelling, e.g. "Mar 31, 2021 + 15 weeks + 2
+    /// days" as "2021-03-31 + 15 weeks + 2 days". Any parsed expression written this way
+    /// parses back to the same expression, so it's safe to store in place of user input.
+    pub fn to_canonical_string(&self) -> String {
+        canonical::render(self)
+    }
+
+    // Rebuilds every chain of operations, including those of nested expressions.
+    fn rewrite_operations(self, rewrite: fn(PeriodOps) -> PeriodOps) -> DateMath {
+        match self {
+            DateMath::Periods(base, rest) => {
+                let mut operations =
+                    rewrite(std::iter::once(PeriodOp::Add(base)).chain(rest).collect()).into_iter();
+
+                match operations.next() {
+                    None => DateMath::Start(CalculatedDate::Today),
+                    Some(PeriodOp::Add(base)) => DateMath::Periods(base, operations.collect()),
+                    Some(first) => DateMath::StartWithPeriods(
+                        CalculatedDate::Today,
+                        first,
+                        operations.collect(),
+                    ),
+                }
+            }
+            DateMath::StartWithPeriods(date, base, rest) => {
+                let mut operations =
+                    rewrite(std::iter::once(base).chain(rest).collect()).into_iter();
+
+                match operations.next() {
+                    None => DateMath::Start(date),
+                    Some(first) => DateMath::StartWithPeriods(date, first, operations.collect()),
+                }
+            }
+            DateMath::WeekdayOf(math) => {
+                DateMath::WeekdayOf(Box::new(math.rewrite_operations(rewrite)))
+            }
+            DateMath::Compare(first, second) => DateMath::Compare(
+                Box::new(first.rewrite_operations(rewrite)),
+                Box::new(second.rewrite_operations(rewrite)),
+            ),
+            DateMath::DaysBetween(kind, first, second) => DateMath::DaysBetween(
+                kind,
+                Box::new(first.rewrite_operations(rewrite)),
+                Box::new(second.rewrite_operations(rewrite)),
+            ),
+            DateMath::Is(subject, predicate) => DateMath::Is(
+                Box::new(subject.rewrite_operations(rewrite)),
+                Box::new(predicate.map_expressions(|math| math.rewrite_operations(rewrite))),
+            ),
+            DateMath::DiffIn(math, format) => {
+                DateMath::DiffIn(Box::new(math.rewrite_operations(rewrite)), format)
+            }
+            DateMath::Convert(math, conversion) => {
+                DateMath::Convert(Box::new(math.rewrite_operations(rewrite)), conversion)
+            }
+            DateMath::DaysIn(math) => DateMath::DaysIn(Box::new(math.rewrite_operations(rewrite))),
+            other => other,
         }
     }
+
+    /// Evaluates the expression once per anchor date, treating each as "today".
+    ///
+    /// # Panics
+    ///
+    /// Panics when the expression can't be computed for one of the anchors, as `compute`
+    /// does.
+    pub fn compute_each(&self, anchors: &[NaiveDate], config: &Config) -> Vec<ComputeOutcome> {
+        anchors
+            .iter()
+            .map(|anchor| self.compute_with_config(*anchor, config))
+            .collect()
+    }
+}
+
+// Clock time like "half an hour" gives a date and time, moving on from `start_time`: the
+// time of day when counting from today, and midnight from any other date.
+fn apply_all<'a>(
+    start: NaiveDate,
+    start_time: NaiveDateTime,
+    operations: impl IntoIterator<Item = &'a PeriodOp>,
+    config: &Config,
+) -> Result<ComputeOutcome, ComputeError> {
+    let operations = operations
+        .into_iter()
+        .map(|op| op.resolve(config))
+        .collect::<Result<PeriodOps, _>>()?;
+
+    if operations.iter().any(PeriodOp::is_clock_time) {
+        operations
+            .iter()
+            .try_fold(start_time, |acc, x| x.apply_to_datetime(acc, config))
+            .map(ComputeOutcome::DateTime)
+            .ok_or(ComputeError::OutOfRange)
+    } else {
+        operations
+            .iter()
+            .try_fold(start, |acc, x| x.apply_with_config(acc, config))
+            .map(ComputeOutcome::Date)
+            .ok_or(ComputeError::OutOfRange)
+    }
+}
+
+/// Evaluates many expressions against the same reference date. With the `parallel`
+/// feature enabled, expressions are evaluated across threads.
+pub fn compute_all(
+    expressions: &[DateMath],
+    today: NaiveDate,
+) -> Vec<Result<ComputeOutcome, ComputeError>> {
+    compute_all_with_config(expressions, today, &Config::default())
+}
+
+#[cfg(not(feature = "parallel"))]
+pub fn compute_all_with_config(
+    expressions: &[DateMath],
+    today: NaiveDate,
+    config: &Config,
+) -> Vec<Result<ComputeOutcome, ComputeError>> {
+    expressions
+        .iter()
+        .map(|math| math.try_compute_with_config(today, config))
+        .collect()
+}
+
+#[cfg(feature = "parallel")]
+pub fn compute_all_with_config(
+    expressions: &[DateMath],
+    today: NaiveDate,
+    config: &Config,
+) -> Vec<Result<ComputeOutcome, ComputeError>> {
+    use rayon::prelude::*;
+
+    expressions
+        .par_iter()
+        .map(|math| math.try_compute_with_config(today, config))
+        .collect()
 }
 
 #[derive(Debug, PartialEq)]
@@ -93,37 +843,366 @@ impl<'a> From<IResult<&'a str, DateMath>> for ParseResult<'a> {
     }
 }
 
+// Without a time of day, today is treated as having just begun.
+fn reference_time(today: NaiveDate, config: &Config) -> NaiveDateTime {
+    today.and_time(config.time.unwrap_or_else(|| NaiveTime::from_hms(0, 0, 0)))
+}
+
+/// Why part of an expression couldn't be parsed on its own.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParseError {
+    /// The byte offset of the input where parsing stopped
+    pub offset: usize,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "unable to parse input at byte {}", self.offset)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses just a period, e.g. "3 weeks".
+pub fn parse_period(input: &str) -> Result<Period, ParseError> {
+    parse_whole(input, period::parse)
+}
+
+/// Parses just an addition or subtraction of a period, e.g. "- 3 weeks".
+pub fn parse_period_op(input: &str) -> Result<PeriodOp, ParseError> {
+    parse_whole(input, period_operation::parse)
+}
+
+/// Parses just a date, e.g. "next 31st" or "Mar 1, 2022".
+pub fn parse_date(input: &str) -> Result<CalculatedDate, ParseError> {
+    parse_whole(input, calculated_date::parse)
+}
+
+/// Parses Elasticsearch date math, e.g. "now-1d/d" or "2022-01-01||+1y-1d", which the
+/// full grammar also accepts.
+pub fn parse_elasticsearch(input: &str) -> Result<DateMath, ParseError> {
+    parse_whole(input, elasticsearch::parse)
+}
+
+// Runs `parser` over all of `input` apart from surrounding whitespace.
+fn parse_whole<O>(input: &str, parser: fn(&str) -> IResult<&str, O>) -> Result<O, ParseError> {
+    let trimmed = input.trim();
+
+    all_consuming(parser)(trimmed)
+        .map(|(_, output)| output)
+        .map_err(|e| {
+            let rest = match e {
+                nom::Err::Error(e) | nom::Err::Failure(e) => e.input,
+                nom::Err::Incomplete(_) => "",
+            };
+
+            ParseError {
+                offset: input.len() - input.trim_start().len() + trimmed.len() - rest.len(),
+            }
+        })
+}
+
+/// Expressions `parse` reads. Dates, periods, and the other dialects describe their own
+/// forms alongside their parsers.
+#[cfg(feature = "cli")]
+const SYNTAX: &[Form] = &[
+    Form {
+        pattern: "<date> [(+|-) <period>]...",
+        description: "a date, optionally moved by periods",
+        examples: &["dec 30, 2021 + 2 weeks + 1 day", "tomorrow"],
+    },
+    Form {
+        pattern: "<period> [(+|-) <period>]...",
+        description: "periods from today",
+        examples: &["2 weeks + 3 days"],
+    },
+    Form {
+        pattern: "<period> (ago|from <date>|after <date>|before <date>)",
+        description: "periods relative to a date",
+        examples: &["3 weeks ago", "1 year, 2 months, and 3 days from now"],
+    },
+    Form {
+        pattern: "<date> - <date> [in (weeks and days|months)]",
+        description: "the time between two dates",
+        examples: &[
+            "Mar 31, 2021 - Mar 24, 2021",
+            "Mar 31, 2021 - Mar 6, 2021 in weeks and days",
+            "Mar 31, 2021 - Jan 6, 2021 in months",
+        ],
+    },
+    Form {
+        pattern: "<period> [(+|-) <period>]... in (days|weeks|weeks and days|months|years|years and months)",
+        description: "periods converted to other units, without a date",
+        examples: &["3 weeks in days", "18 months in years and months"],
+    },
+    Form {
+        pattern: "days in <period> [(+|-) <period>]... [ago|from <date>|after <date>|before <date>]",
+        description: "calendar days the periods span, counted from the date or today",
+        examples: &["days in 3 months from Jan 31, 2022", "days in 1 year"],
+    },
+    Form {
+        pattern: "age on <date> for <date>",
+        description: "completed years between two dates",
+        examples: &["age on 2030-01-01 for 1990-05-04"],
+    },
+    Form {
+        pattern: "weekday of <expression>",
+        description: "the day of the week an expression falls on",
+        examples: &["weekday of today + 3 days"],
+    },
+    Form {
+        pattern: "compare <expression> vs <expression>",
+        description: "which of two dates comes first, and by how much",
+        examples: &["compare today vs 2 weeks ago"],
+    },
+    Form {
+        pattern: "earlier of <expression> and <expression>",
+        description: "the same comparison, worded differently",
+        examples: &["earlier of today and 2 weeks ago"],
+    },
+    Form {
+        pattern: "percent of <span> elapsed [at <date>]",
+        description: "how far through a week, month, quarter, or year a date is",
+        examples: &[
+            "percent of year elapsed",
+            "percent of Q3 elapsed at 2022-08-15",
+        ],
+    },
+    Form {
+        pattern: "days left in [the] <span>",
+        description: "days until the end of a week, month, quarter, or year",
+        examples: &["days left in month", "days left in Q3"],
+    },
+    Form {
+        pattern: "days remaining in cycle | fraction of cycle remaining",
+        description: "days up to the next billing date, or a calendar month without one",
+        examples: &["days remaining in cycle", "fraction of cycle remaining"],
+    },
+    Form {
+        pattern: "weekdays between <expression> and <expression>",
+        description: "Mondays through Fridays from the earlier date up to the later",
+        examples: &["weekdays between today and end of month"],
+    },
+    Form {
+        pattern: "weekend days between <expression> and <expression>",
+        description: "Saturdays and Sundays from the earlier date up to the later",
+        examples: &["weekend days between 2022-01-01 and 2022-03-31"],
+    },
+];
+
 pub fn parse(input: &str) -> IResult<&str, DateMath> {
     alt((
+        map(preceded(tag("weekday of "), parse), |math| {
+            DateMath::WeekdayOf(Box::new(math))
+        }),
+        map(
+            alt((
+                preceded(tag("compare "), |i| parse_separated_pair(i, " vs ")),
+                preceded(tag("earlier of "), |i| parse_separated_pair(i, " and ")),
+            )),
+            |(first, second)| DateMath::Compare(Box::new(first), Box::new(second)),
+        ),
+        parse_is,
+        map(
+            pair(
+                alt((
+                    value(DayKind::Weekday, tag("weekdays between ")),
+                    value(DayKind::Weekend, tag("weekend days between ")),
+                )),
+                |i| parse_separated_pair(i, " and "),
+            ),
+            |(kind, (first, second))| {
+                DateMath::DaysBetween(kind, Box::new(first), Box::new(second))
+            },
+        ),
+        map(
+            preceded(
+                tag("days in "),
+                verify(parse, |math| {
+                    matches!(
+                        math,
+                        DateMath::Periods(_, _) | DateMath::StartWithPeriods(_, _, _)
+                    )
+                }),
+            ),
+            |math| DateMath::DaysIn(Box::new(math)),
+        ),
+        map(
+            pair(
+                pair(period::parse, period_operation::parse_list),
+                preceded(tag(" in "), parse_conversion),
+            ),
+            |((period, periods), conversion)| {
+                DateMath::Convert(Box::new(DateMath::Periods(period, periods)), conversion)
+            },
+        ),
+        map(
+            |i| parse_split(i, " in ", parse_diff_format),
+            |(math, format)| DateMath::DiffIn(Box::new(math), format),
+        ),
+        map(
+            pair(
+                delimited(
+                    alt((tag("percent of "), tag("percentage of "))),
+                    calculated_date::parse_span,
+                    tag(" elapsed"),
+                ),
+                opt(preceded(tag(" at "), calculated_date::parse)),
+            ),
+            |(span, date)| DateMath::Elapsed(span, date.unwrap_or(CalculatedDate::Today)),
+        ),
+        map(
+            preceded(
+                pair(tag("days left in "), opt(tag("the "))),
+                calculated_date::parse_span,
+            ),
+            DateMath::DaysLeft,
+        ),
+        alt((
+            value(
+                DateMath::DaysRemainingInCycle,
+                tag("days remaining in cycle"),
+            ),
+            value(
+                DateMath::FractionOfCycleRemaining,
+                tag("fraction of cycle remaining"),
+            ),
+        )),
         map(
             pair(
-                calculated_date::parse,
-                pair(period_operation::parse, many0(period_operation::parse)),
+                cron::parse,
+                opt(preceded(
+                    delimited(space1, tag("after"), space1),
+                    calculated_date::parse,
+                )),
             ),
-            |(a, (b, c))| DateMath::StartWithPeriods(a, b, c),
+            |((schedule, count), anchor)| {
+                DateMath::Schedule(schedule, anchor.unwrap_or(CalculatedDate::Today), count)
+            },
         ),
+        map(month_days::parse, |(days, months)| {
+            DateMath::MonthDays(days, months)
+        }),
         map(
-            separated_pair(
-                calculated_date::parse,
-                delimited(space0, tag("-"), space0),
-                calculated_date::parse,
+            pair(
+                preceded(
+                    tag("age"),
+                    opt(preceded(
+                        tag(" on "),
+                        map_parser(take_until(" for "), all_consuming(calculated_date::parse)),
+                    )),
+                ),
+                preceded(tag(" for "), calculated_date::parse_literal),
             ),
-            |(from, to)| DateMath::DateDiff(from, to),
+            |(on, born)| DateMath::Age(on.unwrap_or(CalculatedDate::Today), born),
+        ),
+        all_consuming(elasticsearch::parse),
+        map(all_consuming(grafana::parse), DateMath::TimeRange),
+        // Plain day and week offsets like "-7d" are left to JQL, which reads them as dates.
+        map(
+            preceded(not(all_consuming(jql::parse)), all_consuming(splunk::parse)),
+            DateMath::Splunk,
         ),
+        parse_from_date,
         map(relative_period::parse, |(date, period_op, rest)| {
             DateMath::StartWithPeriods(date, period_op, rest)
         }),
-        map(calculated_date::parse, DateMath::Start),
         map(
-            pair(period::parse, many0(period_operation::parse)),
+            pair(period::parse, period_operation::parse_list),
             |(period, periods)| DateMath::Periods(period, periods),
         ),
     ))(input)
 }
 
+// Sub-expressions may contain the separator themselves (e.g. "2 weeks and 1 day ago"), so
+// each occurrence is tried, preferring a split where both sides parse completely.
+pub(crate) fn parse_separated_pair<'a>(
+    input: &'a str,
+    separator: &str,
+) -> IResult<&'a str, (DateMath, DateMath)> {
+    parse_split(input, separator, parse)
+}
+
+fn parse_split<'a, O>(
+    input: &'a str,
+    separator: &str,
+    second: impl Fn(&'a str) -> IResult<&'a str, O>,
+) -> IResult<&'a str, (DateMath, O)> {
+    let splits = || {
+        input.match_indices(separator).filter_map(|(index, _)| {
+            let (_, first) = all_consuming(parse)(&input[..index]).ok()?;
+            let (rest, second) = second(&input[index + separator.len()..]).ok()?;
+            Some((rest, (first, second)))
+        })
+    };
+
+    if let Some(split) = splits().find(|(rest, _)| rest.is_empty()) {
+        return Ok(split);
+    }
+
+    if let Some(split) = splits().next() {
+        return Ok(split);
+    }
+
+    Err(nom::Err::Error(nom::error::Error::new(
+        input,
+        nom::error::ErrorKind::SeparatedList,
+    )))
+}
+
+// The subject ends wherever the remainder reads as a predicate, e.g. "Jan 15, 2022" in
+// "is Jan 15, 2022 before 3 weeks from now".
+fn parse_is(input: &str) -> IResult<&str, DateMath> {
+    map(
+        preceded(tag("is "), |i| parse_split(i, " ", predicate::parse)),
+        |(subject, predicate)| DateMath::Is(Box::new(subject), Box::new(predicate)),
+    )(input)
+}
+
+fn parse_conversion(input: &str) -> IResult<&str, Conversion> {
+    alt((
+        value(Conversion::WeeksAndDays, tag("weeks and days")),
+        value(Conversion::YearsAndMonths, tag("years and months")),
+        value(Conversion::Days, tag("days")),
+        value(Conversion::Weeks, tag("weeks")),
+        value(Conversion::Months, tag("months")),
+        value(Conversion::Years, tag("years")),
+    ))(input)
+}
+
+fn parse_diff_format(input: &str) -> IResult<&str, DiffFormat> {
+    alt((
+        value(DiffFormat::WeeksAndDays, tag("weeks and days")),
+        value(DiffFormat::Months, tag("whole months")),
+        value(DiffFormat::Months, tag("months")),
+    ))(input)
+}
+
+// Expressions starting with a date share that prefix, so it's parsed once and the
+// remainder decides between periods, a difference, or the date on its own.
+fn parse_from_date(input: &str) -> IResult<&str, DateMath> {
+    let (input, date) = calculated_date::parse(input)?;
+    let (rest, operations) = period_operation::parse_list(input)?;
+    let mut operations = operations.into_iter();
+
+    if let Some(first) = operations.next() {
+        return Ok((
+            rest,
+            DateMath::StartWithPeriods(date, first, operations.collect()),
+        ));
+    }
+
+    match preceded(delimited(space0, tag("-"), space0), calculated_date::parse)(input) {
+        Ok((input, to)) => Ok((input, DateMath::DateDiff(date, to))),
+        Err(_) => Ok((input, DateMath::Start(date))),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use smallvec::smallvec;
 
     #[test]
     fn test_smoke() {
@@ -141,6 +1220,22 @@ mod tests {
             "2 weeks and 1 day before January 15",
             "2 weeks and 1 day from January 15",
             "2 weeks and 1 day after January 15",
+            "next '0 9 * * MON'",
+            "next 3 '0 9 * * MON' after January 15, 2022",
+            "next 15th",
+            "last 1st - 2 days",
+            "2 weeks after next 31st",
+            "next anniversary of 1990-05-04",
+            "age for 1990-05-04",
+            "age on Jan 1, 2030 for May 4, 1990",
+            "end of month",
+            "first business day after July 4, 2025",
+            "last business day before end of month",
+            "T+2 from 2024-03-01",
+            "T+1 + 1 week",
+            "weekday of 2 weeks from now",
+            "compare 2 weeks from now vs next 15th",
+            "earlier of 2 weeks and 1 day ago and yesterday",
         ];
 
         assert!(examples
@@ -153,7 +1248,7 @@ mod tests {
     fn test_parse() {
         assert_eq!(
             parse("1 day + 2 months").unwrap().1,
-            DateMath::Periods(Period::Day(1), vec![PeriodOp::Add(Period::Month(2))])
+            DateMath::Periods(Period::Day(1), smallvec![PeriodOp::Add(Period::Month(2))])
         );
 
         assert_eq!(
@@ -161,7 +1256,7 @@ mod tests {
             DateMath::StartWithPeriods(
                 CalculatedDate::Raw(NaiveDate::from_ymd_opt(2021, 1, 2).unwrap()),
                 PeriodOp::Add(Period::Week(15)),
-                vec![]
+                smallvec![]
             )
         );
 
@@ -170,7 +1265,7 @@ mod tests {
             DateMath::StartWithPeriods(
                 CalculatedDate::Today,
                 PeriodOp::Subtract(Period::Week(2)),
-                vec![PeriodOp::Subtract(Period::Day(1))]
+                smallvec![PeriodOp::Subtract(Period::Day(1))]
             )
         );
 
@@ -179,7 +1274,7 @@ mod tests {
             DateMath::StartWithPeriods(
                 CalculatedDate::Raw(NaiveDate::from_ymd_opt(2021, 3, 31).unwrap()),
                 PeriodOp::Add(Period::Week(15)),
-                vec![PeriodOp::Add(Period::Day(2))]
+                smallvec![PeriodOp::Add(Period::Day(2))]
             )
         );
 
@@ -188,7 +1283,7 @@ mod tests {
             DateMath::StartWithPeriods(
                 CalculatedDate::Raw(NaiveDate::from_ymd_opt(2021, 3, 31).unwrap()),
                 PeriodOp::Subtract(Period::Week(15)),
-                vec![PeriodOp::Add(Period::Day(2))]
+                smallvec![PeriodOp::Add(Period::Day(2))]
             )
         );
 
@@ -213,12 +1308,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_date_math_diff_in_weeks_and_days() {
+        assert_eq!(
+            parse("Mar 31, 2021 - Mar 6, 2021 in weeks and days")
+                .unwrap()
+                .1
+                .compute(date(2022, 1, 31))
+                .to_string(),
+            "3 weeks and 4 days"
+        );
+    }
+
     #[test]
     fn test_date_math_start_with_periods() {
         let result = DateMath::StartWithPeriods(
             CalculatedDate::Raw(NaiveDate::from_ymd_opt(2021, 3, 31).unwrap()),
             PeriodOp::Add(Period::Week(15)),
-            vec![
+            smallvec![
                 PeriodOp::Add(Period::Day(2)),
                 PeriodOp::Subtract(Period::Day(1)),
             ],
@@ -275,14 +1382,1014 @@ mod tests {
         assert_eq!("7 days", result.to_string());
     }
 
-    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
-        NaiveDate::from_ymd_opt(year, month, day).unwrap()
-    }
+    #[test]
+    fn test_date_math_schedule() {
+        let result = parse("next 2 '0 9 * * MON' after Jan 1, 2022")
+            .unwrap()
+            .1
+            .compute(date(2022, 1, 31));
 
-    fn is_parse_success(result: &ParseResult) -> bool {
-        match result {
-            ParseResult::Success(_) => true,
-            _ => false,
+        assert_eq!("2022-01-03 09:00\n2022-01-10 09:00", result.to_string());
+
+        for (input, error) in &[
+            ("next 0 '* * * * *'", ComputeError::ScheduleCount),
+            ("next 4294967295 '0 0 31 2 *'", ComputeError::ScheduleCount),
+            ("next '0 0 31 2 *'", ComputeError::ScheduleExhausted),
+            (
+                "next 2 '0 0 1 1 *' after 9999-06-01",
+                ComputeError::OutOfRange,
+            ),
+        ] {
+            assert_eq!(
+                compute(input, date(2022, 1, 31), &Config::default()),
+                Err(*error),
+                "{}",
+                input
+            );
         }
     }
+
+    #[test]
+    fn test_date_math_schedule_from_now() {
+        let config = Config::default().time(chrono::NaiveTime::from_hms(10, 30, 0));
+        let math = parse("next 2 '0 9,12 * * *'").unwrap().1;
+
+        assert_eq!(
+            "2022-01-31 12:00\n2022-02-01 09:00",
+            math.compute_with_config(date(2022, 1, 31), &config)
+                .to_string()
+        );
+        assert_eq!(
+            "2022-01-31 09:00\n2022-01-31 12:00",
+            math.compute(date(2022, 1, 31)).to_string()
+        );
+    }
+
+    #[test]
+    fn test_date_math_month_days() {
+        let next = parse("next of the 1st and 15th").unwrap().1;
+        let list = parse("list the 1st and 15th for the next 2 months")
+            .unwrap()
+            .1;
+
+        assert_eq!("2022-02-01", next.compute(date(2022, 1, 31)).to_string());
+        assert_eq!(
+            "2022-02-01\n2022-02-15\n2022-03-01\n2022-03-15",
+            list.compute(date(2022, 1, 31)).to_string()
+        );
+    }
+
+    #[test]
+    fn test_date_math_age() {
+        let result = parse("age on 2030-01-01 for 1990-05-04")
+            .unwrap()
+            .1
+            .compute(date(2022, 1, 31));
+
+        assert_eq!("39 years", result.to_string());
+
+        let result = parse("age for 2021-01-31")
+            .unwrap()
+            .1
+            .compute(date(2022, 1, 31));
+
+        assert_eq!("1 year", result.to_string());
+    }
+
+    #[test]
+    fn test_date_math_weekday_of() {
+        let result = parse("weekday of Jan 31, 2022 + 2 weeks + 3 days")
+            .unwrap()
+            .1
+            .compute(date(2022, 1, 31));
+
+        assert_eq!(result, ComputeOutcome::Weekday(Weekday::Thu));
+        assert_eq!("Thursday", result.to_string());
+    }
+
+    #[test]
+    fn test_date_math_compute_each() {
+        let results = parse("today + 30 days")
+            .unwrap()
+            .1
+            .compute_each(&[date(2022, 1, 1), date(2022, 3, 15)], &Config::default());
+
+        assert_eq!(
+            results,
+            vec![
+                ComputeOutcome::Date(date(2022, 1, 31)),
+                ComputeOutcome::Date(date(2022, 4, 14))
+            ]
+        );
+
+        let results = parse("2 weeks")
+            .unwrap()
+            .1
+            .compute_each(&[date(2022, 1, 1)], &Config::default());
+
+        assert_eq!(results, vec![ComputeOutcome::Date(date(2022, 1, 15))]);
+    }
+
+    #[test]
+    fn test_date_math_date_diff_auto() {
+        let result = DateMath::DateDiff(
+            CalculatedDate::Raw(date(2023, 5, 2)),
+            CalculatedDate::Raw(date(2022, 1, 1)),
+        )
+        .compute_with_config(
+            date(2022, 1, 31),
+            &Config::default().diff_format(DiffFormat::Auto),
+        );
+
+        assert_eq!("1 year and 4 months", result.to_string());
+        assert_eq!(
+            ComputeOutcome::Duration(vec![Period::Year(1), Period::Month(2), Period::Day(3)])
+                .to_string(),
+            "1 year, 2 months, and 3 days"
+        );
+    }
+
+    #[test]
+    fn test_date_math_compare() {
+        let result = parse("compare 2022-02-01 + 2 weeks vs 2022-02-01")
+            .unwrap()
+            .1
+            .compute(date(2022, 1, 31));
+
+        assert_eq!(
+            "2022-02-01 is 14 days earlier than 2022-02-15",
+            result.to_string()
+        );
+
+        let result = parse("earlier of 2 weeks and 1 day ago and yesterday")
+            .unwrap()
+            .1
+            .compute(date(2022, 1, 31));
+
+        assert_eq!(
+            result,
+            ComputeOutcome::Comparison(date(2022, 1, 16), date(2022, 1, 30))
+        );
+
+        let result = parse("compare today vs Jan 31, 2022")
+            .unwrap()
+            .1
+            .compute(date(2022, 1, 31));
+
+        assert_eq!("both are 2022-01-31", result.to_string());
+
+        let result = parse("compare today vs age for 2020-01-01")
+            .unwrap()
+            .1
+            .try_compute_with_config(date(2022, 1, 31), &Config::default());
+
+        assert_eq!(result, Err(ComputeError::NotADate));
+    }
+
+    #[test]
+    fn test_date_math_weekday_anchor() {
+        assert_eq!(
+            parse("friday + 1 week")
+                .unwrap()
+                .1
+                .compute(date(2022, 1, 31)),
+            ComputeOutcome::Date(date(2022, 2, 11))
+        );
+    }
+
+    #[test]
+    fn test_date_math_fractional_months() {
+        let today = date(2022, 1, 1);
+        let math = parse("1.5 months from today").unwrap().1;
+
+        assert_eq!(
+            math.try_compute_with_config(today, &Config::default()),
+            Err(ComputeError::AmbiguousFraction)
+        );
+        assert_eq!(
+            math.try_compute_with_config(
+                today,
+                &Config::default().fractional_months(FractionalMonthPolicy::ThirtyDays)
+            ),
+            Ok(ComputeOutcome::Date(date(2022, 2, 15)))
+        );
+        assert_eq!(
+            parse("today + 1.5 years").unwrap().1.compute(today),
+            ComputeOutcome::Date(date(2023, 7, 1))
+        );
+        assert_eq!(
+            parse("1.5 weeks from today").unwrap().1.compute(today),
+            ComputeOutcome::Date(date(2022, 1, 11))
+        );
+        assert_eq!(
+            parse("today + 2.5 days").unwrap().1.compute(today),
+            ComputeOutcome::DateTime(date(2022, 1, 3).and_hms(12, 0, 0))
+        );
+    }
+
+    #[test]
+    fn test_date_math_across_eras() {
+        assert_eq!(
+            parse("Jan 1, 1 AD - Dec 31, 1 BC")
+                .unwrap()
+                .1
+                .compute(date(2022, 1, 1)),
+            ComputeOutcome::DifferenceInDays(1)
+        );
+    }
+
+    #[test]
+    fn test_date_math_out_of_range() {
+        let today = date(2022, 1, 1);
+
+        assert_eq!(
+            compute("99999-01-01", today, &Config::default()),
+            Err(ComputeError::OutOfRange)
+        );
+        assert_eq!(
+            compute("9999-12-31 + 1 day", today, &Config::default()),
+            Err(ComputeError::OutOfRange)
+        );
+        assert_eq!(
+            compute("99999-01-01 - 2022-01-01", today, &Config::default()),
+            Err(ComputeError::OutOfRange)
+        );
+        assert_eq!(
+            compute("9999-12-31", today, &Config::default()),
+            Ok(ComputeOutcome::Date(date(9999, 12, 31)))
+        );
+        assert_eq!(
+            parse("friday")
+                .unwrap()
+                .1
+                .try_compute_with_config(date(262_143, 12, 31), &Config::default()),
+            Err(ComputeError::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_date_math_approximate() {
+        let today = date(2022, 1, 31);
+        let math = parse("today + 1 month + 1 year").unwrap().1;
+        let approximate = Config::default().approximate(true);
+
+        assert_eq!(math.compute(today), ComputeOutcome::Date(date(2023, 2, 28)));
+        assert_eq!(math.approximations(&Config::default()), vec![]);
+        assert_eq!(
+            math.compute_with_config(today, &approximate),
+            ComputeOutcome::Date(date(2023, 3, 2))
+        );
+        assert_eq!(
+            math.approximations(&approximate),
+            vec![
+                (Period::Month(1), Period::Day(30)),
+                (Period::Year(1), Period::Day(365))
+            ]
+        );
+        assert_eq!(
+            parse("today + 1.5 months").unwrap().1.approximations(
+                &Config::default().fractional_months(FractionalMonthPolicy::ThirtyDays)
+            ),
+            vec![(Period::FractionalMonths(1500), Period::Day(45))]
+        );
+    }
+
+    #[test]
+    fn test_date_math_vague_quantities() {
+        let today = date(2022, 1, 1);
+        let math = parse("a few weeks from today").unwrap().1;
+        let lenient = Config::default().lenient(FuzzyQuantities::default());
+
+        assert_eq!(
+            math.try_compute_with_config(today, &Config::default()),
+            Err(ComputeError::VagueQuantity)
+        );
+        assert_eq!(
+            math.try_compute_with_config(today, &lenient),
+            Ok(ComputeOutcome::Date(date(2022, 1, 22)))
+        );
+        assert_eq!(
+            math.assumptions(&lenient)
+                .iter()
+                .map(|(vague, assumed)| format!("{} = {}", vague, assumed))
+                .collect::<Vec<_>>(),
+            vec!["a few weeks = 3 weeks"]
+        );
+    }
+
+    #[test]
+    fn test_date_math_elapsed() {
+        let today = date(2022, 7, 2);
+
+        assert_eq!(
+            parse("percent of year elapsed").unwrap().1.compute(today),
+            ComputeOutcome::Percentage(100.0 * 182.0 / 365.0)
+        );
+        assert_eq!(
+            parse("percent of month elapsed at 2022-02-15")
+                .unwrap()
+                .1
+                .compute(today)
+                .to_string(),
+            "50.0%"
+        );
+    }
+
+    #[test]
+    fn test_date_math_days_left() {
+        let today = date(2022, 7, 2);
+
+        assert_eq!(
+            parse("days left in month").unwrap().1.compute(today),
+            ComputeOutcome::DifferenceInDays(29)
+        );
+        assert_eq!(
+            parse("days left in the year").unwrap().1.compute(today),
+            ComputeOutcome::DifferenceInDays(182)
+        );
+        assert_eq!(
+            parse("days left in Q3").unwrap().1.compute(today),
+            ComputeOutcome::DifferenceInDays(90)
+        );
+        assert_eq!(
+            parse("days left in Q1").unwrap().1.compute(today),
+            ComputeOutcome::DifferenceInDays(0)
+        );
+        assert_eq!(
+            parse("days left in quarter")
+                .unwrap()
+                .1
+                .compute(date(2022, 2, 1)),
+            ComputeOutcome::DifferenceInDays(58)
+        );
+    }
+
+    #[test]
+    fn test_date_math_terms() {
+        let config = Config::default()
+            .term("Fall", date(2022, 8, 29), date(2022, 12, 16))
+            .term("spring", date(2023, 1, 17), date(2023, 5, 5));
+        let today = date(2022, 7, 2);
+
+        assert_eq!(
+            compute("start of fall term", today, &config),
+            Ok(ComputeOutcome::Date(date(2022, 8, 29)))
+        );
+        assert_eq!(
+            compute("end of the Spring term", today, &config),
+            Ok(ComputeOutcome::Date(date(2023, 5, 5)))
+        );
+        assert_eq!(
+            compute("3 weeks into spring term", today, &config),
+            Ok(ComputeOutcome::Date(date(2023, 2, 7)))
+        );
+        assert_eq!(
+            compute(
+                "weekdays between today and start of fall term",
+                today,
+                &config
+            ),
+            Ok(ComputeOutcome::DifferenceInDays(40))
+        );
+        assert_eq!(
+            compute("is today before start of summer term", today, &config),
+            Err(ComputeError::UnknownTerm)
+        );
+    }
+
+    #[test]
+    fn test_date_math_billing_dates() {
+        let config = Config::default().billing_day(31);
+
+        assert_eq!(
+            compute("next billing date", date(2022, 1, 31), &config),
+            Ok(ComputeOutcome::Date(date(2022, 2, 28)))
+        );
+        assert_eq!(
+            compute("billing date in 2 cycles", date(2022, 1, 31), &config),
+            Ok(ComputeOutcome::Date(date(2022, 3, 31)))
+        );
+        assert_eq!(
+            compute("billing date in 1 cycle", date(2024, 2, 28), &config),
+            Ok(ComputeOutcome::Date(date(2024, 2, 29)))
+        );
+        assert_eq!(
+            compute("billing date in 3 cycles", date(2022, 2, 28), &config),
+            Ok(ComputeOutcome::Date(date(2022, 5, 31)))
+        );
+        assert_eq!(
+            parse("next billing date")
+                .unwrap()
+                .1
+                .try_compute_with_config(date(2022, 1, 1), &Config::default()),
+            Err(ComputeError::NoBillingDay)
+        );
+        assert!(all_consuming(parse)("billing date in 0 cycles").is_err());
+    }
+
+    #[test]
+    fn test_date_math_jql() {
+        let today = date(2022, 3, 31);
+        let compute = |input| parse(input).unwrap().1.compute(today);
+
+        assert_eq!(
+            compute("startOfWeek(-1) + 1 day"),
+            ComputeOutcome::Date(date(2022, 3, 22))
+        );
+        assert_eq!(
+            compute("endOfMonth() - -7d"),
+            ComputeOutcome::DifferenceInDays(7)
+        );
+        assert_eq!(
+            compute("is 2022-03-30 between -7d and endOfYear()"),
+            ComputeOutcome::Boolean(true)
+        );
+    }
+
+    #[test]
+    fn test_date_math_convert() {
+        let today = date(2022, 3, 31);
+        let config = Config::default();
+
+        assert_eq!(
+            compute("3 weeks in days", today, &config),
+            Ok(ComputeOutcome::Duration(vec![Period::Day(21)]))
+        );
+        assert_eq!(
+            compute("18 months in years and months", today, &config),
+            Ok(ComputeOutcome::Duration(vec![
+                Period::Year(1),
+                Period::Month(6)
+            ]))
+        );
+        assert_eq!(
+            compute("1 year - 2 months in months", today, &config),
+            Ok(ComputeOutcome::Duration(vec![Period::Month(10)]))
+        );
+        assert_eq!(
+            compute("2 months in days", today, &config),
+            Err(ComputeError::NoFixedLength)
+        );
+        assert_eq!(
+            compute("2 months in days", today, &config.clone().approximate(true)),
+            Ok(ComputeOutcome::Duration(vec![Period::Day(60)]))
+        );
+        assert_eq!(
+            compute("2000000000000000000 weeks in days", today, &config),
+            Err(ComputeError::OutOfRange)
+        );
+        assert_eq!(
+            compute("18446744073709551615 years in months", today, &config),
+            Err(ComputeError::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_date_math_days_in() {
+        let today = date(2022, 3, 31);
+        let config = Config::default();
+
+        assert_eq!(
+            compute("days in 3 months from Jan 31", today, &config),
+            Ok(ComputeOutcome::DifferenceInDays(89))
+        );
+        assert_eq!(
+            compute(
+                "days in 3 months from Jan 31",
+                today,
+                &config.clone().approximate(true)
+            ),
+            Ok(ComputeOutcome::DifferenceInDays(90))
+        );
+        assert_eq!(
+            compute("days in 1 month", today, &config),
+            Ok(ComputeOutcome::DifferenceInDays(30))
+        );
+        assert_eq!(
+            compute("days in 1 year ago", today, &config),
+            Ok(ComputeOutcome::DifferenceInDays(365))
+        );
+        assert!(all_consuming(parse)("days in today").is_err());
+    }
+
+    #[test]
+    fn test_date_math_sql_interval() {
+        let compute = |input| parse(input).unwrap().1.compute(date(2022, 3, 31));
+
+        assert_eq!(
+            compute("2024-01-31 + INTERVAL '1 month'"),
+            ComputeOutcome::Date(date(2024, 2, 29))
+        );
+        assert_eq!(
+            compute("2024-01-31 + INTERVAL '2 mons 3 days' - 1 week"),
+            ComputeOutcome::Date(date(2024, 3, 27))
+        );
+        assert_eq!(
+            parse("today - interval '1 year -2 days'").unwrap().1,
+            DateMath::StartWithPeriods(
+                CalculatedDate::Today,
+                PeriodOp::Subtract(Period::Year(1)),
+                smallvec![PeriodOp::Add(Period::Day(2))]
+            )
+        );
+    }
+
+    #[test]
+    fn test_date_math_elasticsearch() {
+        let today = date(2022, 3, 31);
+
+        let at_time = Config::default().time(NaiveTime::from_hms(14, 35, 0));
+        let compute = |input, config: &Config| {
+            parse_elasticsearch(input)
+                .unwrap()
+                .try_compute_with_config(today, config)
+        };
+
+        assert_eq!(
+            parse("now+1M/M").unwrap().1.compute(today),
+            ComputeOutcome::Date(date(2022, 4, 1))
+        );
+        assert_eq!(
+            compute("now-1d/M", &Config::default()),
+            Ok(ComputeOutcome::Date(date(2022, 3, 1)))
+        );
+        assert_eq!(
+            compute("now-7d/w", &Config::default()),
+            Ok(ComputeOutcome::Date(date(2022, 3, 21)))
+        );
+        assert_eq!(
+            compute("now-6h", &at_time),
+            Ok(ComputeOutcome::DateTime(today.and_hms(8, 35, 0)))
+        );
+        assert_eq!(
+            compute("now-15h/d", &at_time),
+            Ok(ComputeOutcome::DateTime(date(2022, 3, 30).and_hms(0, 0, 0)))
+        );
+        assert_eq!(
+            parse_elasticsearch("2022-01-01||+1y-1d"),
+            Ok(parse("Jan 1, 2022 + 1 year - 1 day").unwrap().1)
+        );
+        assert_eq!(
+            parse_elasticsearch("now - 1 day"),
+            Err(ParseError { offset: 3 })
+        );
+    }
+
+    #[test]
+    fn test_date_math_clock_time() {
+        let today = date(2022, 3, 1);
+        let config = Config::default().time(NaiveTime::from_hms(23, 50, 0));
+
+        assert_eq!(
+            compute("half an hour from now", today, &config),
+            Ok(ComputeOutcome::DateTime(date(2022, 3, 2).and_hms(0, 20, 0)))
+        );
+        assert_eq!(
+            compute("2 hours ago", today, &Config::default()),
+            Ok(ComputeOutcome::DateTime(
+                date(2022, 2, 28).and_hms(22, 0, 0)
+            ))
+        );
+        assert_eq!(
+            compute("2022-03-01 + 3 days + 90 minutes", today, &config),
+            Ok(ComputeOutcome::DateTime(date(2022, 3, 4).and_hms(1, 30, 0)))
+        );
+        assert_eq!(
+            parse("today + half an hour")
+                .unwrap()
+                .1
+                .compile_with_config(&config)(today),
+            ComputeOutcome::DateTime(date(2022, 3, 2).and_hms(0, 20, 0))
+        );
+    }
+
+    #[test]
+    fn test_date_math_splunk() {
+        let today = date(2022, 3, 31);
+        let config = Config::default().time(NaiveTime::from_hms(14, 35, 0));
+
+        assert_eq!(
+            compute("-7d@d", today, &config),
+            Ok(ComputeOutcome::Date(date(2022, 3, 24)))
+        );
+        assert_eq!(
+            compute("-2h@h", today, &config),
+            Ok(ComputeOutcome::DateTime(
+                date(2022, 3, 31).and_hms(12, 0, 0)
+            ))
+        );
+        assert_eq!(
+            compute("-2h", today, &Config::default()),
+            Ok(ComputeOutcome::DateTime(
+                date(2022, 3, 30).and_hms(22, 0, 0)
+            ))
+        );
+        assert_eq!(
+            parse("-7d").unwrap().1,
+            DateMath::Start(CalculatedDate::Jql(JqlDate::Relative(PeriodOp::Subtract(
+                Period::Day(7)
+            ))))
+        );
+    }
+
+    #[test]
+    fn test_date_math_grafana() {
+        let today = date(2022, 3, 31);
+        let config = Config::default().time(NaiveTime::from_hms(14, 35, 0));
+
+        assert_eq!(
+            compute("now-6h to now with length", today, &config),
+            Ok(ComputeOutcome::List(vec![
+                ComputeOutcome::DateTime(today.and_hms(8, 35, 0)),
+                ComputeOutcome::DateTime(today.and_hms(14, 35, 0)),
+                ComputeOutcome::TimeSpan(6 * 60 * 60),
+            ]))
+        );
+        assert_eq!(
+            compute("from=now/d&to=now/d", today, &config),
+            Ok(ComputeOutcome::List(vec![
+                ComputeOutcome::DateTime(today.and_hms(0, 0, 0)),
+                ComputeOutcome::DateTime(today.and_hms(23, 59, 59)),
+            ]))
+        );
+        assert_eq!(ComputeOutcome::TimeSpan(6 * 60 * 60).to_string(), "6h");
+        assert_eq!(ComputeOutcome::TimeSpan(-93_600).to_string(), "-1d 2h");
+        assert_eq!(ComputeOutcome::TimeSpan(0).to_string(), "0s");
+    }
+
+    #[test]
+    fn test_date_math_working_time() {
+        let today = date(2022, 1, 7);
+        let config = Config::default();
+
+        assert_eq!(
+            compute("today + 3w 2d of work", today, &config),
+            Ok(ComputeOutcome::Date(date(2022, 2, 1)))
+        );
+        assert_eq!(
+            compute("1d 4h of work from today", today, &config),
+            Ok(ComputeOutcome::Date(date(2022, 1, 11)))
+        );
+        assert_eq!(
+            compute("2 business days ago", today, &config),
+            Ok(ComputeOutcome::Date(date(2022, 1, 5)))
+        );
+        assert_eq!(
+            compute(
+                "today + 1w of work",
+                today,
+                &config.clone().working_time(4, 10)
+            ),
+            Ok(ComputeOutcome::Date(date(2022, 1, 13)))
+        );
+        assert_eq!(
+            compute(
+                "today + 1d of work",
+                today,
+                &config
+                    .clone()
+                    .holidays(HolidayList::new(vec![date(2022, 1, 10)]))
+            ),
+            Ok(ComputeOutcome::Date(date(2022, 1, 11)))
+        );
+        assert_eq!(
+            compute("today + 99999999999 business days", today, &config),
+            Err(ComputeError::OutOfRange)
+        );
+        assert_eq!(
+            compute("today + 9999999999w of work", today, &config),
+            Err(ComputeError::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_date_math_cadences() {
+        let config = Config::default()
+            .cadence("Release", "every 6 weeks from 2022-01-11".parse().unwrap())
+            .cadence("sync", "last friday of every month".parse().unwrap());
+        let today = date(2022, 3, 1);
+
+        assert_eq!(
+            compute("next release", today, &config),
+            Ok(ComputeOutcome::Date(date(2022, 4, 5)))
+        );
+        assert_eq!(
+            compute("next sync - next release", today, &config),
+            Ok(ComputeOutcome::DifferenceInDays(11))
+        );
+        assert_eq!(
+            compute("next deploy", today, &config),
+            Err(ComputeError::UnknownCadence)
+        );
+        assert!(all_consuming(parse)("next 32nd").is_err());
+
+        for config in &[config.clone(), Config::default()] {
+            assert_eq!(
+                compute("next friday", today, config),
+                Ok(ComputeOutcome::Date(date(2022, 3, 4)))
+            );
+            assert_eq!(
+                compute("next wednesday + 2 days", today, config),
+                Ok(ComputeOutcome::Date(date(2022, 3, 4)))
+            );
+            assert_eq!(
+                compute("next march", today, config),
+                Ok(ComputeOutcome::Date(date(2023, 3, 1)))
+            );
+        }
+    }
+
+    #[test]
+    fn test_date_math_sprints() {
+        let config = Config::default().sprints(date(2022, 1, 3), 14);
+        let today = date(2022, 1, 31);
+
+        assert_eq!(
+            compute("sprint 7 start", today, &config),
+            Ok(ComputeOutcome::Date(date(2022, 3, 28)))
+        );
+        assert_eq!(
+            compute("sprint 1 end", today, &config),
+            Ok(ComputeOutcome::Date(date(2022, 1, 16)))
+        );
+        assert_eq!(
+            compute("2 sprints from now", today, &config),
+            Ok(ComputeOutcome::Date(date(2022, 2, 28)))
+        );
+        assert_eq!(
+            compute("sprint 3 end - sprint 2 start", today, &config),
+            Ok(ComputeOutcome::DifferenceInDays(27))
+        );
+        assert_eq!(
+            compute("1 sprint ago", today, &Config::default()),
+            Err(ComputeError::NoSprints)
+        );
+        assert_eq!(
+            compute("sprint 2 start", today, &Config::default()),
+            Err(ComputeError::NoSprints)
+        );
+        assert_eq!(
+            compute("sprint 99999999999 start", today, &config),
+            Err(ComputeError::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_date_math_cycle_remaining() {
+        let billing = Config::default().billing_day(31);
+
+        assert_eq!(
+            compute("days remaining in cycle", date(2022, 2, 10), &billing),
+            Ok(ComputeOutcome::DifferenceInDays(18))
+        );
+        assert_eq!(
+            compute("fraction of cycle remaining", date(2022, 2, 10), &billing),
+            Ok(ComputeOutcome::Percentage(18.0 / 28.0 * 100.0))
+        );
+        assert_eq!(
+            compute("days remaining in cycle", date(2022, 2, 28), &billing),
+            Ok(ComputeOutcome::DifferenceInDays(31))
+        );
+        assert_eq!(
+            compute(
+                "fraction of cycle remaining",
+                date(2022, 4, 16),
+                &Config::default()
+            ),
+            Ok(ComputeOutcome::Percentage(50.0))
+        );
+    }
+
+    #[test]
+    fn test_date_math_days_between() {
+        let today = date(2022, 7, 2);
+
+        assert_eq!(
+            parse("weekdays between today and eom")
+                .unwrap()
+                .1
+                .compute(today),
+            ComputeOutcome::DifferenceInDays(20)
+        );
+        assert_eq!(
+            parse("weekend days between today and eom")
+                .unwrap()
+                .1
+                .compute(today),
+            ComputeOutcome::DifferenceInDays(9)
+        );
+        assert_eq!(
+            parse("weekdays between 2022-07-15 and 1 week ago")
+                .unwrap()
+                .1
+                .compute(today),
+            ComputeOutcome::DifferenceInDays(14)
+        );
+    }
+
+    #[test]
+    fn test_date_math_normalize() {
+        let normalized = |input| parse(input).unwrap().1.normalize();
+
+        assert_eq!(
+            normalized("2 weeks + 3 days + 3 weeks"),
+            normalized("3 days + 5 weeks")
+        );
+        assert_eq!(
+            normalized("2 weeks + 3 days + 3 weeks"),
+            DateMath::Periods(Period::Week(5), smallvec![PeriodOp::Add(Period::Day(3))])
+        );
+        assert_eq!(
+            normalized("1 day - 3 days"),
+            DateMath::StartWithPeriods(
+                CalculatedDate::Today,
+                PeriodOp::Subtract(Period::Day(2)),
+                smallvec![]
+            )
+        );
+        assert_eq!(
+            normalized("Mar 1, 2022 + 2 days - 2 days"),
+            DateMath::Start(CalculatedDate::Raw(date(2022, 3, 1)))
+        );
+        assert_eq!(
+            normalized("is 1 week + 1 week after 2 weeks"),
+            normalized("is 2 weeks after 2 weeks")
+        );
+
+        let input = "Jan 31, 2022 + 1 day + 1 month";
+        let today = date(2022, 1, 1);
+
+        assert_ne!(
+            normalized(input),
+            normalized("Jan 31, 2022 + 1 month + 1 day")
+        );
+        assert_eq!(
+            normalized(input).compute(today),
+            parse(input).unwrap().1.compute(today)
+        );
+    }
+
+    #[test]
+    fn test_date_math_combine_like_units() {
+        let combined = |input| parse(input).unwrap().1.combine_like_units();
+
+        assert_eq!(
+            combined("2 weeks + 1 week - 3 days"),
+            DateMath::Periods(Period::Day(18), smallvec![])
+        );
+        assert_eq!(
+            combined("Mar 1, 2022 + 2 days - 2 days"),
+            DateMath::Start(CalculatedDate::Raw(date(2022, 3, 1)))
+        );
+        assert_eq!(
+            combined("compare today + 1 week + 1 week vs today"),
+            combined("compare today + 2 weeks vs today")
+        );
+
+        for input in &[
+            "Jan 31, 2022 + 1 week - 7 days + 1 month + 1 month + 2 days",
+            "2 days + 1 year - 1 day + 3 weeks",
+            "1 week - 3 days ago",
+        ] {
+            let (_, math) = parse(input).unwrap();
+
+            for today in &[date(2022, 1, 31), date(2024, 2, 29)] {
+                assert_eq!(
+                    math.clone().combine_like_units().compute(*today),
+                    math.compute(*today),
+                    "{}",
+                    input
+                );
+            }
+        }
+
+        let (_, math) = parse("today + 100000000 days - 100000000 days").unwrap();
+        assert_eq!(
+            math.combine_like_units()
+                .try_compute_with_config(date(2022, 1, 31), &Config::default()),
+            Err(ComputeError::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_date_math_is() {
+        let today = date(2022, 2, 1);
+
+        assert_eq!(
+            parse("is today after 2022-06-01").unwrap().1.compute(today),
+            ComputeOutcome::Boolean(false)
+        );
+        assert_eq!(
+            parse("is Jan 15, 2022 before 3 weeks from now")
+                .unwrap()
+                .1
+                .compute(today),
+            ComputeOutcome::Boolean(true)
+        );
+        assert_eq!(
+            parse("is 2 weeks and 1 day ago between 3 weeks ago and yesterday")
+                .unwrap()
+                .1
+                .compute(today),
+            ComputeOutcome::Boolean(true)
+        );
+        assert_eq!(ComputeOutcome::Boolean(true).to_string(), "true");
+    }
+
+    #[test]
+    fn test_compute_all() {
+        let expressions = vec![
+            parse("today + 2 weeks").unwrap().1,
+            parse("today + 300000 years").unwrap().1,
+            parse("2022-02-14 - today").unwrap().1,
+        ];
+
+        assert_eq!(
+            compute_all(&expressions, date(2022, 1, 31)),
+            vec![
+                Ok(ComputeOutcome::Date(date(2022, 2, 14))),
+                Err(ComputeError::OutOfRange),
+                Ok(ComputeOutcome::DifferenceInDays(14)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sub_parsers() {
+        assert_eq!(parse_period(" 3 weeks "), Ok(Period::Week(3)));
+        assert_eq!(
+            parse_period_op("- two days"),
+            Ok(PeriodOp::Subtract(Period::Day(2)))
+        );
+        assert_eq!(
+            parse_date("Mar 1, 2022"),
+            Ok(CalculatedDate::Raw(date(2022, 3, 1)))
+        );
+        assert_eq!(parse_period("3 weeks ago"), Err(ParseError { offset: 7 }));
+        assert_eq!(
+            parse_date("soon").unwrap_err().to_string(),
+            "unable to parse input at byte 0"
+        );
+    }
+
+    #[test]
+    fn test_expressions_as_keys() {
+        let mut cache = std::collections::HashMap::new();
+
+        for input in &[
+            "2 weeks ago",
+            "next 15th",
+            "2 weeks ago",
+            "is today before eom",
+        ] {
+            let (_, math) = parse(input).unwrap();
+            *cache.entry(math.clone()).or_insert(0) += 1;
+        }
+
+        let (_, math) = parse("2 weeks ago").unwrap();
+        assert_eq!(cache.len(), 3);
+        assert_eq!(cache[&math], 2);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        for input in &[
+            "Mar 31, 2021 - Mar 24, 2021 in weeks and days",
+            "next 3 '0 9 * * MON' after January 15, 2022",
+            "is tomorrow between today and 1.5 years from now",
+            "list the 1st and 15th for the next 2 months",
+        ] {
+            let (_, math) = parse(input).unwrap();
+            let json = serde_json::to_string(&math).unwrap();
+
+            assert_eq!(serde_json::from_str::<DateMath>(&json).unwrap(), math);
+        }
+
+        let outcome = ComputeOutcome::Duration(vec![Period::Week(2), Period::Day(1)]);
+        let json = serde_json::to_string(&outcome).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ComputeOutcome>(&json).unwrap(),
+            outcome
+        );
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn compute(
+        input: &str,
+        today: NaiveDate,
+        config: &Config,
+    ) -> Result<ComputeOutcome, ComputeError> {
+        parse(input)
+            .unwrap()
+            .1
+            .try_compute_with_config(today, config)
+    }
+
+    fn is_parse_success(result: &ParseResult) -> bool {
+        matches!(result, ParseResult::Success(_))
+    }
 }
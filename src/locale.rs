@@ -0,0 +1,179 @@
+//! Number words and units in languages other than English, each behind its own feature:
+//! `locale-es`, `locale-fr`, and `locale-de`. Words from every enabled language are
+//! accepted together, since none of them clash.
+
+use crate::period::Unit;
+use nom::{
+    error::{Error, ErrorKind},
+    Err, IResult,
+};
+
+pub(crate) struct Locale {
+    numbers: &'static [(&'static str, usize)],
+    units: &'static [(&'static str, Unit)],
+}
+
+#[cfg(feature = "locale-es")]
+const SPANISH: Locale = Locale {
+    numbers: &[
+        ("uno", 1),
+        ("una", 1),
+        ("un", 1),
+        ("dos", 2),
+        ("tres", 3),
+        ("cuatro", 4),
+        ("cinco", 5),
+        ("seis", 6),
+        ("siete", 7),
+        ("ocho", 8),
+        ("nueve", 9),
+        ("diez", 10),
+        ("once", 11),
+        ("doce", 12),
+    ],
+    units: &[
+        ("días", Unit::Day),
+        ("dias", Unit::Day),
+        ("día", Unit::Day),
+        ("dia", Unit::Day),
+        ("semanas", Unit::Week),
+        ("semana", Unit::Week),
+        ("meses", Unit::Month),
+        ("mes", Unit::Month),
+        ("años", Unit::Year),
+        ("año", Unit::Year),
+    ],
+};
+
+#[cfg(feature = "locale-fr")]
+const FRENCH: Locale = Locale {
+    numbers: &[
+        ("une", 1),
+        ("un", 1),
+        ("deux", 2),
+        ("trois", 3),
+        ("quatre", 4),
+        ("cinq", 5),
+        ("six", 6),
+        ("sept", 7),
+        ("huit", 8),
+        ("neuf", 9),
+        ("dix", 10),
+        ("onze", 11),
+        ("douze", 12),
+    ],
+    units: &[
+        ("jours", Unit::Day),
+        ("jour", Unit::Day),
+        ("semaines", Unit::Week),
+        ("semaine", Unit::Week),
+        ("mois", Unit::Month),
+        ("années", Unit::Year),
+        ("année", Unit::Year),
+        ("ans", Unit::Year),
+        ("an", Unit::Year),
+    ],
+};
+
+#[cfg(feature = "locale-de")]
+const GERMAN: Locale = Locale {
+    numbers: &[
+        ("eins", 1),
+        ("einen", 1),
+        ("eine", 1),
+        ("ein", 1),
+        ("zwei", 2),
+        ("drei", 3),
+        ("vier", 4),
+        ("fünf", 5),
+        ("sechs", 6),
+        ("sieben", 7),
+        ("acht", 8),
+        ("neun", 9),
+        ("zehn", 10),
+        ("elf", 11),
+        ("zwölf", 12),
+    ],
+    units: &[
+        ("tagen", Unit::Day),
+        ("tage", Unit::Day),
+        ("tag", Unit::Day),
+        ("wochen", Unit::Week),
+        ("woche", Unit::Week),
+        ("monaten", Unit::Month),
+        ("monate", Unit::Month),
+        ("monat", Unit::Month),
+        ("jahren", Unit::Year),
+        ("jahre", Unit::Year),
+        ("jahr", Unit::Year),
+    ],
+};
+
+const LOCALES: &[Locale] = &[
+    #[cfg(feature = "locale-es")]
+    SPANISH,
+    #[cfg(feature = "locale-fr")]
+    FRENCH,
+    #[cfg(feature = "locale-de")]
+    GERMAN,
+];
+
+pub(crate) fn parse_number(input: &str) -> IResult<&str, usize> {
+    longest_match(input, LOCALES.iter().flat_map(|locale| locale.numbers))
+}
+
+pub(crate) fn parse_unit(input: &str) -> IResult<&str, Unit> {
+    longest_match(input, LOCALES.iter().flat_map(|locale| locale.units))
+}
+
+// The longest word `input` starts with, ignoring case, so "unos" isn't read as "un" and
+// German nouns can be capitalized.
+fn longest_match<'a, 'w, T: Copy + 'w>(
+    input: &'a str,
+    words: impl Iterator<Item = &'w (&'static str, T)>,
+) -> IResult<&'a str, T> {
+    words
+        .filter(|(word, _)| {
+            input
+                .get(..word.len())
+                .is_some_and(|prefix| prefix.to_lowercase() == *word)
+        })
+        .max_by_key(|(word, _)| word.len())
+        .map(|(word, value)| (&input[word.len()..], *value))
+        .ok_or_else(|| Err::Error(Error::new(input, ErrorKind::Tag)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::period::{parse, Period};
+
+    #[test]
+    #[cfg(feature = "locale-es")]
+    fn test_spanish() {
+        assert_eq!(parse("dos semanas").unwrap().1, Period::Week(2));
+        assert_eq!(parse("un año").unwrap().1, Period::Year(1));
+        assert_eq!(parse("3 meses").unwrap().1, Period::Month(3));
+    }
+
+    #[test]
+    #[cfg(feature = "locale-fr")]
+    fn test_french() {
+        assert_eq!(parse("trois jours").unwrap().1, Period::Day(3));
+        assert_eq!(parse("une semaine").unwrap().1, Period::Week(1));
+        assert_eq!(parse("deux ans").unwrap().1, Period::Year(2));
+    }
+
+    #[test]
+    #[cfg(feature = "locale-de")]
+    fn test_german() {
+        assert_eq!(parse("zwei Wochen").unwrap().1, Period::Week(2));
+        assert_eq!(parse("fünf Tage").unwrap().1, Period::Day(5));
+        assert_eq!(parse("einen Monat").unwrap().1, Period::Month(1));
+    }
+
+    #[test]
+    fn test_english_is_unaffected() {
+        assert_eq!(parse("two weeks").unwrap().1, Period::Week(2));
+        assert_eq!(parse("six days").unwrap().1, Period::Day(6));
+    }
+}
@@ -0,0 +1,207 @@
+//! Holiday calendars of financial markets, so business-day and settlement math lines up
+//! with a specific exchange. The rules are those in force in recent years; one-off
+//! closures, like national days of mourning, aren't included.
+
+use crate::{date_utils, holidays::HolidayCalendar};
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MarketCalendar {
+    /// The euro area's TARGET2 payment system
+    Target2,
+    /// The New York Stock Exchange
+    Nyse,
+    /// The London Stock Exchange, which follows bank holidays in England
+    Lse,
+}
+
+impl FromStr for MarketCalendar {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "target2" => Ok(MarketCalendar::Target2),
+            "nyse" => Ok(MarketCalendar::Nyse),
+            "lse" => Ok(MarketCalendar::Lse),
+            _ => Err(format!("unknown market calendar: '{}'", value)),
+        }
+    }
+}
+
+impl HolidayCalendar for MarketCalendar {
+    fn is_holiday(&self, date: NaiveDate) -> bool {
+        let holidays = match self {
+            MarketCalendar::Target2 => target2(date.year()),
+            MarketCalendar::Nyse => nyse(date.year()),
+            MarketCalendar::Lse => lse(date.year()),
+        };
+
+        holidays.contains(&date)
+    }
+}
+
+fn target2(year: i32) -> Vec<NaiveDate> {
+    let easter = easter(year);
+
+    vec![
+        NaiveDate::from_ymd(year, 1, 1),
+        easter - Duration::days(2),
+        easter + Duration::days(1),
+        NaiveDate::from_ymd(year, 5, 1),
+        NaiveDate::from_ymd(year, 12, 25),
+        NaiveDate::from_ymd(year, 12, 26),
+    ]
+}
+
+fn nyse(year: i32) -> Vec<NaiveDate> {
+    let mut holidays = vec![
+        nth_weekday(year, 1, Weekday::Mon, 3),
+        nth_weekday(year, 2, Weekday::Mon, 3),
+        easter(year) - Duration::days(2),
+        last_weekday(year, 5, Weekday::Mon),
+        observed(NaiveDate::from_ymd(year, 7, 4)),
+        nth_weekday(year, 9, Weekday::Mon, 1),
+        nth_weekday(year, 11, Weekday::Thu, 4),
+        observed(NaiveDate::from_ymd(year, 12, 25)),
+    ];
+
+    // New Year's Day on a Saturday isn't made up on the Friday before, which would fall
+    // in the previous year.
+    let new_year = NaiveDate::from_ymd(year, 1, 1);
+    if new_year.weekday() != Weekday::Sat {
+        holidays.push(observed(new_year));
+    }
+
+    if year >= 2022 {
+        holidays.push(observed(NaiveDate::from_ymd(year, 6, 19)));
+    }
+
+    holidays
+}
+
+fn lse(year: i32) -> Vec<NaiveDate> {
+    let easter = easter(year);
+    let christmas = NaiveDate::from_ymd(year, 12, 25);
+    // Christmas and Boxing Day falling on a weekend are both made up on the following
+    // weekdays.
+    let (christmas, boxing_day) = match christmas.weekday() {
+        Weekday::Fri => (christmas, christmas + Duration::days(3)),
+        Weekday::Sat => (christmas + Duration::days(2), christmas + Duration::days(3)),
+        Weekday::Sun => (christmas + Duration::days(2), christmas + Duration::days(1)),
+        _ => (christmas, christmas + Duration::days(1)),
+    };
+
+    vec![
+        next_weekday_if_weekend(NaiveDate::from_ymd(year, 1, 1)),
+        easter - Duration::days(2),
+        easter + Duration::days(1),
+        nth_weekday(year, 5, Weekday::Mon, 1),
+        last_weekday(year, 5, Weekday::Mon),
+        last_weekday(year, 8, Weekday::Mon),
+        christmas,
+        boxing_day,
+    ]
+}
+
+// A Saturday holiday is observed the Friday before, and a Sunday one the Monday after.
+fn observed(date: NaiveDate) -> NaiveDate {
+    match date.weekday() {
+        Weekday::Sat => date - Duration::days(1),
+        Weekday::Sun => date + Duration::days(1),
+        _ => date,
+    }
+}
+
+// A weekend holiday is made up on the following Monday.
+fn next_weekday_if_weekend(date: NaiveDate) -> NaiveDate {
+    match date.weekday() {
+        Weekday::Sat | Weekday::Sun => date_utils::upcoming_weekday(date, Weekday::Mon),
+        _ => date,
+    }
+}
+
+fn nth_weekday(year: i32, month: u32, weekday: Weekday, n: i64) -> NaiveDate {
+    date_utils::upcoming_weekday(NaiveDate::from_ymd(year, month, 1), weekday)
+        + Duration::weeks(n - 1)
+}
+
+fn last_weekday(year: i32, month: u32, weekday: Weekday) -> NaiveDate {
+    let last = NaiveDate::from_ymd(year, month, date_utils::days_in_month(year, month));
+
+    date_utils::upcoming_weekday(last - Duration::days(6), weekday)
+}
+
+// Easter Sunday in the Gregorian calendar, by the anonymous algorithm.
+fn easter(year: i32) -> NaiveDate {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+
+    NaiveDate::from_ymd(year, month as u32, day as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd(year, month, day)
+    }
+
+    #[test]
+    fn test_easter() {
+        assert_eq!(easter(2022), date(2022, 4, 17));
+        assert_eq!(easter(2024), date(2024, 3, 31));
+        assert_eq!(easter(2025), date(2025, 4, 20));
+    }
+
+    #[test]
+    fn test_nyse() {
+        let nyse = MarketCalendar::Nyse;
+
+        assert!(nyse.is_holiday(date(2025, 1, 20)));
+        assert!(nyse.is_holiday(date(2025, 4, 18)));
+        assert!(nyse.is_holiday(date(2025, 11, 27)));
+        assert!(nyse.is_holiday(date(2021, 12, 24)));
+        assert!(nyse.is_holiday(date(2022, 6, 20)));
+        assert!(!nyse.is_holiday(date(2021, 12, 31)));
+        assert!(!nyse.is_holiday(date(2025, 12, 26)));
+    }
+
+    #[test]
+    fn test_target2() {
+        let target2 = MarketCalendar::Target2;
+
+        assert!(target2.is_holiday(date(2025, 4, 21)));
+        assert!(target2.is_holiday(date(2025, 5, 1)));
+        assert!(target2.is_holiday(date(2025, 12, 26)));
+        assert!(!target2.is_holiday(date(2025, 1, 20)));
+    }
+
+    #[test]
+    fn test_lse() {
+        let lse = MarketCalendar::Lse;
+
+        assert!(lse.is_holiday(date(2022, 1, 3)));
+        assert!(lse.is_holiday(date(2025, 5, 5)));
+        assert!(lse.is_holiday(date(2025, 8, 25)));
+        // Christmas 2022 fell on a Sunday
+        assert!(lse.is_holiday(date(2022, 12, 26)));
+        assert!(lse.is_holiday(date(2022, 12, 27)));
+        // Christmas 2021 fell on a Saturday
+        assert!(lse.is_holiday(date(2021, 12, 27)));
+        assert!(lse.is_holiday(date(2021, 12, 28)));
+    }
+}
@@ -0,0 +1,193 @@
+//! Days that recur every month, like a payroll paid on the 1st and 15th.
+
+#[cfg(feature = "cli")]
+use crate::syntax::Form;
+use crate::{date_utils, parser_utils::*, Config};
+use chrono::{Datelike, NaiveDate};
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    combinator::{map, opt},
+    multi::separated_list1,
+    sequence::{delimited, preceded, tuple},
+    IResult,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MonthDays(Vec<u32>);
+
+impl MonthDays {
+    pub fn new(days: Vec<u32>) -> Self {
+        MonthDays(days)
+    }
+
+    pub fn days(&self) -> &[u32] {
+        &self.0
+    }
+
+    /// The first of the days after `date`, or `None` when there are no days or the next
+    /// one is past the supported years.
+    pub fn next_after(&self, date: NaiveDate, config: &Config) -> Option<NaiveDate> {
+        self.within(date, 12, config)?.into_iter().next()
+    }
+
+    /// Every one of the days after `date`, up to and including the same day `months`
+    /// months later, or `None` when that's past the supported years.
+    pub fn within(&self, date: NaiveDate, months: u32, config: &Config) -> Option<Vec<NaiveDate>> {
+        monthly(date, months, |year, month| {
+            self.0.iter().filter_map(move |day| {
+                date_utils::day_of_month(year, month, *day, config.short_months)
+            })
+        })
+    }
+}
+
+/// The dates `days_in` picks out of each month, after `date` and up to and including the
+/// same day `months` months later, or `None` when that's past the supported years. Monthly
+/// cadences are found this way too.
+pub(crate) fn monthly<I>(
+    date: NaiveDate,
+    months: u32,
+    days_in: impl Fn(i32, u32) -> I,
+) -> Option<Vec<NaiveDate>>
+where
+    I: IntoIterator<Item = NaiveDate>,
+{
+    let end = date_utils::add_months(date, months.into())
+        .filter(|end| date_utils::in_supported_range(*end))?;
+
+    let mut dates: Vec<NaiveDate> = (0..=months as i32)
+        .flat_map(|offset| {
+            let (year, month) = date_utils::shift_month(date.year(), date.month(), offset);
+
+            days_in(year, month)
+        })
+        .filter(|day| *day > date && *day <= end)
+        .collect();
+
+    // Clamping can land two days, like the 30th and 31st, on the same date.
+    dates.sort();
+    dates.dedup();
+    Some(dates)
+}
+
+/// Several days of the month at once.
+#[cfg(feature = "cli")]
+pub(crate) const SYNTAX: &[Form] = &[
+    Form {
+        pattern: "next of the <day>[, <day>] and <day>",
+        description: "the next of several days recurring every month",
+        examples: &["next of the 1st and 15th"],
+    },
+    Form {
+        pattern: "list the <day>[, <day>] and <day> for the next <count> months",
+        description: "every one of several days of the month over the coming months",
+        examples: &["list the 1st and 15th for the next 6 months"],
+    },
+];
+
+/// Parses "next of the 1st and 15th" and "list the 1st and 15th for the next 6 months",
+/// with the number of months when listing.
+pub(crate) fn parse(input: &str) -> IResult<&str, (MonthDays, Option<u32>)> {
+    alt((
+        map(preceded(tag("next of "), parse_days), |days| (days, None)),
+        map(
+            tuple((
+                preceded(tag("list "), parse_days),
+                delimited(
+                    tag(" for the next "),
+                    opt(parse_digits),
+                    alt((tag(" months"), tag(" month"), tag("month"))),
+                ),
+            )),
+            |(days, months)| (days, Some(months.unwrap_or(1))),
+        ),
+    ))(input)
+}
+
+fn parse_days(input: &str) -> IResult<&str, MonthDays> {
+    map(
+        preceded(
+            tag("the "),
+            separated_list1(
+                alt((tag(", and "), tag(", "), tag(" and "))),
+                parse_day_ordinal,
+            ),
+        ),
+        MonthDays,
+    )(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ShortMonthPolicy;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse("next of the 1st and 15th"),
+            Ok(("", (MonthDays(vec![1, 15]), None)))
+        );
+        assert_eq!(
+            parse("list the 1st, 10th, and 20th for the next 6 months"),
+            Ok(("", (MonthDays(vec![1, 10, 20]), Some(6))))
+        );
+        assert_eq!(
+            parse("list the 15th for the next month"),
+            Ok(("", (MonthDays(vec![15]), Some(1))))
+        );
+    }
+
+    #[test]
+    fn test_next_after() {
+        let days = MonthDays(vec![1, 15]);
+        let config = Config::default();
+
+        assert_eq!(
+            days.next_after(NaiveDate::from_ymd(2022, 1, 10), &config),
+            Some(NaiveDate::from_ymd(2022, 1, 15))
+        );
+        assert_eq!(
+            days.next_after(NaiveDate::from_ymd(2022, 12, 15), &config),
+            Some(NaiveDate::from_ymd(2023, 1, 1))
+        );
+        assert_eq!(
+            MonthDays::new(vec![]).next_after(NaiveDate::from_ymd(2022, 1, 10), &config),
+            None
+        );
+    }
+
+    #[test]
+    fn test_within() {
+        let days = MonthDays(vec![15, 31]);
+        let dates = |config| days.within(NaiveDate::from_ymd(2022, 1, 20), 2, &config);
+
+        assert_eq!(
+            dates(Config::default()),
+            Some(vec![
+                NaiveDate::from_ymd(2022, 1, 31),
+                NaiveDate::from_ymd(2022, 2, 15),
+                NaiveDate::from_ymd(2022, 3, 15),
+            ])
+        );
+        assert_eq!(
+            dates(Config::default().short_months(ShortMonthPolicy::Clamp)),
+            Some(vec![
+                NaiveDate::from_ymd(2022, 1, 31),
+                NaiveDate::from_ymd(2022, 2, 15),
+                NaiveDate::from_ymd(2022, 2, 28),
+                NaiveDate::from_ymd(2022, 3, 15),
+            ])
+        );
+        assert_eq!(
+            days.within(
+                NaiveDate::from_ymd(2022, 1, 20),
+                4_000_000_000,
+                &Config::default()
+            ),
+            None
+        );
+    }
+}
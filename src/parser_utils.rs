@@ -1,9 +1,22 @@
 use nom::{
+    branch::alt,
+    bytes::complete::tag,
     character::complete::digit1,
-    combinator::{map_res, recognize},
+    combinator::{map_res, recognize, verify},
+    sequence::terminated,
     IResult,
 };
 
 pub(crate) fn parse_digits<T: std::str::FromStr>(input: &str) -> IResult<&str, T> {
     map_res(recognize(digit1), str::parse)(input)
 }
+
+pub(crate) fn parse_day_ordinal(input: &str) -> IResult<&str, u32> {
+    verify(
+        terminated(
+            parse_digits,
+            alt((tag("st"), tag("nd"), tag("rd"), tag("th"))),
+        ),
+        |day| (1..=31).contains(day),
+    )(input)
+}
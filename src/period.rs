@@ -1,52 +1,490 @@
-use crate::parser_utils::*;
-use chrono::Duration;
+#[cfg(feature = "cli")]
+use crate::syntax::Form;
+use crate::{
+    config::{Config, FractionalMonthPolicy},
+    locale,
+    parser_utils::*,
+    ComputeError,
+};
 use nom::{
     branch::alt,
     bytes::complete::tag,
-    character::complete::space1,
-    combinator::{map_res, opt, value},
-    sequence::{pair, terminated},
+    character::complete::{digit1, one_of, satisfy, space1},
+    combinator::{map, map_opt, not, opt, value},
+    multi::separated_list1,
+    sequence::{pair, preceded, separated_pair, terminated},
     IResult,
 };
+use std::convert::TryFrom;
+
+pub(crate) const MINUTES_PER_DAY: usize = 24 * 60;
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Period {
     Day(usize),
     Week(usize),
     Month(usize),
     Year(usize),
+    /// Iterations of the configured sprint length
+    Sprint(usize),
+    /// Days counted on business days only, skipping weekends and holidays
+    BusinessDay(usize),
+    /// Working time in the style of issue trackers, e.g. "3w 2d of work", counted in business
+    /// days
+    Work(WorkDuration),
+    /// Thousandths of a month, e.g. 1500 for "1.5 months"
+    FractionalMonths(usize),
+    /// A vague number of units, e.g. "a few weeks"
+    Approximately(Quantity, Unit),
+    /// Minutes of clock time, e.g. 30 for "half an hour", which move a date and time
+    /// rather than a date
+    Minute(usize),
+}
+
+/// How far a period moves a date, mirroring chrono's `Days` and `Months`: a number of
+/// days, or a number of calendar months or years that keeps the day of the month.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Step {
+    Days(usize),
+    Months(usize),
+    Years(usize),
+    BusinessDays(usize),
+}
+
+impl Step {
+    /// The same step with months as 30 days and years as 365.
+    pub fn approximate(self) -> Step {
+        match self {
+            Step::Days(days) => Step::Days(days),
+            Step::Months(months) => Step::Days(months.saturating_mul(30)),
+            Step::Years(years) => Step::Days(years.saturating_mul(365)),
+            Step::BusinessDays(days) => Step::BusinessDays(days),
+        }
+    }
+}
+
+/// Weeks, days, hours, and minutes of working time, e.g. "3w 2d 4h". How many business
+/// days they come to depends on the working week, five eight-hour days unless configured.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WorkDuration {
+    weeks: u32,
+    days: u32,
+    hours: u32,
+    minutes: u32,
+}
+
+impl WorkDuration {
+    pub fn new(weeks: u32, days: u32, hours: u32, minutes: u32) -> Self {
+        WorkDuration {
+            weeks,
+            days,
+            hours,
+            minutes,
+        }
+    }
+
+    /// The business days the work takes, counting a partly worked day as a whole one.
+    pub fn business_days(self, days_per_week: usize, hours_per_day: usize) -> usize {
+        let minutes_per_day = hours_per_day.max(1).saturating_mul(60);
+        let minutes = (self.weeks as usize)
+            .saturating_mul(days_per_week)
+            .saturating_add(self.days as usize)
+            .saturating_mul(minutes_per_day)
+            .saturating_add((self.hours as usize).saturating_mul(60))
+            .saturating_add(self.minutes as usize);
+
+        minutes.div_ceil(minutes_per_day)
+    }
+}
+
+impl std::fmt::Display for WorkDuration {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let parts: Vec<String> = [
+            (self.weeks, "w"),
+            (self.days, "d"),
+            (self.hours, "h"),
+            (self.minutes, "m"),
+        ]
+        .iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, unit)| format!("{}{}", count, unit))
+        .collect();
+
+        if parts.is_empty() {
+            write!(f, "0d")
+        } else {
+            write!(f, "{}", parts.join(" "))
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Quantity {
+    Couple,
+    Few,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Unit {
+    Day,
+    Week,
+    Month,
+    Year,
+    Sprint,
+    BusinessDay,
+    Hour,
+    Minute,
+}
+
+impl Unit {
+    pub fn name(self) -> &'static str {
+        match self {
+            Unit::Day => "day",
+            Unit::Week => "week",
+            Unit::Month => "month",
+            Unit::Year => "year",
+            Unit::Sprint => "sprint",
+            Unit::BusinessDay => "business day",
+            Unit::Hour => "hour",
+            Unit::Minute => "minute",
+        }
+    }
+
+    pub(crate) fn of(self, value: usize) -> Period {
+        match self {
+            Unit::Day => Period::Day(value),
+            Unit::Week => Period::Week(value),
+            Unit::Month => Period::Month(value),
+            Unit::Year => Period::Year(value),
+            Unit::Sprint => Period::Sprint(value),
+            Unit::BusinessDay => Period::BusinessDay(value),
+            Unit::Hour => Period::Minute(value.saturating_mul(60)),
+            Unit::Minute => Period::Minute(value),
+        }
+    }
 }
 
 impl Period {
-    pub fn to_duration(&self) -> Duration {
+    pub fn days(count: usize) -> Period {
+        Period::Day(count)
+    }
+
+    pub fn weeks(count: usize) -> Period {
+        Period::Week(count)
+    }
+
+    pub fn months(count: usize) -> Period {
+        Period::Month(count)
+    }
+
+    pub fn years(count: usize) -> Period {
+        Period::Year(count)
+    }
+
+    /// The unit counted; fractional months count months, and clock time minutes.
+    pub fn unit(self) -> Unit {
         match self {
-            Period::Day(v) => Duration::days(*v as i64),
-            Period::Week(v) => Duration::weeks(*v as i64),
-            Period::Month(v) => Duration::days(30 * *v as i64),
-            Period::Year(v) => Duration::days(365 * *v as i64),
+            Period::Day(_) => Unit::Day,
+            Period::Week(_) => Unit::Week,
+            Period::Month(_) | Period::FractionalMonths(_) => Unit::Month,
+            Period::Year(_) => Unit::Year,
+            Period::Sprint(_) => Unit::Sprint,
+            Period::BusinessDay(_) | Period::Work(_) => Unit::BusinessDay,
+            Period::Approximately(_, unit) => unit,
+            Period::Minute(_) => Unit::Minute,
         }
     }
+
+    /// Resolves periods whose length depends on configuration, failing when the
+    /// configuration doesn't allow them.
+    pub(crate) fn resolve(self, config: &Config) -> Result<Period, ComputeError> {
+        match (self, config.fractional_months, config.fuzzy) {
+            (Period::FractionalMonths(_), FractionalMonthPolicy::Reject, _) => {
+                Err(ComputeError::AmbiguousFraction)
+            }
+            (Period::Work(work), _, _) => Ok(Period::BusinessDay(
+                work.business_days(config.work_week_days, config.work_day_hours),
+            )),
+            (Period::Sprint(count), _, _) => match config.sprints {
+                Some((_, days)) => Ok(Period::Day(count.saturating_mul(days))),
+                None => Err(ComputeError::NoSprints),
+            },
+            (Period::Approximately(quantity, unit), _, Some(fuzzy)) => {
+                unit.of(fuzzy.count(quantity)).resolve(config)
+            }
+            (Period::Approximately(_, _), _, None) => Err(ComputeError::VagueQuantity),
+            (period, _, _) => Ok(period),
+        }
+    }
+
+    /// The number of units, in thousandths for fractional months and minutes for clock
+    /// time, or `None` for vague quantities and working time.
+    pub fn count(self) -> Option<usize> {
+        match self {
+            Period::Day(v)
+            | Period::Week(v)
+            | Period::Month(v)
+            | Period::Year(v)
+            | Period::Sprint(v)
+            | Period::BusinessDay(v)
+            | Period::FractionalMonths(v)
+            | Period::Minute(v) => Some(v),
+            Period::Approximately(_, _) | Period::Work(_) => None,
+        }
+    }
+
+    /// How the period moves a date under `config`, once it's resolved; approximate
+    /// configs step months and years by fixed lengths. Fractional months have no
+    /// calendar meaning, so they're always 30-day months. A date has no time of day, so
+    /// clock time only steps it by whole days.
+    pub fn to_step(self, config: &Config) -> Result<Step, ComputeError> {
+        let step = match self.resolve(config)? {
+            Period::Day(v) => Step::Days(v),
+            Period::Week(v) => Step::Days(v.saturating_mul(7)),
+            Period::Month(v) => Step::Months(v),
+            Period::Year(v) => Step::Years(v),
+            Period::BusinessDay(v) => Step::BusinessDays(v),
+            Period::FractionalMonths(v) => {
+                Step::Days(v.saturating_mul(30).saturating_add(500) / 1000)
+            }
+            Period::Minute(v) => Step::Days(v / MINUTES_PER_DAY),
+            // Resolving turns these into one of the periods above.
+            Period::Sprint(_) | Period::Work(_) | Period::Approximately(_, _) => {
+                return Err(ComputeError::NoFixedLength)
+            }
+        };
+
+        Ok(if config.approximate {
+            step.approximate()
+        } else {
+            step
+        })
+    }
 }
 
+impl std::fmt::Display for Period {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let (value, unit) = match *self {
+            Period::Day(v) => (v, "day"),
+            Period::Week(v) => (v, "week"),
+            Period::Month(v) => (v, "month"),
+            Period::Year(v) => (v, "year"),
+            Period::Sprint(v) => (v, "sprint"),
+            Period::BusinessDay(v) => (v, "business day"),
+            Period::Minute(v) if v % 60 == 0 => (v / 60, "hour"),
+            Period::Minute(v) => (v, "minute"),
+            Period::Work(work) => return write!(f, "{} of work", work),
+            // Always written with a decimal point, so it reads back as a fraction.
+            Period::FractionalMonths(v) => {
+                let thousandths = format!("{:03}", v % 1000);
+                let thousandths = thousandths.trim_end_matches('0');
+
+                return write!(
+                    f,
+                    "{}.{} months",
+                    v / 1000,
+                    if thousandths.is_empty() {
+                        "0"
+                    } else {
+                        thousandths
+                    }
+                );
+            }
+            Period::Approximately(quantity, unit) => {
+                let quantity = match quantity {
+                    Quantity::Couple => "a couple of",
+                    Quantity::Few => "a few",
+                };
+
+                return write!(f, "{} {}s", quantity, unit.name());
+            }
+        };
+
+        if value == 1 {
+            write!(f, "1 {}", unit)
+        } else {
+            write!(f, "{} {}s", value, unit)
+        }
+    }
+}
+
+/// The periods `parse` reads.
+#[cfg(feature = "cli")]
+pub(crate) const SYNTAX: &[Form] = &[
+    Form {
+        pattern: "<count> (day|week|month|year|sprint|business day|hour|minute)[s]",
+        description: "counts may be digits or words up to twelve",
+        examples: &["3 days", "two weeks", "5 business days", "2 hours"],
+    },
+    Form {
+        pattern: "<decimal> (day|week|month|year|hour|minute)[s]",
+        description:
+            "rounded down to the next smaller unit; fractional months need --fractional-months",
+        examples: &["1.5 weeks", "2.5 days"],
+    },
+    Form {
+        pattern: "half a year | [a] quarter of a year | half an hour",
+        description: "six months, three months, or thirty minutes",
+        examples: &["half a year", "half an hour"],
+    },
+    Form {
+        pattern: "a couple [of] <unit>s | a few <unit>s",
+        description: "vague quantities, accepted with --lenient",
+        examples: &["a few weeks"],
+    },
+    Form {
+        pattern: "<count>w <count>d <count>h <count>m of work",
+        description: "working time in business days, of --days-per-week and --hours-per-day",
+        examples: &["3w 2d of work", "1d 4h of work"],
+    },
+];
+
 pub fn parse(input: &str) -> IResult<&str, Period> {
-    map_res(
+    alt((
+        parse_decimal,
+        parse_quantity,
+        parse_fraction,
+        parse_vague,
+        parse_work,
+    ))(input)
+}
+
+fn parse_unit(input: &str) -> IResult<&str, Unit> {
+    alt((
+        terminated(
+            alt((
+                value(Unit::Day, tag("day")),
+                value(Unit::Week, tag("week")),
+                value(Unit::Month, tag("month")),
+                value(Unit::Year, tag("year")),
+                value(Unit::Sprint, tag("sprint")),
+                value(Unit::BusinessDay, tag("business day")),
+                value(Unit::Hour, tag("hour")),
+                value(Unit::Minute, tag("minute")),
+            )),
+            opt(tag("s")),
+        ),
+        locale::parse_unit,
+    ))(input)
+}
+
+// Decimal quantities become whole numbers of the next smaller unit, rounded down: weeks
+// become days, years months, and days and hours clock time, so "1.5 weeks" is 10 days
+// and "2.5 days" is 2 days and 12 hours. Months vary in length, so they're kept for the
+// configured fractional month policy to resolve.
+fn parse_decimal(input: &str) -> IResult<&str, Period> {
+    map_opt(
+        pair(
+            terminated(separated_pair(parse_digits, tag("."), digit1), space1),
+            parse_unit,
+        ),
+        |(quantity, unit): ((usize, &str), Unit)| match unit {
+            Unit::Day => scaled(quantity, MINUTES_PER_DAY).map(|minutes| {
+                if minutes % MINUTES_PER_DAY == 0 {
+                    Period::Day(minutes / MINUTES_PER_DAY)
+                } else {
+                    Period::Minute(minutes)
+                }
+            }),
+            Unit::Week => scaled(quantity, 7).map(Period::Day),
+            Unit::Month => scaled(quantity, 1000).map(Period::FractionalMonths),
+            Unit::Year => scaled(quantity, 12).map(Period::Month),
+            Unit::Hour => scaled(quantity, 60).map(Period::Minute),
+            Unit::Minute => scaled(quantity, 1).map(Period::Minute),
+            Unit::Sprint | Unit::BusinessDay => None,
+        },
+    )(input)
+}
+
+// A decimal `whole.fraction` times `factor`, rounded down, or `None` when it doesn't fit.
+// Digits past the ninth are dropped, since they're worth less than one of the smaller
+// unit.
+fn scaled((whole, fraction): (usize, &str), factor: usize) -> Option<usize> {
+    let fraction = &fraction[..fraction.len().min(9)];
+    let numerator: usize = fraction.parse().ok()?;
+    let denominator = 10usize.checked_pow(u32::try_from(fraction.len()).ok()?)?;
+
+    whole
+        .checked_mul(factor)?
+        .checked_add(numerator.checked_mul(factor)? / denominator)
+}
+
+fn parse_quantity(input: &str) -> IResult<&str, Period> {
+    map(
         pair(
-            terminated(alt((parse_digits, parse_written_number)), space1),
             terminated(
-                alt((tag("day"), tag("week"), tag("month"), tag("year"))),
-                opt(tag("s")),
+                alt((parse_digits, parse_written_number, locale::parse_number)),
+                space1,
             ),
+            parse_unit,
+        ),
+        |(digit, unit)| unit.of(digit),
+    )(input)
+}
+
+fn parse_vague(input: &str) -> IResult<&str, Period> {
+    map(
+        pair(
+            alt((
+                value(Quantity::Couple, tag("a couple of ")),
+                value(Quantity::Couple, tag("a couple ")),
+                value(Quantity::Few, tag("a few ")),
+            )),
+            parse_unit,
         ),
-        |(digit, duration)| match duration {
-            "day" => Ok(Period::Day(digit)),
-            "week" => Ok(Period::Week(digit)),
-            "month" => Ok(Period::Month(digit)),
-            "year" => Ok(Period::Year(digit)),
-            _ => Err("unable to parse duration"),
+        |(quantity, unit)| Period::Approximately(quantity, unit),
+    )(input)
+}
+
+// Working time like "3w 2d 4h 30m of work", adding up units written more than once. The
+// marker keeps bare offsets like "6h" or "7d" for the dialects that read them as clock
+// time or calendar days.
+fn parse_work(input: &str) -> IResult<&str, Period> {
+    map(
+        terminated(
+            separated_list1(
+                space1,
+                terminated(
+                    pair(parse_digits, one_of("wdhm")),
+                    not(satisfy(char::is_alphanumeric)),
+                ),
+            ),
+            tag(" of work"),
+        ),
+        |parts: Vec<(usize, char)>| {
+            let mut work = WorkDuration::default();
+
+            for (count, unit) in parts {
+                let total = match unit {
+                    'w' => &mut work.weeks,
+                    'd' => &mut work.days,
+                    'h' => &mut work.hours,
+                    _ => &mut work.minutes,
+                };
+                *total = total.saturating_add(u32::try_from(count).unwrap_or(u32::MAX));
+            }
+
+            Period::Work(work)
         },
     )(input)
 }
 
+fn parse_fraction(input: &str) -> IResult<&str, Period> {
+    alt((
+        value(Period::Month(6), tag("half a year")),
+        value(Period::Minute(30), tag("half an hour")),
+        value(
+            Period::Month(3),
+            preceded(opt(tag("a ")), tag("quarter of a year")),
+        ),
+    ))(input)
+}
+
 fn parse_written_number(input: &str) -> IResult<&str, usize> {
     alt((
         value(1, tag("one")),
@@ -67,6 +505,8 @@ fn parse_written_number(input: &str) -> IResult<&str, usize> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::FuzzyQuantities;
+    use chrono::NaiveDate;
 
     #[test]
     fn test_duration() {
@@ -87,11 +527,155 @@ mod tests {
         assert_eq!(parse("300 years").unwrap().1, Period::Year(300));
     }
 
+    #[test]
+    fn test_constructors_and_accessors() {
+        assert_eq!(Period::days(3), parse("3 days").unwrap().1);
+        assert_eq!(Period::weeks(2).unit(), Unit::Week);
+        assert_eq!(Period::months(1).count(), Some(1));
+        assert_eq!(Period::years(5).unit().name(), "year");
+
+        let fractional = parse("1.5 months").unwrap().1;
+        assert_eq!(
+            (fractional.unit(), fractional.count()),
+            (Unit::Month, Some(1500))
+        );
+
+        let vague = Period::Approximately(Quantity::Few, Unit::Day);
+        assert_eq!((vague.unit(), vague.count()), (Unit::Day, None));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Period::Day(1).to_string(), "1 day");
+        assert_eq!(Period::Week(3).to_string(), "3 weeks");
+        assert_eq!(Period::Month(0).to_string(), "0 months");
+        assert_eq!(Period::Year(2).to_string(), "2 years");
+        assert_eq!(Period::FractionalMonths(2000).to_string(), "2.0 months");
+        assert_eq!(Period::FractionalMonths(1025).to_string(), "1.025 months");
+    }
+
+    #[test]
+    fn test_step() {
+        let config = Config::default();
+
+        assert_eq!(Period::Week(2).to_step(&config), Ok(Step::Days(14)));
+        assert_eq!(Period::Month(3).to_step(&config), Ok(Step::Months(3)));
+        assert_eq!(
+            Period::Year(1).to_step(&config.clone().approximate(true)),
+            Ok(Step::Days(365))
+        );
+        assert_eq!(
+            Period::Sprint(2).to_step(&config),
+            Err(ComputeError::NoSprints)
+        );
+        assert_eq!(
+            Period::Sprint(2).to_step(&config.clone().sprints(NaiveDate::from_ymd(2022, 1, 3), 10)),
+            Ok(Step::Days(20))
+        );
+        assert_eq!(
+            Period::Work(WorkDuration::new(1, 0, 0, 0)).to_step(&config.clone().working_time(4, 8)),
+            Ok(Step::BusinessDays(4))
+        );
+        assert_eq!(
+            Period::Approximately(Quantity::Few, Unit::Week).to_step(&config),
+            Err(ComputeError::VagueQuantity)
+        );
+    }
+
+    #[test]
+    fn test_work() {
+        assert_eq!(
+            parse("3w 2d 4h 30m of work").unwrap().1,
+            Period::Work(WorkDuration::new(3, 2, 4, 30))
+        );
+        assert_eq!(
+            parse("2d of work ago").unwrap(),
+            (" ago", Period::Work(WorkDuration::new(0, 2, 0, 0)))
+        );
+        assert!(parse("2x of work").is_err());
+        assert!(parse("6h").is_err());
+        assert!(parse("7d").is_err());
+        assert_eq!(
+            Period::Work(WorkDuration::new(1, 0, 0, 90)).to_string(),
+            "1w 90m of work"
+        );
+        assert_eq!(WorkDuration::new(1, 2, 4, 0).business_days(5, 8), 8);
+        assert_eq!(WorkDuration::new(0, 0, 9, 0).business_days(5, 8), 2);
+        assert_eq!(WorkDuration::new(2, 0, 0, 0).business_days(4, 10), 8);
+    }
+
     #[test]
     fn test_nonsense() {
         assert!(parse("1day").is_err());
     }
 
+    #[test]
+    fn test_decimals() {
+        assert_eq!(parse("2.0 weeks").unwrap().1, Period::Day(14));
+        assert_eq!(parse("3.0 days").unwrap().1, Period::Day(3));
+        assert_eq!(parse("1.5 years").unwrap().1, Period::Month(18));
+        assert_eq!(parse("0.25 years").unwrap().1, Period::Month(3));
+        assert_eq!(parse("1.5 weeks").unwrap().1, Period::Day(10));
+        assert_eq!(parse("2.5 days").unwrap().1, Period::Minute(60 * 60));
+        assert_eq!(parse("1.3 years").unwrap().1, Period::Month(15));
+        assert_eq!(parse("1.75 hours").unwrap().1, Period::Minute(105));
+        assert_eq!(parse("0.0000000009999 days").unwrap().1, Period::Day(0));
+        assert!(parse("99999999999999999999.5 days").is_err());
+        assert!(parse("1.5 sprints").is_err());
+        assert_eq!(
+            parse("1.5 months").unwrap().1,
+            Period::FractionalMonths(1500)
+        );
+        assert_eq!(Period::FractionalMonths(1500).to_string(), "1.5 months");
+        assert_eq!(
+            Period::FractionalMonths(1500)
+                .to_step(&Config::default().fractional_months(FractionalMonthPolicy::ThirtyDays)),
+            Ok(Step::Days(45))
+        );
+    }
+
+    #[test]
+    fn test_vague() {
+        let couple = parse("a couple of days").unwrap().1;
+        let few = parse("a few weeks").unwrap().1;
+
+        assert_eq!(couple, Period::Approximately(Quantity::Couple, Unit::Day));
+        assert_eq!(few.to_string(), "a few weeks");
+        assert_eq!(
+            few.resolve(&Config::default()),
+            Err(ComputeError::VagueQuantity)
+        );
+        assert_eq!(
+            few.resolve(&Config::default().lenient(FuzzyQuantities { couple: 2, few: 4 })),
+            Ok(Period::Week(4))
+        );
+        assert_eq!(
+            couple.resolve(&Config::default().lenient(FuzzyQuantities::default())),
+            Ok(Period::Day(2))
+        );
+    }
+
+    #[test]
+    fn test_fractions() {
+        assert_eq!(parse("half a year").unwrap().1, Period::Month(6));
+        assert_eq!(parse("a quarter of a year").unwrap().1, Period::Month(3));
+        assert_eq!(parse("quarter of a year").unwrap().1, Period::Month(3));
+        assert_eq!(parse("half an hour").unwrap().1, Period::Minute(30));
+    }
+
+    #[test]
+    fn test_clock_time() {
+        assert_eq!(parse("2 hours").unwrap().1, Period::Minute(120));
+        assert_eq!(parse("1 minute").unwrap().1, Period::Minute(1));
+        assert_eq!(Period::Minute(120).to_string(), "2 hours");
+        assert_eq!(Period::Minute(90).to_string(), "90 minutes");
+        assert_eq!(Period::Minute(30).unit(), Unit::Minute);
+        assert_eq!(
+            Period::Minute(3 * 24 * 60 + 90).to_step(&Config::default()),
+            Ok(Step::Days(3))
+        );
+    }
+
     #[test]
     fn test_spelled_numbers() {
         assert_eq!(parse("two days").unwrap().1, Period::Day(2));
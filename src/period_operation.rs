@@ -1,5 +1,5 @@
-use crate::{period, Period};
-use chrono::NaiveDate;
+use crate::{date_utils, holidays, period, period::Step, sql, ComputeError, Config, Period};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
 use nom::{
     branch::alt,
     bytes::complete::tag,
@@ -8,18 +8,201 @@ use nom::{
     sequence::{preceded, terminated},
     IResult,
 };
+use smallvec::SmallVec;
+use std::convert::TryFrom;
 
-#[derive(Debug, Eq, PartialEq)]
+/// Most expressions have only a handful of trailing operations, so they're stored inline.
+pub type PeriodOps = SmallVec<[PeriodOp; 3]>;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PeriodOp {
     Add(Period),
     Subtract(Period),
 }
 
 impl PeriodOp {
-    pub fn apply(&self, value: NaiveDate) -> NaiveDate {
+    pub fn add(period: Period) -> PeriodOp {
+        PeriodOp::Add(period)
+    }
+
+    pub fn subtract(period: Period) -> PeriodOp {
+        PeriodOp::Subtract(period)
+    }
+
+    pub fn is_subtraction(&self) -> bool {
+        matches!(self, PeriodOp::Subtract(_))
+    }
+
+    /// Whether the period is clock time, which moves a date and time rather than a date.
+    pub fn is_clock_time(&self) -> bool {
+        matches!(self.period(), Period::Minute(_))
+    }
+
+    pub fn period(&self) -> Period {
+        match self {
+            PeriodOp::Add(period) | PeriodOp::Subtract(period) => *period,
+        }
+    }
+
+    pub(crate) fn resolve(&self, config: &Config) -> Result<PeriodOp, ComputeError> {
+        Ok(match self {
+            PeriodOp::Add(period) => PeriodOp::Add(period.resolve(config)?),
+            PeriodOp::Subtract(period) => PeriodOp::Subtract(period.resolve(config)?),
+        })
+    }
+
+    pub fn apply(&self, value: NaiveDate) -> Option<NaiveDate> {
+        self.apply_with_config(value, &Config::default())
+    }
+
+    /// Months and years step through the calendar, keeping the day of the month: a day
+    /// missing from a short month becomes its last day, and February 29 lands where the
+    /// config's leap day policy says in years without one. Approximate configs use fixed
+    /// lengths instead.
+    pub fn apply_with_config(&self, value: NaiveDate, config: &Config) -> Option<NaiveDate> {
+        let (period, sign) = match self {
+            PeriodOp::Add(period) => (period, 1i64),
+            PeriodOp::Subtract(period) => (period, -1),
+        };
+
+        match period.to_step(config).ok()? {
+            Step::Days(days) => {
+                let days = i32::try_from(sign * i64::try_from(days).ok()?).ok()?;
+                NaiveDate::from_num_days_from_ce_opt(value.num_days_from_ce().checked_add(days)?)
+            }
+            Step::Months(months) => {
+                date_utils::add_months(value, sign * i64::try_from(months).ok()?)
+            }
+            Step::Years(years) => {
+                let years = i32::try_from(sign * i64::try_from(years).ok()?).ok()?;
+                date_utils::anniversary(value, value.year().checked_add(years)?, config.leap_day)
+            }
+            Step::BusinessDays(days) if sign > 0 => {
+                holidays::add_business_days(value, days, &*config.holidays)
+            }
+            Step::BusinessDays(days) => {
+                holidays::subtract_business_days(value, days, &*config.holidays)
+            }
+        }
+    }
+
+    /// Like `apply_with_config`, keeping the time of day, except that clock time moves
+    /// the time too.
+    pub fn apply_to_datetime(
+        &self,
+        value: NaiveDateTime,
+        config: &Config,
+    ) -> Option<NaiveDateTime> {
+        let moved = self
+            .apply_with_config(value.date(), config)?
+            .and_time(value.time());
+
         match self {
-            PeriodOp::Add(period) => value + period.to_duration(),
-            PeriodOp::Subtract(period) => value - period.to_duration(),
+            PeriodOp::Add(Period::Minute(minutes)) => moved.checked_add_signed(Duration::minutes(
+                (minutes % period::MINUTES_PER_DAY) as i64,
+            )),
+            PeriodOp::Subtract(Period::Minute(minutes)) => moved.checked_sub_signed(
+                Duration::minutes((minutes % period::MINUTES_PER_DAY) as i64),
+            ),
+            _ => Some(moved),
+        }
+    }
+}
+
+/// Rewrites each run of adjacent days and weeks as whole weeks followed by the days left
+/// over, so runs written differently, like "+ 3 days + 5 weeks" and "+ 2 weeks + 3 days +
+/// 3 weeks", come out the same. Everything else stays where it is, as in `combine`.
+pub fn normalize(operations: impl IntoIterator<Item = PeriodOp>) -> PeriodOps {
+    merge_day_runs(operations, |total, ops| {
+        let count = usize::try_from(total.unsigned_abs()).unwrap_or(usize::MAX);
+        let periods = [Period::Week(count / 7), Period::Day(count % 7)];
+
+        ops.extend(
+            periods
+                .iter()
+                .filter(|period| period.count() != Some(0))
+                .map(|&period| signed(total, period)),
+        );
+    })
+}
+
+/// Merges each run of adjacent days and weeks into one operation, in weeks when it
+/// divides evenly, and drops runs that cancel out. The result computes the same dates:
+/// months and years stay where they are, since stepping through them isn't additive
+/// (January 31 plus a month plus a month is March 28).
+pub fn combine(operations: impl IntoIterator<Item = PeriodOp>) -> PeriodOps {
+    merge_day_runs(operations, |total, ops| {
+        let count = usize::try_from(total.unsigned_abs()).unwrap_or(usize::MAX);
+        let period = if count % 7 == 0 {
+            Period::Week(count / 7)
+        } else {
+            Period::Day(count)
+        };
+
+        ops.push(signed(total, period));
+    })
+}
+
+/// Sums each run of adjacent days and weeks, handing every total that doesn't cancel out
+/// to `emit` in the run's place.
+fn merge_day_runs(
+    operations: impl IntoIterator<Item = PeriodOp>,
+    emit: impl Fn(i64, &mut PeriodOps),
+) -> PeriodOps {
+    let mut combined = PeriodOps::new();
+    let flush = |days: Option<i64>, combined: &mut PeriodOps| {
+        if let Some(total) = days.filter(|total| *total != 0) {
+            emit(total, combined);
+        }
+    };
+    let mut days: Option<i64> = None;
+
+    for op in operations {
+        let sign = if op.is_subtraction() { -1 } else { 1 };
+        let count = match op.period() {
+            Period::Day(count) => Some(count),
+            Period::Week(count) => count.checked_mul(7),
+            _ => {
+                flush(days.take(), &mut combined);
+                combined.push(op);
+                continue;
+            }
+        };
+        // A run only grows while its running total stays within the supported years, so
+        // merging can't hide a step that fails part way, as in "+ 100000000 days - 100000000
+        // days". Anything further starts a new run.
+        let total = count
+            .and_then(|count| i64::try_from(count).ok())
+            .and_then(|count| days.unwrap_or(0).checked_add(sign * count))
+            .filter(|total| total.abs() <= date_utils::SUPPORTED_DAYS);
+
+        match total {
+            Some(total) => days = Some(total),
+            None => {
+                flush(days.take(), &mut combined);
+                combined.push(op);
+            }
+        }
+    }
+
+    flush(days, &mut combined);
+    combined
+}
+
+fn signed(total: i64, period: Period) -> PeriodOp {
+    if total > 0 {
+        PeriodOp::Add(period)
+    } else {
+        PeriodOp::Subtract(period)
+    }
+}
+
+impl std::fmt::Display for PeriodOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PeriodOp::Add(period) => write!(f, "+ {}", period),
+            PeriodOp::Subtract(period) => write!(f, "- {}", period),
         }
     }
 }
@@ -40,9 +223,29 @@ pub fn parse(input: &str) -> IResult<&str, PeriodOp> {
     )(input)
 }
 
+/// Parses any number of trailing operations without allocating when there are none. A SQL
+/// interval, like "+ INTERVAL '2 months 3 days'", adds an operation for each of its parts.
+pub fn parse_list(mut input: &str) -> IResult<&str, PeriodOps> {
+    let mut operations = PeriodOps::new();
+
+    loop {
+        if let Ok((rest, op)) = parse(input) {
+            operations.push(op);
+            input = rest;
+        } else if let Ok((rest, interval)) = preceded(space0, sql::parse_operations)(input) {
+            operations.extend(interval);
+            input = rest;
+        } else {
+            return Ok((input, operations));
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::LeapDayPolicy;
+    use smallvec::smallvec;
 
     #[test]
     fn test_add() {
@@ -57,6 +260,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_constructors() {
+        let op = PeriodOp::subtract(Period::days(3));
+
+        assert_eq!(op, parse("- 3 days").unwrap().1);
+        assert!(op.is_subtraction());
+        assert!(!PeriodOp::add(Period::weeks(1)).is_subtraction());
+        assert_eq!(op.period(), Period::days(3));
+    }
+
     #[test]
     fn test_subtract() {
         assert_eq!(
@@ -69,4 +282,112 @@ mod tests {
             PeriodOp::Subtract(Period::Day(3))
         );
     }
+
+    #[test]
+    fn test_apply_years() {
+        let leap_day = NaiveDate::from_ymd(2024, 2, 29);
+        let march_1 = Config::default().leap_day(LeapDayPolicy::March1);
+
+        assert_eq!(
+            PeriodOp::Add(Period::Year(1)).apply(leap_day),
+            Some(NaiveDate::from_ymd(2025, 2, 28))
+        );
+        assert_eq!(
+            PeriodOp::Subtract(Period::Year(1)).apply_with_config(leap_day, &march_1),
+            Some(NaiveDate::from_ymd(2023, 3, 1))
+        );
+        assert_eq!(
+            PeriodOp::Add(Period::Year(4)).apply(leap_day),
+            Some(NaiveDate::from_ymd(2028, 2, 29))
+        );
+        assert_eq!(
+            PeriodOp::Add(Period::Year(1)).apply(NaiveDate::from_ymd(2024, 1, 1)),
+            Some(NaiveDate::from_ymd(2025, 1, 1))
+        );
+    }
+
+    #[test]
+    fn test_list() {
+        assert_eq!(parse_list("").unwrap(), ("", smallvec![]));
+        assert_eq!(
+            parse_list(" + 2 weeks - 3 days and more").unwrap(),
+            (
+                " and more",
+                smallvec![
+                    PeriodOp::Add(Period::Week(2)),
+                    PeriodOp::Subtract(Period::Day(3))
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn test_normalize() {
+        let (_, operations) = parse_list("+ 2 weeks + 3 days + 3 weeks - 3 days + 1 year").unwrap();
+
+        assert_eq!(
+            normalize(operations),
+            PeriodOps::from_vec(vec![
+                PeriodOp::Add(Period::Week(5)),
+                PeriodOp::Add(Period::Year(1)),
+            ])
+        );
+
+        let (_, operations) = parse_list("- 2 days + 1 day").unwrap();
+
+        assert_eq!(
+            normalize(operations),
+            PeriodOps::from_vec(vec![PeriodOp::Subtract(Period::Day(1))])
+        );
+
+        let (_, operations) = parse_list("- 10 days + 1 month + 1 day").unwrap();
+
+        assert_eq!(
+            normalize(operations),
+            PeriodOps::from_vec(vec![
+                PeriodOp::Subtract(Period::Week(1)),
+                PeriodOp::Subtract(Period::Day(3)),
+                PeriodOp::Add(Period::Month(1)),
+                PeriodOp::Add(Period::Day(1)),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_combine() {
+        let combined = |input| combine(parse_list(input).unwrap().1);
+
+        assert_eq!(
+            combined("+ 2 weeks + 1 week - 3 days"),
+            PeriodOps::from_vec(vec![PeriodOp::Add(Period::Day(18))])
+        );
+        assert_eq!(
+            combined("+ 3 days + 1 month + 1 week + 1 week - 2 weeks + 1 month"),
+            PeriodOps::from_vec(vec![
+                PeriodOp::Add(Period::Day(3)),
+                PeriodOp::Add(Period::Month(1)),
+                PeriodOp::Add(Period::Month(1)),
+            ])
+        );
+        assert_eq!(
+            combined("- 4 days - 3 days + 1 year"),
+            PeriodOps::from_vec(vec![
+                PeriodOp::Subtract(Period::Week(1)),
+                PeriodOp::Add(Period::Year(1)),
+            ])
+        );
+        assert_eq!(
+            combined("+ 100000000 days - 100000000 days"),
+            PeriodOps::from_vec(vec![
+                PeriodOp::Add(Period::Day(100_000_000)),
+                PeriodOp::Subtract(Period::Day(100_000_000)),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(PeriodOp::Add(Period::Week(2)).to_string(), "+ 2 weeks");
+        assert_eq!(PeriodOp::Subtract(Period::Day(1)).to_string(), "- 1 day");
+    }
 }
@@ -0,0 +1,165 @@
+#[cfg(feature = "cli")]
+use crate::syntax::Form;
+use crate::{parse_separated_pair, ComputeError, Config, DateMath};
+use chrono::NaiveDate;
+use nom::{branch::alt, bytes::complete::tag, combinator::map, sequence::preceded, IResult};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Predicate {
+    Before(DateMath),
+    OnOrBefore(DateMath),
+    On(DateMath),
+    OnOrAfter(DateMath),
+    After(DateMath),
+    Between(DateMath, DateMath),
+}
+
+impl Predicate {
+    pub fn matches(
+        &self,
+        date: NaiveDate,
+        today: NaiveDate,
+        config: &Config,
+    ) -> Result<bool, ComputeError> {
+        let bound = |math: &DateMath| math.try_compute_date(today, config);
+
+        Ok(match self {
+            Predicate::Before(math) => date < bound(math)?,
+            Predicate::OnOrBefore(math) => date <= bound(math)?,
+            Predicate::On(math) => date == bound(math)?,
+            Predicate::OnOrAfter(math) => date >= bound(math)?,
+            Predicate::After(math) => date > bound(math)?,
+            Predicate::Between(first, second) => {
+                let (first, second) = (bound(first)?, bound(second)?);
+                first.min(second) <= date && date <= first.max(second)
+            }
+        })
+    }
+
+    /// Rebuilds the predicate with each of its expressions transformed by `f`.
+    pub(crate) fn map_expressions(self, mut f: impl FnMut(DateMath) -> DateMath) -> Predicate {
+        match self {
+            Predicate::Before(math) => Predicate::Before(f(math)),
+            Predicate::OnOrBefore(math) => Predicate::OnOrBefore(f(math)),
+            Predicate::On(math) => Predicate::On(f(math)),
+            Predicate::OnOrAfter(math) => Predicate::OnOrAfter(f(math)),
+            Predicate::After(math) => Predicate::After(f(math)),
+            Predicate::Between(first, second) => Predicate::Between(f(first), f(second)),
+        }
+    }
+
+    pub(crate) fn expressions(&self) -> Vec<&DateMath> {
+        match self {
+            Predicate::Before(math)
+            | Predicate::OnOrBefore(math)
+            | Predicate::On(math)
+            | Predicate::OnOrAfter(math)
+            | Predicate::After(math) => vec![math],
+            Predicate::Between(first, second) => vec![first, second],
+        }
+    }
+}
+
+/// Comparisons, which end with one of the predicates `parse` reads.
+#[cfg(feature = "cli")]
+pub(crate) const SYNTAX: &[Form] = &[Form {
+    pattern: "is <expression> <predicate>",
+    description: "true or false, where the predicate is one of: before, after, \
+                      on, on or before, on or after <expression>, or between \
+                      <expression> and <expression>",
+    examples: &[
+        "is today after 2022-06-01",
+        "is tomorrow between today and 1 week from now",
+    ],
+}];
+
+pub fn parse(input: &str) -> IResult<&str, Predicate> {
+    alt((
+        map(
+            preceded(tag("on or before "), crate::parse),
+            Predicate::OnOrBefore,
+        ),
+        map(
+            preceded(tag("on or after "), crate::parse),
+            Predicate::OnOrAfter,
+        ),
+        map(preceded(tag("before "), crate::parse), Predicate::Before),
+        map(preceded(tag("after "), crate::parse), Predicate::After),
+        map(preceded(tag("on "), crate::parse), Predicate::On),
+        map(
+            preceded(tag("between "), |i| parse_separated_pair(i, " and ")),
+            |(first, second)| Predicate::Between(first, second),
+        ),
+    ))(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(predicate: &str, date: NaiveDate) -> bool {
+        parse(predicate)
+            .unwrap()
+            .1
+            .matches(date, NaiveDate::from_ymd(2022, 1, 31), &Config::default())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_after() {
+        assert!(matches(
+            "after 3 weeks ago",
+            NaiveDate::from_ymd(2022, 1, 11)
+        ));
+        assert!(!matches(
+            "after 3 weeks ago",
+            NaiveDate::from_ymd(2022, 1, 10)
+        ));
+        assert!(matches(
+            "on or after 3 weeks ago",
+            NaiveDate::from_ymd(2022, 1, 10)
+        ));
+    }
+
+    #[test]
+    fn test_before() {
+        assert!(matches(
+            "before yesterday",
+            NaiveDate::from_ymd(2022, 1, 29)
+        ));
+        assert!(!matches(
+            "before yesterday",
+            NaiveDate::from_ymd(2022, 1, 30)
+        ));
+        assert!(matches(
+            "on or before yesterday",
+            NaiveDate::from_ymd(2022, 1, 30)
+        ));
+    }
+
+    #[test]
+    fn test_on() {
+        assert!(matches(
+            "on today - 1 week",
+            NaiveDate::from_ymd(2022, 1, 24)
+        ));
+        assert!(!matches("on today", NaiveDate::from_ymd(2022, 1, 24)));
+    }
+
+    #[test]
+    fn test_between() {
+        assert!(matches(
+            "between 2 weeks and 1 day ago and yesterday",
+            NaiveDate::from_ymd(2022, 1, 20)
+        ));
+        assert!(matches(
+            "between tomorrow and 1 week ago",
+            NaiveDate::from_ymd(2022, 1, 24)
+        ));
+        assert!(!matches(
+            "between tomorrow and 1 week ago",
+            NaiveDate::from_ymd(2022, 1, 23)
+        ));
+    }
+}
@@ -0,0 +1,237 @@
+//! A parse that keeps going past the first problem, for editors that underline every
+//! mistake in an expression at once.
+//!
+//! Arithmetic expressions are split at their operators and each piece is parsed on its
+//! own, so one bad period doesn't hide problems in the rest. Other forms are reported
+//! where the parser stopped.
+
+use crate::{
+    calculated_date, parse, period, period_operation,
+    token::{self, TokenKind},
+    CalculatedDate, DateMath, ParseResult, Period, PeriodOps,
+};
+use nom::combinator::all_consuming;
+use std::ops::Range;
+
+/// A problem found while parsing, and where in the input it is.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The byte range of the input the problem covers
+    pub span: Range<usize>,
+    pub message: String,
+}
+
+/// The result of `parse_recovering`: as much of the expression as could be understood,
+/// and everything that couldn't.
+#[derive(Debug, PartialEq)]
+pub struct Recovered {
+    pub math: Option<DateMath>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Parses an expression, collecting every problem instead of stopping at the first. The
+/// expression is built from the pieces that parsed, so it may leave parts of the input
+/// out; it's only complete when there are no diagnostics.
+pub fn parse_recovering(input: &str) -> Recovered {
+    let stopped_at = match parse(input).into() {
+        ParseResult::Success(math) => {
+            return Recovered {
+                math: Some(math),
+                diagnostics: vec![],
+            }
+        }
+        ParseResult::PartialSuccess(_, rest) => input.len() - rest.len(),
+        ParseResult::Error(_) => 0,
+    };
+
+    let mut diagnostics = vec![];
+    let mut pieces = segments(input).into_iter();
+    let start = pieces.next().and_then(|span| {
+        let text = &input[span.clone()];
+
+        if let Ok((_, date)) = all_consuming(calculated_date::parse)(text) {
+            Some(Start::Date(date))
+        } else if let Ok((_, period)) = all_consuming(period::parse)(text) {
+            Some(Start::Period(period))
+        } else if let ParseResult::PartialSuccess(math, rest) = parse(text).into() {
+            diagnostics.push(Diagnostic {
+                span: span.end - rest.trim_start().len()..span.end,
+                message: format!("unexpected '{}'", rest.trim()),
+            });
+            Some(Start::Expression(math))
+        } else {
+            diagnose(input, span, "expected a date or a period", &mut diagnostics);
+            None
+        }
+    });
+    let operations: PeriodOps = pieces
+        .filter_map(
+            |span| match all_consuming(period_operation::parse)(&input[span.clone()]) {
+                Ok((_, operation)) => Some(operation),
+                Err(_) => {
+                    diagnose(input, span, "expected a period", &mut diagnostics);
+                    None
+                }
+            },
+        )
+        .collect();
+
+    // The pieces can each be fine while the whole isn't, e.g. with a misplaced keyword.
+    if diagnostics.is_empty() {
+        let rest = input[stopped_at..].trim();
+        let offset = input.len() - input[stopped_at..].trim_start().len();
+
+        diagnostics.push(Diagnostic {
+            span: offset..offset + rest.len(),
+            message: if rest.is_empty() {
+                "expected an expression".to_string()
+            } else {
+                format!("unexpected '{}'", rest)
+            },
+        });
+    }
+
+    let math = match (start, operations.is_empty()) {
+        (Some(Start::Date(date)), true) => Some(DateMath::Start(date)),
+        (Some(Start::Date(date)), false) => Some(from_date(date, operations)),
+        (Some(Start::Period(period)), _) => Some(DateMath::Periods(period, operations)),
+        (Some(Start::Expression(math)), _) => Some(math),
+        (None, false) => Some(from_date(CalculatedDate::Today, operations)),
+        (None, true) => None,
+    };
+
+    Recovered { math, diagnostics }
+}
+
+// How the input before the first operator was understood.
+enum Start {
+    Date(CalculatedDate),
+    Period(Period),
+    // A whole expression with something unexpected after it
+    Expression(DateMath),
+}
+
+fn from_date(date: CalculatedDate, operations: PeriodOps) -> DateMath {
+    let mut operations = operations.into_iter();
+    let first = operations.next().expect("there is at least one operation");
+
+    DateMath::StartWithPeriods(date, first, operations.collect())
+}
+
+// The input split before each "+" or "-", with surrounding whitespace trimmed.
+fn segments(input: &str) -> Vec<Range<usize>> {
+    let mut starts: Vec<usize> = token::tokenize(input)
+        .into_iter()
+        .filter(|token| token.kind == TokenKind::Operator)
+        .map(|token| token.span.start)
+        .collect();
+    starts.insert(0, 0);
+
+    starts
+        .iter()
+        .zip(starts.iter().skip(1).chain(Some(&input.len())))
+        .map(|(&start, &end)| {
+            let text = &input[start..end];
+            let leading = text.len() - text.trim_start().len();
+            start + leading..start + text.trim_end().len()
+        })
+        .filter(|span| !span.is_empty())
+        .collect()
+}
+
+// Points at each unknown word in `span` with its likely correction, or at the whole span
+// when every word is known but they don't fit together.
+fn diagnose(input: &str, span: Range<usize>, expected: &str, out: &mut Vec<Diagnostic>) {
+    let before = out.len();
+
+    for word in token::tokenize(&input[span.clone()])
+        .into_iter()
+        .filter(|token| token.kind == TokenKind::Word)
+    {
+        let message = match token::closest_known(word.text) {
+            Some(known) => format!("unknown word '{}' (did you mean '{}'?)", word.text, known),
+            None => format!("unknown word '{}'", word.text),
+        };
+
+        out.push(Diagnostic {
+            span: span.start + word.span.start..span.start + word.span.end,
+            message,
+        });
+    }
+
+    if out.len() == before {
+        out.push(Diagnostic {
+            span: span.clone(),
+            message: format!("{}, found '{}'", expected, &input[span]),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PeriodOp;
+    use smallvec::smallvec;
+
+    fn messages(input: &str) -> Vec<(String, &str)> {
+        parse_recovering(input)
+            .diagnostics
+            .into_iter()
+            .map(|diagnostic| (diagnostic.message, &input[diagnostic.span]))
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_input() {
+        assert_eq!(
+            parse_recovering("2 weeks ago"),
+            Recovered {
+                math: parse("2 weeks ago").ok().map(|(_, math)| math),
+                diagnostics: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn test_several_problems() {
+        assert_eq!(
+            messages("tomorow + 2 wekks + 3 days - 1 fortnight"),
+            vec![
+                (
+                    "unknown word 'tomorow' (did you mean 'tomorrow'?)".to_string(),
+                    "tomorow"
+                ),
+                (
+                    "unknown word 'wekks' (did you mean 'weeks'?)".to_string(),
+                    "wekks"
+                ),
+                ("unknown word 'fortnight'".to_string(), "fortnight"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_best_effort_expression() {
+        assert_eq!(
+            parse_recovering("2022-03-01 + 2 wekks + 3 days").math,
+            Some(DateMath::StartWithPeriods(
+                CalculatedDate::Raw(chrono::NaiveDate::from_ymd(2022, 3, 1)),
+                PeriodOp::Add(Period::Day(3)),
+                smallvec![],
+            ))
+        );
+        assert_eq!(parse_recovering("soonish").math, None);
+    }
+
+    #[test]
+    fn test_misplaced_words() {
+        assert_eq!(
+            messages("2 weeks ago ago"),
+            vec![("unexpected 'ago'".to_string(), "ago")]
+        );
+        assert_eq!(
+            messages("2 weeks + 3"),
+            vec![("expected a period, found '+ 3'".to_string(), "+ 3")]
+        );
+    }
+}
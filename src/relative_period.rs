@@ -1,51 +1,65 @@
-use crate::{calculated_date, period, CalculatedDate, Period, PeriodOp};
+use crate::{calculated_date, period, CalculatedDate, Edge, Period, PeriodOp, PeriodOps};
 use nom::{
     branch::alt,
     bytes::complete::tag,
     character::complete::space1,
-    combinator::map,
-    multi::many1,
+    combinator::{map, opt},
+    multi::fold_many1,
     sequence::{delimited, pair, preceded, separated_pair, terminated},
     IResult,
 };
+use smallvec::{smallvec, SmallVec};
 
-pub fn parse(input: &str) -> IResult<&str, (CalculatedDate, PeriodOp, Vec<PeriodOp>)> {
+type Builder = fn(Period) -> PeriodOp;
+type Periods = SmallVec<[Period; 3]>;
+
+pub fn parse(input: &str) -> IResult<&str, (CalculatedDate, PeriodOp, PeriodOps)> {
     let (input, (period, rest)) = parse_sentence(input)?;
+    let (input, (date, builder)) = parse_anchor(input)?;
+
+    Ok((input, build_period_op_pair(date, period, rest, builder)))
+}
 
-    let result = alt((
+fn parse_anchor(input: &str) -> IResult<&str, (CalculatedDate, Builder)> {
+    alt((
         map(tag(" ago"), |_| {
-            build_period_op_pair(
-                CalculatedDate::Today,
-                period,
-                rest.clone(),
-                PeriodOp::Subtract,
-            )
+            (CalculatedDate::Today, PeriodOp::Subtract as Builder)
         }),
         map(
             preceded(alt((tag(" from "), tag(" after "))), calculated_date::parse),
-            |date| build_period_op_pair(date, period, rest.clone(), PeriodOp::Add),
+            |date| (date, PeriodOp::Add as Builder),
         ),
         map(preceded(tag(" before "), calculated_date::parse), |date| {
-            build_period_op_pair(date, period, rest.clone(), PeriodOp::Subtract)
+            (date, PeriodOp::Subtract as Builder)
         }),
-    ))(input);
-
-    result
+        map(
+            preceded(
+                pair(tag(" into "), opt(tag("the "))),
+                calculated_date::parse_term,
+            ),
+            |name| {
+                (
+                    CalculatedDate::Term(Edge::Start, name),
+                    PeriodOp::Add as Builder,
+                )
+            },
+        ),
+    ))(input)
 }
 
 fn build_period_op_pair<F>(
     date: CalculatedDate,
     period: Period,
-    rest: Vec<Period>,
+    rest: Periods,
     builder: F,
-) -> (CalculatedDate, PeriodOp, Vec<PeriodOp>)
+) -> (CalculatedDate, PeriodOp, PeriodOps)
 where
     F: Fn(Period) -> PeriodOp,
 {
     (
         date,
         builder(period),
-        rest.into_iter().map(builder).collect::<Vec<PeriodOp>>(),
+        rest.into_iter().map(builder).collect::<PeriodOps>(),
     )
 }
 
@@ -53,15 +67,25 @@ fn period_and_comma(input: &str) -> IResult<&str, Period> {
     terminated(period::parse, tag(","))(input)
 }
 
-fn parse_sentence(input: &str) -> IResult<&str, (Period, Vec<Period>)> {
+fn parse_sentence(input: &str) -> IResult<&str, (Period, Periods)> {
     let comma_delimited = map(
         separated_pair(
-            pair(period_and_comma, many1(preceded(space1, period_and_comma))),
+            pair(
+                period_and_comma,
+                fold_many1(
+                    preceded(space1, period_and_comma),
+                    Periods::new(),
+                    |mut acc, period| {
+                        acc.push(period);
+                        acc
+                    },
+                ),
+            ),
             delimited(space1, tag("and"), space1),
             period::parse,
         ),
         |((period, mut rest), last)| {
-            rest.extend([last]);
+            rest.push(last);
             (period, rest)
         },
     );
@@ -72,10 +96,10 @@ fn parse_sentence(input: &str) -> IResult<&str, (Period, Vec<Period>)> {
             delimited(space1, tag("and"), space1),
             period::parse,
         ),
-        |(period, other)| (period, vec![other]),
+        |(period, other)| (period, smallvec![other]),
     );
 
-    let single = map(period::parse, |period| (period, vec![]));
+    let single = map(period::parse, |period| (period, Periods::new()));
 
     alt((comma_delimited, single_and, single))(input)
 }
@@ -92,7 +116,7 @@ mod tests {
             (
                 CalculatedDate::Today,
                 PeriodOp::Subtract(Period::Day(3)),
-                Vec::new()
+                PeriodOps::new()
             )
         );
 
@@ -101,7 +125,7 @@ mod tests {
             (
                 CalculatedDate::Today,
                 PeriodOp::Subtract(Period::Year(12)),
-                Vec::new()
+                PeriodOps::new()
             )
         );
     }
@@ -113,7 +137,7 @@ mod tests {
             (
                 CalculatedDate::Today,
                 PeriodOp::Subtract(Period::Day(3)),
-                vec![PeriodOp::Subtract(Period::Week(1))]
+                smallvec![PeriodOp::Subtract(Period::Week(1))]
             )
         );
     }
@@ -122,7 +146,7 @@ mod tests {
     fn test_human_sentence() {
         assert_eq!(
             parse_sentence("1 year, 2 months, and 3 days").unwrap().1,
-            (Period::Year(1), vec![Period::Month(2), Period::Day(3)])
+            (Period::Year(1), smallvec![Period::Month(2), Period::Day(3)])
         );
     }
 
@@ -133,7 +157,7 @@ mod tests {
             (
                 CalculatedDate::Today,
                 PeriodOp::Add(Period::Year(1)),
-                vec![
+                smallvec![
                     PeriodOp::Add(Period::Month(2)),
                     PeriodOp::Add(Period::Day(3))
                 ]
@@ -148,7 +172,7 @@ mod tests {
             (
                 CalculatedDate::Today,
                 PeriodOp::Add(Period::Day(3)),
-                Vec::new()
+                PeriodOps::new()
             )
         );
 
@@ -157,7 +181,7 @@ mod tests {
             (
                 CalculatedDate::Today,
                 PeriodOp::Add(Period::Week(12)),
-                Vec::new()
+                PeriodOps::new()
             )
         );
     }
@@ -169,7 +193,7 @@ mod tests {
             (
                 CalculatedDate::Tomorrow,
                 PeriodOp::Add(Period::Week(2)),
-                vec![PeriodOp::Add(Period::Day(3))]
+                smallvec![PeriodOp::Add(Period::Day(3))]
             )
         );
 
@@ -178,7 +202,7 @@ mod tests {
             (
                 CalculatedDate::Tomorrow,
                 PeriodOp::Add(Period::Week(2)),
-                vec![PeriodOp::Add(Period::Day(3))]
+                smallvec![PeriodOp::Add(Period::Day(3))]
             )
         );
 
@@ -187,7 +211,7 @@ mod tests {
             (
                 CalculatedDate::Today,
                 PeriodOp::Add(Period::Week(2)),
-                vec![PeriodOp::Add(Period::Day(3))]
+                smallvec![PeriodOp::Add(Period::Day(3))]
             )
         );
 
@@ -196,7 +220,7 @@ mod tests {
             (
                 CalculatedDate::Yesterday,
                 PeriodOp::Subtract(Period::Week(2)),
-                vec![PeriodOp::Subtract(Period::Day(3))]
+                smallvec![PeriodOp::Subtract(Period::Day(3))]
             )
         );
 
@@ -205,7 +229,7 @@ mod tests {
             (
                 CalculatedDate::Raw(NaiveDate::from_ymd(2022, 7, 11)),
                 PeriodOp::Subtract(Period::Week(2)),
-                vec![PeriodOp::Subtract(Period::Day(3))]
+                smallvec![PeriodOp::Subtract(Period::Day(3))]
             )
         );
     }
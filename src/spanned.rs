@@ -0,0 +1,251 @@
+//! Source locations for parsed expressions, for editors and linters that highlight which
+//! part of the input produced which part of an expression.
+//!
+//! Locations are found after parsing: the whole expression covers what `parse` consumed,
+//! and each node below it is matched back against the input by re-running its parser
+//! within the range of its parent, from where the previous node ended, in the order
+//! `Visit` walks them.
+
+use crate::{
+    calculated_date, period, period_operation,
+    visit::{self, Visit},
+    CalculatedDate, DateMath, PeriodOp,
+};
+use nom::{combinator::all_consuming, IResult};
+use std::ops::Range;
+
+/// A node of a parsed expression and the byte range of the input it was parsed from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Spanned {
+    pub kind: NodeKind,
+    pub span: Range<usize>,
+    /// Nested nodes, in the order `Visit` reaches them.
+    pub children: Vec<Spanned>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeKind {
+    Expression,
+    Date,
+    Operation,
+}
+
+/// Parses an expression like `parse`, alongside the location of each of its nodes.
+pub fn parse_spanned(input: &str) -> IResult<&str, (DateMath, Spanned)> {
+    let (rest, math) = crate::parse(input)?;
+    let parsed = &input[..input.len() - rest.len()];
+    let start = parsed.len() - parsed.trim_start().len();
+    let span = start..parsed.trim_end().len();
+    let mut locator = Locator {
+        input,
+        bounds: span.clone(),
+        cursor: start,
+        nodes: vec![],
+    };
+    let root = locator.node(NodeKind::Expression, span, |locator| {
+        visit::walk_date_math(locator, &math)
+    });
+
+    Ok((rest, (math, root)))
+}
+
+struct Locator<'a> {
+    input: &'a str,
+    bounds: Range<usize>,
+    cursor: usize,
+    nodes: Vec<Spanned>,
+}
+
+impl<'a> Locator<'a> {
+    // The first range, from the cursor onward, that `matches` accepts. Whitespace around
+    // a match belongs to neither side, so it's trimmed.
+    fn find(&self, matches: impl Fn(&'a str) -> Option<usize>) -> Option<Range<usize>> {
+        let candidates = self.input[self.cursor..self.bounds.end]
+            .char_indices()
+            .filter(|(_, c)| !c.is_whitespace())
+            .map(|(index, _)| self.cursor + index);
+
+        for start in candidates {
+            if let Some(length) = matches(&self.input[start..self.bounds.end]) {
+                let matched = &self.input[start..start + length];
+                return Some(start..start + matched.trim_end().len());
+            }
+        }
+
+        None
+    }
+
+    fn enter(&mut self, kind: NodeKind, span: Range<usize>, walk: impl FnOnce(&mut Self)) {
+        let node = self.node(kind, span, walk);
+        self.nodes.push(node);
+    }
+
+    // The node covering `span`, with the nodes `walk` finds within it as its children.
+    fn node(
+        &mut self,
+        kind: NodeKind,
+        span: Range<usize>,
+        walk: impl FnOnce(&mut Self),
+    ) -> Spanned {
+        let bounds = std::mem::replace(&mut self.bounds, span.clone());
+        let siblings = std::mem::take(&mut self.nodes);
+        self.cursor = span.start;
+
+        walk(self);
+
+        let children = std::mem::replace(&mut self.nodes, siblings);
+        self.bounds = bounds;
+        self.cursor = span.end;
+
+        Spanned {
+            kind,
+            span,
+            children,
+        }
+    }
+}
+
+fn consumed<O: PartialEq>(
+    parser: fn(&str) -> IResult<&str, O>,
+    expected: &O,
+) -> impl Fn(&str) -> Option<usize> + '_ {
+    move |input| match parser(input) {
+        Ok((rest, output)) if output == *expected => Some(input.len() - rest.len()),
+        _ => None,
+    }
+}
+
+impl<'a> Visit for Locator<'a> {
+    // A nested expression usually runs to the end of what parsing from its start consumes.
+    // When it's followed by more of its parent instead, like the first date of "compare
+    // <date> vs <date>", it ends before one of the parent's separators, which all start
+    // with whitespace, so only those ends are tried, shortest first.
+    fn visit_date_math(&mut self, math: &DateMath) {
+        let greedy = consumed(crate::parse, math);
+        let span = self.find(|input| {
+            greedy(input).or_else(|| {
+                input
+                    .char_indices()
+                    .filter(|(_, c)| c.is_whitespace())
+                    .map(|(index, _)| index)
+                    .find(|end| {
+                        matches!(
+                            all_consuming(crate::parse)(&input[..*end]),
+                            Ok((_, ref parsed)) if parsed == math
+                        )
+                    })
+            })
+        });
+
+        if let Some(span) = span {
+            self.enter(NodeKind::Expression, span, |locator| {
+                visit::walk_date_math(locator, math)
+            });
+        }
+    }
+
+    fn visit_calculated_date(&mut self, date: &CalculatedDate) {
+        if let Some(span) = self.find(consumed(calculated_date::parse, date)) {
+            self.enter(NodeKind::Date, span, |locator| {
+                visit::walk_calculated_date(locator, date)
+            });
+        }
+    }
+
+    fn visit_period_op(&mut self, op: &PeriodOp) {
+        let period = op.period();
+        let operation = consumed(period_operation::parse, op);
+        let leading = consumed(period::parse, &period);
+        let span = self.find(|input| match op {
+            PeriodOp::Add(_) => operation(input).or_else(|| leading(input)),
+            PeriodOp::Subtract(_) => operation(input),
+        });
+
+        if let Some(span) = span {
+            self.enter(NodeKind::Operation, span, |_| {});
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Each node as its kind and source text, depth first.
+    fn located(input: &str) -> Vec<(NodeKind, &str)> {
+        fn flatten<'a>(input: &'a str, node: &Spanned, out: &mut Vec<(NodeKind, &'a str)>) {
+            out.push((node.kind, &input[node.span.clone()]));
+            node.children
+                .iter()
+                .for_each(|child| flatten(input, child, out));
+        }
+
+        let (_, (_, root)) = parse_spanned(input).unwrap();
+        let mut out = vec![];
+        flatten(input, &root, &mut out);
+        out
+    }
+
+    #[test]
+    fn test_periods() {
+        assert_eq!(
+            located("2 weeks + 3 days  - 1 day"),
+            vec![
+                (NodeKind::Expression, "2 weeks + 3 days  - 1 day"),
+                (NodeKind::Operation, "2 weeks"),
+                (NodeKind::Operation, "+ 3 days"),
+                (NodeKind::Operation, "- 1 day"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dates() {
+        assert_eq!(
+            located("Mar 31, 2021 - first business day after today"),
+            vec![
+                (
+                    NodeKind::Expression,
+                    "Mar 31, 2021 - first business day after today"
+                ),
+                (NodeKind::Date, "Mar 31, 2021"),
+                (NodeKind::Date, "first business day after today"),
+                (NodeKind::Date, "today"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_separated_expressions() {
+        assert_eq!(
+            located("compare Jan 1, 2022 vs 3 days ago"),
+            vec![
+                (NodeKind::Expression, "compare Jan 1, 2022 vs 3 days ago"),
+                (NodeKind::Expression, "Jan 1, 2022"),
+                (NodeKind::Date, "Jan 1, 2022"),
+                (NodeKind::Expression, "3 days ago"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nested_expressions() {
+        let (_, (_, root)) = parse_spanned("is Jan 15, 2022 before 3 weeks from now").unwrap();
+
+        assert_eq!(root.span, 0..39);
+        assert_eq!(
+            root.children
+                .iter()
+                .map(|child| (child.kind, child.span.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (NodeKind::Expression, 3..15),
+                (NodeKind::Expression, 23..39)
+            ]
+        );
+        assert_eq!(
+            located("is Jan 15, 2022 before 3 weeks from now")[2],
+            (NodeKind::Date, "Jan 15, 2022")
+        );
+    }
+}
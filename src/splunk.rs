@@ -0,0 +1,300 @@
+//! Splunk's relative time modifiers, like "-7d@d" or "@mon+3d", so the window a search
+//! covers can be checked against the reference date and time.
+
+#[cfg(feature = "cli")]
+use crate::syntax::Form;
+use crate::{date_utils, parse_whole, parser_utils::*, ParseError};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Timelike, Weekday};
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{char, one_of, satisfy},
+    combinator::{map, map_opt, not, opt, value},
+    multi::many1,
+    sequence::{preceded, terminated, tuple},
+    IResult,
+};
+use std::str::FromStr;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SplunkTime(pub(crate) Vec<Modifier>);
+
+/// One step of a relative time, applied from left to right.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) enum Modifier {
+    /// Units added, or subtracted when negative, e.g. "-7d"
+    Offset(i64, TimeUnit),
+    /// Back to the start of the unit, e.g. "@d"; weeks start on Sunday, as in Splunk
+    Snap(TimeUnit),
+    /// Back to the latest such weekday, e.g. "@w1" for Monday
+    SnapToWeekday(Weekday),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) enum TimeUnit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+impl SplunkTime {
+    /// Whether the time works in hours, minutes, or seconds, so the result needs a time of
+    /// day rather than just a date.
+    pub fn uses_time(&self) -> bool {
+        self.0.iter().any(|modifier| match modifier {
+            Modifier::Offset(_, unit) | Modifier::Snap(unit) => {
+                matches!(unit, TimeUnit::Second | TimeUnit::Minute | TimeUnit::Hour)
+            }
+            Modifier::SnapToWeekday(_) => false,
+        })
+    }
+
+    /// The time relative to `now`, or `None` past the supported years.
+    pub fn calculate(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        self.0.iter().try_fold(now, |now, modifier| {
+            Some(modifier.apply(now)?).filter(|now| date_utils::in_supported_range(now.date()))
+        })
+    }
+}
+
+impl FromStr for SplunkTime {
+    type Err = ParseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        parse_whole(value, parse)
+    }
+}
+
+impl Modifier {
+    pub(crate) fn apply(self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let midnight = |date: NaiveDate| date.and_hms(0, 0, 0);
+
+        match self {
+            Modifier::Offset(count, TimeUnit::Second) => {
+                now.checked_add_signed(Duration::seconds(count))
+            }
+            Modifier::Offset(count, TimeUnit::Minute) => {
+                now.checked_add_signed(Duration::minutes(count))
+            }
+            Modifier::Offset(count, TimeUnit::Hour) => {
+                now.checked_add_signed(Duration::hours(count))
+            }
+            Modifier::Offset(count, TimeUnit::Day) => now.checked_add_signed(Duration::days(count)),
+            Modifier::Offset(count, TimeUnit::Week) => {
+                now.checked_add_signed(Duration::weeks(count))
+            }
+            Modifier::Offset(count, TimeUnit::Month) => {
+                Some(date_utils::add_months(now.date(), count)?.and_time(now.time()))
+            }
+            Modifier::Offset(count, TimeUnit::Quarter) => {
+                Some(date_utils::add_months(now.date(), count * 3)?.and_time(now.time()))
+            }
+            Modifier::Offset(count, TimeUnit::Year) => {
+                Some(date_utils::add_months(now.date(), count * 12)?.and_time(now.time()))
+            }
+            Modifier::Snap(TimeUnit::Second) => now.with_nanosecond(0),
+            Modifier::Snap(TimeUnit::Minute) => {
+                Some(now.date().and_hms(now.hour(), now.minute(), 0))
+            }
+            Modifier::Snap(TimeUnit::Hour) => Some(now.date().and_hms(now.hour(), 0, 0)),
+            Modifier::Snap(TimeUnit::Day) => Some(midnight(now.date())),
+            Modifier::Snap(TimeUnit::Week) => Modifier::SnapToWeekday(Weekday::Sun).apply(now),
+            Modifier::Snap(TimeUnit::Month) => Some(midnight(now.date().with_day(1)?)),
+            Modifier::Snap(TimeUnit::Quarter) => {
+                let month = (now.month() - 1) / 3 * 3 + 1;
+
+                Some(midnight(NaiveDate::from_ymd(now.year(), month, 1)))
+            }
+            Modifier::Snap(TimeUnit::Year) => Some(midnight(NaiveDate::from_ymd(now.year(), 1, 1))),
+            Modifier::SnapToWeekday(weekday) => {
+                Some(midnight(date_utils::start_of_week(now.date(), weekday)))
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for SplunkTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for modifier in &self.0 {
+            match modifier {
+                Modifier::Offset(count, unit) => write!(f, "{:+}{}", count, unit_name(*unit))?,
+                Modifier::Snap(unit) => write!(f, "@{}", unit_name(*unit))?,
+                Modifier::SnapToWeekday(weekday) => {
+                    write!(f, "@w{}", weekday.num_days_from_sunday())?
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn unit_name(unit: TimeUnit) -> &'static str {
+    match unit {
+        TimeUnit::Second => "s",
+        TimeUnit::Minute => "m",
+        TimeUnit::Hour => "h",
+        TimeUnit::Day => "d",
+        TimeUnit::Week => "w",
+        TimeUnit::Month => "mon",
+        TimeUnit::Quarter => "q",
+        TimeUnit::Year => "y",
+    }
+}
+
+/// Splunk's relative time modifiers.
+#[cfg(feature = "cli")]
+pub(crate) const SYNTAX: &[Form] = &[Form {
+    pattern: "[(+|-)[<count>]<unit>]...[@<unit>[(+|-)[<count>]<unit>]...]",
+    description: "Splunk relative time, with a time of day for hours, minutes, or \
+                      seconds",
+    examples: &["-7d@d", "@mon+3d", "-24h"],
+}];
+
+/// Parses a run of offsets and snaps, like "-7d@d" or "@w1+8h". A missing count is 1, as
+/// in "-mon".
+pub(crate) fn parse(input: &str) -> IResult<&str, SplunkTime> {
+    map(
+        many1(alt((
+            map(
+                tuple((one_of("+-"), opt(parse_digits::<u32>), parse_unit)),
+                |(sign, count, unit)| {
+                    let count = i64::from(count.unwrap_or(1));
+
+                    Modifier::Offset(if sign == '-' { -count } else { count }, unit)
+                },
+            ),
+            preceded(
+                char('@'),
+                alt((
+                    map_opt(
+                        terminated(
+                            preceded(char('w'), one_of("01234567")),
+                            not(satisfy(char::is_alphanumeric)),
+                        ),
+                        |day| {
+                            let weekday = (0..day.to_digit(10)? % 7)
+                                .fold(Weekday::Sun, |weekday, _| weekday.succ());
+
+                            Some(Modifier::SnapToWeekday(weekday))
+                        },
+                    ),
+                    map(parse_unit, Modifier::Snap),
+                )),
+            ),
+        ))),
+        SplunkTime,
+    )(input)
+}
+
+fn parse_unit(input: &str) -> IResult<&str, TimeUnit> {
+    terminated(
+        alt((
+            alt((
+                value(TimeUnit::Second, tag("seconds")),
+                value(TimeUnit::Second, tag("second")),
+                value(TimeUnit::Second, tag("secs")),
+                value(TimeUnit::Second, tag("sec")),
+                value(TimeUnit::Month, tag("months")),
+                value(TimeUnit::Month, tag("month")),
+                value(TimeUnit::Month, tag("mon")),
+                value(TimeUnit::Minute, tag("minutes")),
+                value(TimeUnit::Minute, tag("minute")),
+                value(TimeUnit::Minute, tag("mins")),
+                value(TimeUnit::Minute, tag("min")),
+                value(TimeUnit::Second, tag("s")),
+                value(TimeUnit::Minute, tag("m")),
+            )),
+            alt((
+                value(TimeUnit::Hour, tag("hours")),
+                value(TimeUnit::Hour, tag("hour")),
+                value(TimeUnit::Hour, tag("hrs")),
+                value(TimeUnit::Hour, tag("hr")),
+                value(TimeUnit::Hour, tag("h")),
+                value(TimeUnit::Day, tag("days")),
+                value(TimeUnit::Day, tag("day")),
+                value(TimeUnit::Day, tag("d")),
+                value(TimeUnit::Week, tag("weeks")),
+                value(TimeUnit::Week, tag("week")),
+                value(TimeUnit::Week, tag("w")),
+            )),
+            alt((
+                value(TimeUnit::Quarter, tag("quarters")),
+                value(TimeUnit::Quarter, tag("quarter")),
+                value(TimeUnit::Quarter, tag("qtrs")),
+                value(TimeUnit::Quarter, tag("qtr")),
+                value(TimeUnit::Quarter, tag("q")),
+                value(TimeUnit::Year, tag("years")),
+                value(TimeUnit::Year, tag("year")),
+                value(TimeUnit::Year, tag("yrs")),
+                value(TimeUnit::Year, tag("yr")),
+                value(TimeUnit::Year, tag("y")),
+            )),
+        )),
+        not(satisfy(char::is_alphanumeric)),
+    )(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calculate(input: &str) -> Option<NaiveDateTime> {
+        let (rest, time) = parse(input).unwrap();
+        assert_eq!(rest, "");
+
+        // A Thursday
+        time.calculate(NaiveDate::from_ymd(2022, 3, 31).and_hms(14, 35, 20))
+    }
+
+    #[test]
+    fn test_offsets_and_snaps() {
+        let at = |y, m, d, h, min| Some(NaiveDate::from_ymd(y, m, d).and_hms(h, min, 0));
+
+        assert_eq!(calculate("-7d@d"), at(2022, 3, 24, 0, 0));
+        assert_eq!(calculate("@mon+3d"), at(2022, 3, 4, 0, 0));
+        assert_eq!(
+            calculate("-24h"),
+            at(2022, 3, 30, 14, 35).map(|t| t + Duration::seconds(20))
+        );
+        assert_eq!(calculate("@h-30m"), at(2022, 3, 31, 13, 30));
+        assert_eq!(calculate("@w"), at(2022, 3, 27, 0, 0));
+        assert_eq!(calculate("@w1"), at(2022, 3, 28, 0, 0));
+        assert_eq!(calculate("-mon@q"), at(2022, 1, 1, 0, 0));
+        assert_eq!(calculate("-1y@y"), at(2021, 1, 1, 0, 0));
+        assert_eq!(calculate("-999999999y"), None);
+    }
+
+    #[test]
+    fn test_uses_time() {
+        assert!(!parse("-7d@w1").unwrap().1.uses_time());
+        assert!(parse("@d+8h").unwrap().1.uses_time());
+    }
+
+    #[test]
+    fn test_display() {
+        for (input, expected) in &[
+            ("-7d@d", "-7d@d"),
+            ("@mon+3days", "@mon+3d"),
+            ("-mon@w7", "-1mon@w0"),
+            ("-2hours@h", "-2h@h"),
+        ] {
+            assert_eq!(parse(input).unwrap().1.to_string(), *expected);
+        }
+    }
+
+    #[test]
+    fn test_rejects() {
+        assert!(parse("-7x").is_err());
+        assert!(parse("@w8").is_err());
+        assert!(parse("7d").is_err());
+    }
+}
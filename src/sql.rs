@@ -0,0 +1,207 @@
+//! SQL intervals, like the `INTERVAL '2 months 3 days'` of a Postgres query, read as the
+//! periods they add up to, and differences written back as intervals.
+
+use crate::{parser_utils::*, PeriodOp, PeriodOps, Unit};
+#[cfg(feature = "cli")]
+use crate::{syntax::Form, ComputeOutcome, Period};
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{char, one_of, satisfy, space0, space1},
+    combinator::{map, not, opt, value},
+    multi::separated_list1,
+    sequence::{delimited, pair, preceded, separated_pair, terminated},
+    IResult,
+};
+
+/// SQL intervals, as `--help-syntax` lists them.
+#[cfg(feature = "cli")]
+pub(crate) const SYNTAX: &[Form] = &[Form {
+    pattern: "<date> (+|-) INTERVAL '[(+|-)]<count> <unit>...'",
+    description: "a SQL interval in years, months, weeks, and days, as in Postgres",
+    examples: &[
+        "2024-01-31 + INTERVAL '1 month'",
+        "today - interval '1 year 2 mons'",
+    ],
+}];
+
+/// Parses "+ INTERVAL '<count> <unit> ...'" or its subtraction, e.g. "- interval '1 year
+/// -2 days'", into one operation per part. A part's own sign turns the operation around.
+/// Hours, minutes, and seconds aren't accepted, since dates have no time.
+pub(crate) fn parse_operations(input: &str) -> IResult<&str, PeriodOps> {
+    map(
+        pair(
+            terminated(one_of("+-"), space0),
+            preceded(
+                pair(alt((tag("INTERVAL"), tag("interval"))), space1),
+                delimited(
+                    pair(char('\''), space0),
+                    separated_list1(
+                        space1,
+                        pair(
+                            opt(one_of("+-")),
+                            separated_pair(parse_digits, space1, parse_unit),
+                        ),
+                    ),
+                    pair(space0, char('\'')),
+                ),
+            ),
+        ),
+        |(sign, parts)| {
+            parts
+                .into_iter()
+                .map(|(part_sign, (count, unit))| {
+                    let period = unit.of(count);
+
+                    if (sign == '-') != (part_sign == Some('-')) {
+                        PeriodOp::Subtract(period)
+                    } else {
+                        PeriodOp::Add(period)
+                    }
+                })
+                .collect()
+        },
+    )(input)
+}
+
+fn parse_unit(input: &str) -> IResult<&str, Unit> {
+    terminated(
+        alt((
+            value(Unit::Year, alt((tag("years"), tag("year")))),
+            value(Unit::Year, alt((tag("yrs"), tag("yr"), tag("y")))),
+            value(Unit::Month, alt((tag("months"), tag("month")))),
+            value(Unit::Month, alt((tag("mons"), tag("mon")))),
+            value(Unit::Week, alt((tag("weeks"), tag("week"), tag("w")))),
+            value(Unit::Day, alt((tag("days"), tag("day"), tag("d")))),
+        )),
+        not(satisfy(char::is_alphanumeric)),
+    )(input)
+}
+
+/// A difference as a Postgres interval literal, e.g. "'2 mons 5 days'::interval", or `None`
+/// for anything that isn't a length of time. Weeks are written as days, as Postgres does.
+#[cfg(feature = "cli")]
+pub(crate) fn interval(outcome: &ComputeOutcome) -> Option<String> {
+    let mut parts = vec![];
+    let mut days = 0;
+    let mut time = None;
+
+    match outcome {
+        ComputeOutcome::DifferenceInDays(count) => days = *count as i64,
+        ComputeOutcome::DifferenceInYears(count) => parts.push(part(*count as i64, "year")),
+        ComputeOutcome::Duration(periods) => {
+            for period in periods {
+                match period {
+                    Period::Year(count) => parts.push(part(*count as i64, "year")),
+                    Period::Month(count) => parts.push(part(*count as i64, "mon")),
+                    Period::Week(count) => days += *count as i64 * 7,
+                    Period::Day(count) => days += *count as i64,
+                    _ => return None,
+                }
+            }
+        }
+        ComputeOutcome::TimeSpan(seconds) => {
+            let remainder = seconds % 86_400;
+
+            days = seconds / 86_400;
+            time = Some(format!(
+                "{}{:02}:{:02}:{:02}",
+                if remainder < 0 { "-" } else { "" },
+                remainder.abs() / 3600,
+                remainder.abs() % 3600 / 60,
+                remainder.abs() % 60
+            ))
+            .filter(|_| remainder != 0);
+        }
+        _ => return None,
+    }
+
+    if days != 0 {
+        parts.push(part(days, "day"));
+    }
+
+    parts.extend(time);
+
+    if parts.is_empty() {
+        parts.push("00:00:00".to_string());
+    }
+
+    Some(format!("'{}'::interval", parts.join(" ")))
+}
+
+#[cfg(feature = "cli")]
+fn part(count: i64, unit: &str) -> String {
+    if count == 1 {
+        format!("1 {}", unit)
+    } else {
+        format!("{} {}s", count, unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Period;
+    use smallvec::smallvec;
+
+    #[test]
+    fn test_parse_operations() {
+        assert_eq!(
+            parse_operations("+ INTERVAL '1 month'"),
+            Ok(("", smallvec![PeriodOp::Add(Period::Month(1))]))
+        );
+        assert_eq!(
+            parse_operations("- interval '2 mons 3 days'"),
+            Ok((
+                "",
+                smallvec![
+                    PeriodOp::Subtract(Period::Month(2)),
+                    PeriodOp::Subtract(Period::Day(3))
+                ]
+            ))
+        );
+        assert_eq!(
+            parse_operations("+ INTERVAL '1 year -2 d'"),
+            Ok((
+                "",
+                smallvec![
+                    PeriodOp::Add(Period::Year(1)),
+                    PeriodOp::Subtract(Period::Day(2))
+                ]
+            ))
+        );
+    }
+
+    #[test]
+    fn test_rejects() {
+        assert!(parse_operations("+ INTERVAL '4 hours'").is_err());
+        assert!(parse_operations("+ INTERVAL 1 month").is_err());
+        assert!(parse_operations("+ INTERVAL '1 monthly'").is_err());
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_interval() {
+        assert_eq!(
+            interval(&ComputeOutcome::Duration(vec![
+                Period::Month(2),
+                Period::Week(1),
+                Period::Day(5)
+            ])),
+            Some("'2 mons 12 days'::interval".to_string())
+        );
+        assert_eq!(
+            interval(&ComputeOutcome::DifferenceInDays(1)),
+            Some("'1 day'::interval".to_string())
+        );
+        assert_eq!(
+            interval(&ComputeOutcome::TimeSpan(-93_600)),
+            Some("'-1 days -02:00:00'::interval".to_string())
+        );
+        assert_eq!(
+            interval(&ComputeOutcome::Duration(vec![])),
+            Some("'00:00:00'::interval".to_string())
+        );
+        assert_eq!(interval(&ComputeOutcome::Boolean(true)), None);
+    }
+}
@@ -0,0 +1,88 @@
+//! A description of the expression grammar, printed by `--help-syntax`. Each module
+//! describes the forms its parser accepts alongside it, and every example is checked
+//! against the parser, so the description can't drift from what's accepted.
+
+use crate::{
+    calculated_date, cron, elasticsearch, grafana, jql, month_days, period, predicate, splunk, sql,
+};
+
+pub(crate) struct Form {
+    pub(crate) pattern: &'static str,
+    pub(crate) description: &'static str,
+    pub(crate) examples: &'static [&'static str],
+}
+
+pub(crate) struct Section {
+    pub(crate) title: &'static str,
+    /// The forms of each module in the section, in order
+    pub(crate) forms: &'static [&'static [Form]],
+}
+
+pub(crate) const GRAMMAR: &[Section] = &[
+    Section {
+        title: "Expressions",
+        forms: &[
+            crate::SYNTAX,
+            sql::SYNTAX,
+            predicate::SYNTAX,
+            cron::SYNTAX,
+            month_days::SYNTAX,
+            elasticsearch::SYNTAX,
+            splunk::SYNTAX,
+            grafana::SYNTAX,
+        ],
+    },
+    Section {
+        title: "Dates",
+        forms: &[calculated_date::SYNTAX, jql::SYNTAX],
+    },
+    Section {
+        title: "Periods",
+        forms: &[period::SYNTAX],
+    },
+];
+
+/// The grammar as text, one form per entry with its examples.
+pub(crate) fn render() -> String {
+    let mut output = String::new();
+
+    for section in GRAMMAR {
+        output.push_str(&format!("{}:\n", section.title));
+
+        for form in section.forms.iter().copied().flatten() {
+            output.push_str(&format!("  {}\n      {}\n", form.pattern, form.description));
+
+            for example in form.examples {
+                output.push_str(&format!("      e.g. {}\n", example));
+            }
+        }
+
+        output.push('\n');
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+    use nom::combinator::all_consuming;
+
+    #[test]
+    fn test_examples_parse() {
+        for section in GRAMMAR {
+            for form in section.forms.iter().copied().flatten() {
+                for example in form.examples {
+                    let parsed = match section.title {
+                        "Dates" => all_consuming(calculated_date::parse)(example).is_ok(),
+                        "Periods" => all_consuming(period::parse)(example).is_ok(),
+                        _ => all_consuming(parse)(example).is_ok(),
+                    };
+
+                    assert!(parsed, "'{}' doesn't parse as {}", example, form.pattern);
+                }
+            }
+        }
+    }
+}
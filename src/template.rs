@@ -0,0 +1,149 @@
+//! Output templates like "Result: {date} ({weekday}, week {iso_week})", for shaping
+//! results without post-processing them.
+
+use crate::date_utils;
+use chrono::{Datelike, NaiveDate};
+use std::str::FromStr;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Template(Vec<Piece>);
+
+#[derive(Clone, Debug, PartialEq)]
+enum Piece {
+    Text(String),
+    Placeholder(Placeholder),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Placeholder {
+    Date,
+    Weekday,
+    IsoWeek,
+    DayOfYear,
+    Epoch,
+    Diff,
+}
+
+/// What a template is filled in with. The epoch and difference depend on flags like
+/// `--zone`, so they're worked out by the caller.
+pub(crate) struct Values<'a> {
+    pub(crate) date: NaiveDate,
+    pub(crate) epoch: i64,
+    pub(crate) diff: &'a str,
+}
+
+impl Template {
+    pub(crate) fn render(&self, values: &Values) -> String {
+        self.0
+            .iter()
+            .map(|piece| match piece {
+                Piece::Text(text) => text.clone(),
+                Piece::Placeholder(Placeholder::Date) => values.date.to_string(),
+                Piece::Placeholder(Placeholder::Weekday) => {
+                    date_utils::weekday_name(values.date.weekday()).to_string()
+                }
+                Piece::Placeholder(Placeholder::IsoWeek) => {
+                    values.date.iso_week().week().to_string()
+                }
+                Piece::Placeholder(Placeholder::DayOfYear) => values.date.ordinal().to_string(),
+                Piece::Placeholder(Placeholder::Epoch) => values.epoch.to_string(),
+                Piece::Placeholder(Placeholder::Diff) => values.diff.to_string(),
+            })
+            .collect()
+    }
+}
+
+impl FromStr for Placeholder {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "date" => Ok(Placeholder::Date),
+            "weekday" => Ok(Placeholder::Weekday),
+            "iso_week" | "week" => Ok(Placeholder::IsoWeek),
+            "doy" => Ok(Placeholder::DayOfYear),
+            "epoch" => Ok(Placeholder::Epoch),
+            "diff" => Ok(Placeholder::Diff),
+            _ => Err(format!(
+                "unknown placeholder '{{{}}}'; expected date, weekday, iso_week, doy, epoch, \
+                 or diff",
+                value
+            )),
+        }
+    }
+}
+
+/// Placeholders are names in braces; `{{` and `}}` stand for literal braces.
+impl FromStr for Template {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut pieces = vec![];
+        let mut text = String::new();
+        let mut chars = value.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(format!("unclosed '{{' in template: '{}'", value)),
+                        }
+                    }
+
+                    pieces.push(Piece::Text(std::mem::take(&mut text)));
+                    pieces.push(Piece::Placeholder(name.trim().parse()?));
+                }
+                '}' => return Err(format!("unmatched '}}' in template: '{}'", value)),
+                c => text.push(c),
+            }
+        }
+
+        pieces.push(Piece::Text(text));
+        pieces.retain(|piece| *piece != Piece::Text(String::new()));
+
+        Ok(Template(pieces))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let template: Template =
+            "Result: {date} ({weekday}, week {week}, day {doy}) {{{epoch}}} {diff}"
+                .parse()
+                .unwrap();
+        let values = Values {
+            date: NaiveDate::from_ymd(2022, 1, 15),
+            epoch: 1_642_204_800,
+            diff: "in 14 days",
+        };
+
+        assert_eq!(
+            template.render(&values),
+            "Result: 2022-01-15 (Saturday, week 2, day 15) {1642204800} in 14 days"
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!("{month}".parse::<Template>().is_err());
+        assert!("{date".parse::<Template>().is_err());
+        assert!("date}".parse::<Template>().is_err());
+    }
+}
@@ -0,0 +1,366 @@
+//! A lexing layer over expressions, independent of the parser, for syntax highlighters
+//! and autocomplete engines that work with words rather than a full parse.
+
+use crate::parser_utils::parse_day_ordinal;
+use chrono::Weekday;
+use nom::{
+    branch::alt,
+    bytes::complete::{is_a, take_while1},
+    character::complete::{digit1, multispace1},
+    combinator::{map, recognize, value},
+    sequence::tuple,
+    IResult,
+};
+use std::ops::Range;
+
+/// Words with a fixed meaning in the grammar.
+pub(crate) const KEYWORDS: &[&str] = &[
+    "a",
+    "ad",
+    "after",
+    "age",
+    "ago",
+    "an",
+    "and",
+    "anniversary",
+    "at",
+    "bc",
+    "bce",
+    "before",
+    "beginning",
+    "between",
+    "billing",
+    "bom",
+    "bow",
+    "boy",
+    "business",
+    "ce",
+    "compare",
+    "couple",
+    "cycle",
+    "cycles",
+    "day",
+    "days",
+    "earlier",
+    "elapsed",
+    "end",
+    "eom",
+    "eow",
+    "eoy",
+    "every",
+    "few",
+    "first",
+    "for",
+    "fraction",
+    "from",
+    "half",
+    "hour",
+    "hours",
+    "in",
+    "interval",
+    "into",
+    "is",
+    "last",
+    "left",
+    "list",
+    "minute",
+    "minutes",
+    "month",
+    "months",
+    "next",
+    "now",
+    "of",
+    "on",
+    "or",
+    "percent",
+    "percentage",
+    "quarter",
+    "remaining",
+    "serial",
+    "sprint",
+    "sprints",
+    "start",
+    "term",
+    "the",
+    "this",
+    "today",
+    "tomorrow",
+    "vs",
+    "week",
+    "weekday",
+    "weekdays",
+    "weekend",
+    "weeks",
+    "year",
+    "years",
+    "yesterday",
+];
+
+/// Units of time, a subset of the keywords.
+pub(crate) const UNITS: &[&str] = &[
+    "day", "days", "week", "weeks", "month", "months", "quarter", "year", "years", "sprint",
+    "sprints", "hour", "hours", "minute", "minutes",
+];
+
+const NUMBER_WORDS: &[&str] = &[
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten", "eleven",
+    "twelve",
+];
+
+const MONTHS: &[&str] = &[
+    "jan",
+    "january",
+    "feb",
+    "february",
+    "mar",
+    "march",
+    "apr",
+    "april",
+    "may",
+    "jun",
+    "june",
+    "jul",
+    "july",
+    "aug",
+    "august",
+    "sep",
+    "sept",
+    "september",
+    "oct",
+    "october",
+    "nov",
+    "november",
+    "dec",
+    "december",
+];
+
+const WEEKDAYS: &[&str] = &[
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenKind {
+    Keyword,
+    Unit,
+    Number,
+    /// A day of the month, e.g. "31st"
+    Ordinal,
+    Month,
+    Weekday,
+    /// A date written in full, e.g. "2022-03-01"
+    Date,
+    /// "+" or "-"
+    Operator,
+    Punctuation,
+    /// A word the grammar doesn't know
+    Word,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+    /// The byte range of the token within the input.
+    pub span: Range<usize>,
+}
+
+/// Splits an expression into tokens, skipping whitespace. Every character of the input
+/// belongs to some token, so tokenizing never fails.
+pub fn tokenize(input: &str) -> Vec<Token<'_>> {
+    let mut tokens = vec![];
+    let mut rest = input;
+
+    while !rest.is_empty() {
+        let start = input.len() - rest.len();
+
+        if let Ok((remaining, _)) = multispace1::<_, ()>(rest) {
+            rest = remaining;
+            continue;
+        }
+
+        let (remaining, kind) = token(rest).unwrap_or_else(|_| {
+            let width = rest.chars().next().map_or(1, char::len_utf8);
+            (&rest[width..], TokenKind::Word)
+        });
+        let end = input.len() - remaining.len();
+
+        tokens.push(Token {
+            kind,
+            text: &input[start..end],
+            span: start..end,
+        });
+        rest = remaining;
+    }
+
+    tokens
+}
+
+fn token(input: &str) -> IResult<&str, TokenKind> {
+    alt((
+        value(
+            TokenKind::Date,
+            recognize(tuple((digit1, is_a("-/"), digit1, is_a("-/"), digit1))),
+        ),
+        value(TokenKind::Ordinal, recognize(parse_day_ordinal)),
+        value(TokenKind::Number, digit1),
+        value(TokenKind::Operator, is_a("+-")),
+        value(TokenKind::Punctuation, is_a(",.'*/")),
+        map(
+            take_while1(|c: char| c.is_alphanumeric() || c == '_'),
+            classify,
+        ),
+    ))(input)
+}
+
+pub(crate) fn classify(word: &str) -> TokenKind {
+    let lower = word.to_lowercase();
+    let lower = lower.as_str();
+
+    if UNITS.contains(&lower) {
+        TokenKind::Unit
+    } else if KEYWORDS.contains(&lower) {
+        TokenKind::Keyword
+    } else if NUMBER_WORDS.contains(&lower) {
+        TokenKind::Number
+    } else if MONTHS.contains(&lower) {
+        TokenKind::Month
+    } else if lower.parse::<Weekday>().is_ok() {
+        TokenKind::Weekday
+    } else {
+        TokenKind::Word
+    }
+}
+
+/// The first word of `input` the grammar doesn't know, paired with the closest word it
+/// does, e.g. "wekks" and "weeks".
+pub fn did_you_mean(input: &str) -> Option<(&str, &'static str)> {
+    tokenize(input)
+        .into_iter()
+        .filter(|token| token.kind == TokenKind::Word)
+        .find_map(|token| closest_known(token.text).map(|known| (token.text, known)))
+}
+
+/// The first unit in `input` finer than a minute, like the "seconds" of "30 seconds ago".
+/// Clock time is kept to the minute, so these are never part of an expression.
+#[cfg(feature = "cli")]
+pub(crate) fn time_unit(input: &str) -> Option<&str> {
+    tokenize(input)
+        .into_iter()
+        .map(|token| token.text)
+        .find(|text| ["second", "seconds"].contains(&text.to_lowercase().as_str()))
+}
+
+/// The known word closest to `word`, if any is close enough to be a likely typo.
+pub(crate) fn closest_known(word: &str) -> Option<&'static str> {
+    let word = word.to_lowercase();
+    let (distance, known) = KEYWORDS
+        .iter()
+        .chain(NUMBER_WORDS)
+        .chain(MONTHS)
+        .chain(WEEKDAYS)
+        .map(|known| (edit_distance(&word, known), *known))
+        .min()?;
+
+    // Allow roughly one typo per three letters, so short words don't match everything.
+    if distance <= (word.chars().count() / 3).max(1) {
+        Some(known)
+    } else {
+        None
+    }
+}
+
+fn edit_distance(from: &str, to: &str) -> usize {
+    let to: Vec<char> = to.chars().collect();
+    let mut previous: Vec<usize> = (0..=to.len()).collect();
+
+    for (i, a) in from.chars().enumerate() {
+        let mut current = vec![i + 1];
+
+        for (j, b) in to.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != *b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+
+        previous = current;
+    }
+
+    previous[to.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(input: &str) -> Vec<(TokenKind, &str)> {
+        tokenize(input)
+            .into_iter()
+            .map(|token| (token.kind, token.text))
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            kinds("Mar 31, 2021 + 2 weeks - three days"),
+            vec![
+                (TokenKind::Month, "Mar"),
+                (TokenKind::Number, "31"),
+                (TokenKind::Punctuation, ","),
+                (TokenKind::Number, "2021"),
+                (TokenKind::Operator, "+"),
+                (TokenKind::Number, "2"),
+                (TokenKind::Unit, "weeks"),
+                (TokenKind::Operator, "-"),
+                (TokenKind::Number, "three"),
+                (TokenKind::Unit, "days"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_keywords_and_dates() {
+        assert_eq!(
+            kinds("next 31st after 2022-03-01 on friday"),
+            vec![
+                (TokenKind::Keyword, "next"),
+                (TokenKind::Ordinal, "31st"),
+                (TokenKind::Keyword, "after"),
+                (TokenKind::Date, "2022-03-01"),
+                (TokenKind::Keyword, "on"),
+                (TokenKind::Weekday, "friday"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_did_you_mean() {
+        assert_eq!(did_you_mean("2 wekks ago"), Some(("wekks", "weeks")));
+        assert_eq!(did_you_mean("tomorow"), Some(("tomorow", "tomorrow")));
+        assert_eq!(did_you_mean("next fridya"), Some(("fridya", "friday")));
+        assert_eq!(did_you_mean("2 weeks ago"), None);
+        assert_eq!(did_you_mean("2 xyzzy"), None);
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_time_unit() {
+        assert_eq!(time_unit("today + 30 Seconds"), Some("Seconds"));
+        assert_eq!(time_unit("today + half an hour"), None);
+        assert_eq!(time_unit("half a year"), None);
+    }
+
+    #[test]
+    fn test_spans() {
+        let tokens = tokenize("2 wekks  ago");
+
+        assert_eq!(tokens[1].kind, TokenKind::Word);
+        assert_eq!(tokens[1].span, 2..7);
+        assert_eq!(tokens[2].span, 9..12);
+    }
+}
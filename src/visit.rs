@@ -0,0 +1,259 @@
+//! Traversals over parsed expressions, for tools that analyze or rewrite them without
+//! matching on every variant themselves.
+//!
+//! Each trait method defaults to walking into the node's children, so implementations
+//! override only the nodes they care about and call the matching `walk_` or `fold_`
+//! function to keep descending.
+
+use crate::{CalculatedDate, DateMath, PeriodOp, Predicate};
+
+/// Inspects an expression and everything nested within it.
+pub trait Visit {
+    fn visit_date_math(&mut self, math: &DateMath) {
+        walk_date_math(self, math)
+    }
+
+    fn visit_calculated_date(&mut self, date: &CalculatedDate) {
+        walk_calculated_date(self, date)
+    }
+
+    /// Called for every operation, including the leading period of `DateMath::Periods`,
+    /// which is visited as an addition.
+    fn visit_period_op(&mut self, _op: &PeriodOp) {}
+
+    fn visit_predicate(&mut self, predicate: &Predicate) {
+        walk_predicate(self, predicate)
+    }
+}
+
+pub fn walk_date_math<V: Visit + ?Sized>(visitor: &mut V, math: &DateMath) {
+    match math {
+        DateMath::Periods(base, rest) => {
+            visitor.visit_period_op(&PeriodOp::Add(*base));
+            rest.iter().for_each(|op| visitor.visit_period_op(op));
+        }
+        DateMath::Start(date)
+        | DateMath::Schedule(_, date, _)
+        | DateMath::Age(date, _)
+        | DateMath::Elapsed(_, date) => visitor.visit_calculated_date(date),
+        DateMath::StartWithPeriods(date, base, rest) => {
+            visitor.visit_calculated_date(date);
+            visitor.visit_period_op(base);
+            rest.iter().for_each(|op| visitor.visit_period_op(op));
+        }
+        DateMath::DateDiff(from, to) => {
+            visitor.visit_calculated_date(from);
+            visitor.visit_calculated_date(to);
+        }
+        DateMath::WeekdayOf(math)
+        | DateMath::DiffIn(math, _)
+        | DateMath::Convert(math, _)
+        | DateMath::DaysIn(math) => visitor.visit_date_math(math),
+        DateMath::Compare(first, second) | DateMath::DaysBetween(_, first, second) => {
+            visitor.visit_date_math(first);
+            visitor.visit_date_math(second);
+        }
+        DateMath::Is(subject, predicate) => {
+            visitor.visit_date_math(subject);
+            visitor.visit_predicate(predicate);
+        }
+        DateMath::DaysLeft(_)
+        | DateMath::DaysRemainingInCycle
+        | DateMath::FractionOfCycleRemaining
+        | DateMath::MonthDays(_, _)
+        | DateMath::Splunk(_)
+        | DateMath::TimeRange(_) => {}
+    }
+}
+
+pub fn walk_calculated_date<V: Visit + ?Sized>(visitor: &mut V, date: &CalculatedDate) {
+    match date {
+        CalculatedDate::FirstBusinessDayAfter(date)
+        | CalculatedDate::LastBusinessDayBefore(date)
+        | CalculatedDate::Settlement(_, date) => visitor.visit_calculated_date(date),
+        _ => {}
+    }
+}
+
+pub fn walk_predicate<V: Visit + ?Sized>(visitor: &mut V, predicate: &Predicate) {
+    for math in predicate.expressions() {
+        visitor.visit_date_math(math);
+    }
+}
+
+/// Rebuilds an expression, letting each node be replaced on the way.
+pub trait Fold {
+    fn fold_date_math(&mut self, math: DateMath) -> DateMath {
+        fold_date_math(self, math)
+    }
+
+    fn fold_calculated_date(&mut self, date: CalculatedDate) -> CalculatedDate {
+        fold_calculated_date(self, date)
+    }
+
+    /// Called for every operation, including the leading period of `DateMath::Periods`,
+    /// which is folded as an addition.
+    fn fold_period_op(&mut self, op: PeriodOp) -> PeriodOp {
+        op
+    }
+
+    fn fold_predicate(&mut self, predicate: Predicate) -> Predicate {
+        fold_predicate(self, predicate)
+    }
+}
+
+pub fn fold_date_math<F: Fold + ?Sized>(folder: &mut F, math: DateMath) -> DateMath {
+    match math {
+        DateMath::Periods(base, rest) => {
+            let first = folder.fold_period_op(PeriodOp::Add(base));
+            let rest = rest.into_iter().map(|op| folder.fold_period_op(op));
+
+            // A leading period folded into a subtraction no longer reads as one.
+            match first {
+                PeriodOp::Add(base) => DateMath::Periods(base, rest.collect()),
+                first => DateMath::StartWithPeriods(CalculatedDate::Today, first, rest.collect()),
+            }
+        }
+        DateMath::Start(date) => DateMath::Start(folder.fold_calculated_date(date)),
+        DateMath::StartWithPeriods(date, base, rest) => DateMath::StartWithPeriods(
+            folder.fold_calculated_date(date),
+            folder.fold_period_op(base),
+            rest.into_iter()
+                .map(|op| folder.fold_period_op(op))
+                .collect(),
+        ),
+        DateMath::DateDiff(from, to) => DateMath::DateDiff(
+            folder.fold_calculated_date(from),
+            folder.fold_calculated_date(to),
+        ),
+        DateMath::Schedule(schedule, date, count) => {
+            DateMath::Schedule(schedule, folder.fold_calculated_date(date), count)
+        }
+        DateMath::Age(on, born) => DateMath::Age(folder.fold_calculated_date(on), born),
+        DateMath::WeekdayOf(math) => DateMath::WeekdayOf(Box::new(folder.fold_date_math(*math))),
+        DateMath::Compare(first, second) => DateMath::Compare(
+            Box::new(folder.fold_date_math(*first)),
+            Box::new(folder.fold_date_math(*second)),
+        ),
+        DateMath::DaysBetween(kind, first, second) => DateMath::DaysBetween(
+            kind,
+            Box::new(folder.fold_date_math(*first)),
+            Box::new(folder.fold_date_math(*second)),
+        ),
+        DateMath::Is(subject, predicate) => DateMath::Is(
+            Box::new(folder.fold_date_math(*subject)),
+            Box::new(folder.fold_predicate(*predicate)),
+        ),
+        DateMath::Elapsed(span, date) => DateMath::Elapsed(span, folder.fold_calculated_date(date)),
+        DateMath::DaysLeft(span) => DateMath::DaysLeft(span),
+        DateMath::DaysRemainingInCycle => DateMath::DaysRemainingInCycle,
+        DateMath::FractionOfCycleRemaining => DateMath::FractionOfCycleRemaining,
+        DateMath::MonthDays(days, months) => DateMath::MonthDays(days, months),
+        DateMath::Splunk(time) => DateMath::Splunk(time),
+        DateMath::TimeRange(range) => DateMath::TimeRange(range),
+        DateMath::DiffIn(math, format) => {
+            DateMath::DiffIn(Box::new(folder.fold_date_math(*math)), format)
+        }
+        DateMath::Convert(math, conversion) => {
+            DateMath::Convert(Box::new(folder.fold_date_math(*math)), conversion)
+        }
+        DateMath::DaysIn(math) => DateMath::DaysIn(Box::new(folder.fold_date_math(*math))),
+    }
+}
+
+pub fn fold_calculated_date<F: Fold + ?Sized>(
+    folder: &mut F,
+    date: CalculatedDate,
+) -> CalculatedDate {
+    match date {
+        CalculatedDate::FirstBusinessDayAfter(date) => {
+            CalculatedDate::FirstBusinessDayAfter(Box::new(folder.fold_calculated_date(*date)))
+        }
+        CalculatedDate::LastBusinessDayBefore(date) => {
+            CalculatedDate::LastBusinessDayBefore(Box::new(folder.fold_calculated_date(*date)))
+        }
+        CalculatedDate::Settlement(days, date) => {
+            CalculatedDate::Settlement(days, Box::new(folder.fold_calculated_date(*date)))
+        }
+        date => date,
+    }
+}
+
+pub fn fold_predicate<F: Fold + ?Sized>(folder: &mut F, predicate: Predicate) -> Predicate {
+    predicate.map_expressions(|math| folder.fold_date_math(math))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, Period};
+    use chrono::NaiveDate;
+
+    struct PinToday(NaiveDate);
+
+    impl Fold for PinToday {
+        fn fold_calculated_date(&mut self, date: CalculatedDate) -> CalculatedDate {
+            match date {
+                CalculatedDate::Today => CalculatedDate::Raw(self.0),
+                date => fold_calculated_date(self, date),
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct CountOperations(usize);
+
+    impl Visit for CountOperations {
+        fn visit_period_op(&mut self, _op: &PeriodOp) {
+            self.0 += 1;
+        }
+    }
+
+    #[test]
+    fn test_fold() {
+        let pinned = PinToday(NaiveDate::from_ymd(2022, 3, 1)).fold_date_math(
+            parse("is first business day after today before today + 1 week")
+                .unwrap()
+                .1,
+        );
+
+        assert_eq!(
+            pinned,
+            parse("is first business day after 2022-03-01 before 2022-03-01 + 1 week")
+                .unwrap()
+                .1
+        );
+    }
+
+    #[test]
+    fn test_visit() {
+        let mut count = CountOperations::default();
+        count.visit_date_math(
+            &parse("compare 2 weeks + 1 day vs today - 3 days")
+                .unwrap()
+                .1,
+        );
+
+        assert_eq!(count.0, 3);
+    }
+
+    #[test]
+    fn test_fold_leading_period() {
+        struct Negate;
+
+        impl Fold for Negate {
+            fn fold_period_op(&mut self, op: PeriodOp) -> PeriodOp {
+                PeriodOp::Subtract(op.period())
+            }
+        }
+
+        assert_eq!(
+            Negate.fold_date_math(parse("2 weeks").unwrap().1),
+            DateMath::StartWithPeriods(
+                CalculatedDate::Today,
+                PeriodOp::Subtract(Period::Week(2)),
+                Default::default()
+            )
+        );
+    }
+}